@@ -2,6 +2,8 @@ use std::io::{stdin, stdout, Write};
 
 use psi_parser::prelude::*;
 
+// The AST is only ever printed using `Debug`
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 enum ExprAst {
     Int(i32),
@@ -0,0 +1,34 @@
+use std::{fs, path::PathBuf};
+
+use psi_parser::{prelude::*, rules};
+
+// Writes the EBNF, DOT graph and railroad diagrams of the included JSON and XML parsers
+// to the directory given as the first argument (or `grammar_docs`)
+fn main() -> std::io::Result<()> {
+    let dir = PathBuf::from(
+        std::env::args()
+            .nth(1)
+            .unwrap_or_else(|| "grammar_docs".to_owned()),
+    );
+
+    fs::create_dir_all(&dir)?;
+
+    let grammars = [
+        ("json", Rules::from(rules::JsonRules)),
+        ("xml", Rules::from(rules::XmlRules)),
+    ];
+
+    for (name, rules) in grammars {
+        fs::write(dir.join(format!("{name}.ebnf")), rules.to_ebnf())?;
+        fs::write(dir.join(format!("{name}.dot")), rules.to_dot())?;
+        fs::write(dir.join(format!("{name}.html")), rules.railroad_html())?;
+
+        if let Some(svg) = rules.railroad_svg("start") {
+            fs::write(dir.join(format!("{name}.svg")), svg)?;
+        }
+
+        println!("Wrote the {name} grammar to {}", dir.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use std::{
+    any::{type_name, Any},
+    error::Error,
+    fmt::Display,
+};
+
+/// User state that transformers can access while parsing,
+/// given using [`Rules::parse_with_context`](crate::rule::Rules::parse_with_context)
+/// or [`ParseOptions::context`](crate::options::ParseOptions::context).
+///
+/// The parser takes a [`checkpoint`](ParseContext::checkpoint) before each transformer or predicate that uses the context,
+/// and [rolls back](ParseContext::rollback) to it when it backtracks past that transformer,
+/// so the context only holds changes made while parsing the accepted derivation.
+///
+/// This is implemented for every `Clone + 'static` type by cloning the whole context at each checkpoint.
+/// Contexts which are large or can't be cloned, such as arenas, can implement it themselves,
+/// for example by keeping an undo log and using its length as the checkpoint.
+pub trait ParseContext: Any {
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// Returns what [`rollback`](ParseContext::rollback) needs to undo the changes made after this call.
+    fn checkpoint(&mut self) -> Box<dyn Any>;
+
+    /// Undoes every change made since `checkpoint` was taken.
+    /// The checkpoints taken after it are dropped without being rolled back.
+    fn rollback(&mut self, checkpoint: Box<dyn Any>);
+
+    /// Called with the first checkpoint of a successful parse, as none of the changes since will be rolled back.
+    fn commit(&mut self, _checkpoint: Box<dyn Any>) {}
+}
+
+impl<T: Clone + Any> ParseContext for T {
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn checkpoint(&mut self) -> Box<dyn Any> {
+        Box::new(self.clone())
+    }
+
+    fn rollback(&mut self, checkpoint: Box<dyn Any>) {
+        if let Ok(checkpoint) = checkpoint.downcast::<T>() {
+            *self = *checkpoint;
+        }
+    }
+}
+
+/// Returned (as a transformer error) when a transformer that uses a context
+/// is run without a context of the expected type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextError {
+    pub expected: &'static str,
+}
+
+impl ContextError {
+    pub fn new<C>() -> Self {
+        Self {
+            expected: type_name::<C>(),
+        }
+    }
+}
+
+impl Error for ContextError {}
+
+impl Display for ContextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "Expected a parse context of type '{}'",
+            self.expected
+        ))
+    }
+}
@@ -0,0 +1,361 @@
+use std::borrow::Cow;
+
+use serde::de::{
+    DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, VariantAccess, Visitor,
+};
+
+use super::{
+    text::{forward_to_text, Entries, Seq, TextDeserializer},
+    DeserializeError,
+};
+use crate::{result::Span, rule::SpanTree, rules::grammar::Cst};
+
+/// Deserializes the [`Cst`] of a grammar loaded with [`parse_grammar`](crate::rules::grammar::parse_grammar).
+/// Given the [`SpanTree`] it was parsed with, errors carry the span of the offending node.
+///
+/// A node is a map of its child nodes by rule name (a rule that is repeated is a sequence), or a sequence
+/// of its child nodes. A node of a left recursive rule has the children of its recursion flattened into it,
+/// so lists are sequences. Strings, numbers and booleans are parsed from the text of a node,
+/// and an enum variant is the rule of the first child node.
+pub struct CstDeserializer<'de> {
+    value: CstValue<'de>,
+    tree: Option<&'de SpanTree>,
+    span: Option<Span>,
+}
+
+enum CstValue<'de> {
+    Cst(&'de Cst),
+    // The child nodes with the same rule
+    Group(Vec<CstDeserializer<'de>>),
+}
+
+impl<'de> CstDeserializer<'de> {
+    pub fn new(cst: &'de Cst) -> Self {
+        Self::with_tree(cst, None)
+    }
+
+    pub fn with_spans(cst: &'de Cst, tree: Option<&'de SpanTree>) -> Self {
+        let tree = match cst {
+            // The tree of `start` is that of its node
+            Cst::Node { .. } => tree,
+            Cst::Token(_) => None,
+        };
+
+        Self::with_tree(cst, tree)
+    }
+
+    fn with_tree(cst: &'de Cst, tree: Option<&'de SpanTree>) -> Self {
+        Self {
+            value: CstValue::Cst(cst),
+            tree,
+            span: tree.map(|tree| tree.span),
+        }
+    }
+
+    fn error(&self, message: String) -> DeserializeError {
+        DeserializeError::Invalid {
+            message,
+            span: self.span,
+        }
+    }
+
+    // The child nodes (with the children of left recursion flattened), each with its tree
+    fn children(&self) -> Vec<CstDeserializer<'de>> {
+        let mut found = Vec::new();
+
+        if let CstValue::Cst(cst) = self.value {
+            collect_children(cst, self.tree, &mut found);
+        }
+
+        found
+    }
+
+    fn into_text(self) -> Result<TextDeserializer<'de>, DeserializeError> {
+        let text = match &self.value {
+            CstValue::Cst(Cst::Token(token)) => Cow::Borrowed(token.as_str()),
+            CstValue::Cst(cst) => Cow::Owned(text(cst)),
+            CstValue::Group(group) if group.len() == 1 => {
+                return self.into_single().into_text();
+            }
+            CstValue::Group(group) => {
+                return Err(self.error(format!("Expected text, found {} nodes", group.len())));
+            }
+        };
+
+        Ok(TextDeserializer {
+            text,
+            span: self.span,
+        })
+    }
+
+    // The node of a group of one
+    fn into_single(self) -> Self {
+        match self.value {
+            CstValue::Group(mut group) if group.len() == 1 => group.remove(0),
+            _ => self,
+        }
+    }
+
+    fn into_map(self) -> Entries<CstDeserializer<'de>> {
+        let mut entries: Vec<(String, CstDeserializer)> = Vec::new();
+
+        for child in self.children() {
+            let CstValue::Cst(Cst::Node { rule, .. }) = child.value else {
+                continue;
+            };
+
+            let group = entries
+                .iter_mut()
+                .find(|(key, _)| key == rule)
+                .and_then(|(_, value)| match &mut value.value {
+                    CstValue::Group(group) => Some(group),
+                    CstValue::Cst(_) => None,
+                });
+
+            match group {
+                Some(group) => group.push(child),
+                None => entries.push((
+                    rule.clone(),
+                    Self {
+                        value: CstValue::Group(vec![child]),
+                        tree: None,
+                        span: self.span,
+                    },
+                )),
+            }
+        }
+
+        Entries::new(entries)
+    }
+}
+
+// Tokens are left out. The trees of the children are those with their rule, in order
+fn collect_children<'de>(
+    cst: &'de Cst,
+    tree: Option<&'de SpanTree>,
+    found: &mut Vec<CstDeserializer<'de>>,
+) {
+    let Cst::Node { rule, children } = cst else {
+        return;
+    };
+
+    let mut trees = tree.map_or([].iter(), |tree| tree.children.iter());
+
+    for child in children {
+        let Cst::Node {
+            rule: child_rule, ..
+        } = child
+        else {
+            continue;
+        };
+
+        let child_tree = trees
+            .clone()
+            .position(|tree| &tree.rule == child_rule)
+            .map(|i| trees.nth(i).unwrap());
+
+        if child_rule == rule {
+            collect_children(child, child_tree, found);
+        } else {
+            found.push(CstDeserializer::with_tree(child, child_tree));
+        }
+    }
+}
+
+fn text(cst: &Cst) -> String {
+    match cst {
+        Cst::Token(token) => token.clone(),
+        Cst::Node { children, .. } => children.iter().map(text).collect(),
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeserializeError> for &'de Cst {
+    type Deserializer = CstDeserializer<'de>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        CstDeserializer::new(self)
+    }
+}
+
+impl<'de> Deserializer<'de> for CstDeserializer<'de> {
+    type Error = DeserializeError;
+
+    // A node with child nodes is a map, otherwise its text is a string
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let span = self.span;
+
+        match self.value {
+            CstValue::Cst(Cst::Node { .. }) if !self.children().is_empty() => {
+                visitor.visit_map(self.into_map())
+            }
+            CstValue::Group(group) if group.len() != 1 => visitor.visit_seq(Seq {
+                values: group.into_iter(),
+            }),
+            CstValue::Group(_) => self.into_single().deserialize_any(visitor),
+            _ => self.into_text()?.deserialize_any(visitor),
+        }
+        .map_err(|error: DeserializeError| error.at(span))
+    }
+
+    forward_to_text! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64 deserialize_char deserialize_str deserialize_string
+        deserialize_bytes deserialize_byte_buf deserialize_identifier
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // A group of nodes, or the child nodes of a node
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let span = self.span;
+
+        let values = match self.value {
+            CstValue::Group(group) if group.len() != 1 => group,
+            CstValue::Group(_) => return self.into_single().deserialize_seq(visitor),
+            CstValue::Cst(Cst::Node { .. }) => self.children(),
+            CstValue::Cst(Cst::Token(_)) => return self.deserialize_any(visitor),
+        };
+
+        visitor
+            .visit_seq(Seq {
+                values: values.into_iter(),
+            })
+            .map_err(|error: DeserializeError| error.at(span))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let this = self.into_single();
+        let span = this.span;
+
+        match this.value {
+            CstValue::Cst(Cst::Node { .. }) => visitor.visit_map(this.into_map()),
+            _ => this.deserialize_any(visitor),
+        }
+        .map_err(|error: DeserializeError| error.at(span))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    // The rule of the first child node is the variant, or the text is the name of a unit variant
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let this = self.into_single();
+        let span = this.span;
+        let mut children = this.children();
+
+        if children.is_empty() {
+            this.into_text()?.deserialize_enum(name, variants, visitor)
+        } else {
+            visitor.visit_enum(CstEnum(children.remove(0)))
+        }
+        .map_err(|error: DeserializeError| error.at(span))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+}
+
+struct CstEnum<'de>(CstDeserializer<'de>);
+
+impl<'de> EnumAccess<'de> for CstEnum<'de> {
+    type Error = DeserializeError;
+    type Variant = CstDeserializer<'de>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let CstValue::Cst(Cst::Node { rule, .. }) = self.0.value else {
+            unreachable!("Only nodes are variants")
+        };
+
+        let variant = seed
+            .deserialize(rule.as_str().into_deserializer())
+            .map_err(|error: DeserializeError| error.at(self.0.span))?;
+
+        Ok((variant, self.0))
+    }
+}
+
+impl<'de> VariantAccess<'de> for CstDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+}
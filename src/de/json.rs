@@ -0,0 +1,281 @@
+use serde::de::{
+    value::BorrowedStrDeserializer, DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer,
+    MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+
+use super::DeserializeError;
+use crate::{
+    rule::SpanTree,
+    rules::json::{Json, Map},
+};
+
+/// Deserializes a [`Json`] value. Given the [`SpanTree`] it was parsed with (by [`JsonRules`](crate::rules::JsonRules)),
+/// errors carry the span of the offending value.
+#[derive(Debug, Clone, Copy)]
+pub struct JsonDeserializer<'de> {
+    json: &'de Json,
+    tree: Option<&'de SpanTree>,
+}
+
+impl<'de> JsonDeserializer<'de> {
+    pub fn new(json: &'de Json) -> Self {
+        Self { json, tree: None }
+    }
+
+    pub fn with_spans(json: &'de Json, tree: Option<&'de SpanTree>) -> Self {
+        // The tree of `start` has the tree of `json` inside it
+        let tree = tree.and_then(|tree| match tree.rule.as_str() {
+            "json" => Some(tree),
+            _ => tree.find("json").first().copied(),
+        });
+
+        Self { json, tree }
+    }
+
+    fn span(&self) -> Option<crate::result::Span> {
+        self.tree.map(|tree| tree.span)
+    }
+
+    // The trees of the elements of an array
+    fn elements(&self) -> Vec<Option<&'de SpanTree>> {
+        let len = self.json.as_array().map_or(0, Vec::len);
+
+        match self.tree.map(|tree| tree.find("json")) {
+            Some(trees) if trees.len() == len => trees.into_iter().map(Some).collect(),
+            _ => vec![None; len],
+        }
+    }
+
+    // The trees of the keys and values of the members of an object
+    fn members(&self, object: &Map) -> Vec<(Option<&'de SpanTree>, Option<&'de SpanTree>)> {
+        match self.tree.map(|tree| tree.find("member")) {
+            // With duplicated keys, the members don't line up with the map
+            Some(trees) if trees.len() == object.len() => trees
+                .into_iter()
+                .map(|member| {
+                    (
+                        member.find("string").first().copied(),
+                        member.find("json").first().copied(),
+                    )
+                })
+                .collect(),
+            _ => vec![(None, None); object.len()],
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeserializeError> for &'de Json {
+    type Deserializer = JsonDeserializer<'de>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        JsonDeserializer::new(self)
+    }
+}
+
+impl<'de> Deserializer<'de> for JsonDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let result = match self.json {
+            Json::Null => visitor.visit_unit(),
+            Json::Boolean(boolean) => visitor.visit_bool(*boolean),
+            Json::Number(_) => match self.json.as_i64() {
+                Some(integer) => visitor.visit_i64(integer),
+                None => visitor.visit_f64(self.json.as_f64().unwrap()),
+            },
+            Json::String(string) => visitor.visit_borrowed_str(string),
+            Json::Array(array) => visitor.visit_seq(JsonSeq {
+                values: array.iter().zip(self.elements()).collect(),
+                index: 0,
+            }),
+            Json::Object(object) => visitor.visit_map(JsonMap {
+                members: object.iter().zip(self.members(object)).collect(),
+                index: 0,
+            }),
+        };
+
+        result.map_err(|error: DeserializeError| error.at(self.span()))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.json {
+            Json::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // `"Variant"` or `{"Variant": value}`
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let result = match self.json {
+            Json::String(variant) => visitor.visit_enum(JsonEnum {
+                variant,
+                value: None,
+            }),
+            Json::Object(object) if object.len() == 1 => {
+                let (variant, value) = object.iter().next().unwrap();
+                let (_, tree) = self.members(object)[0];
+
+                visitor.visit_enum(JsonEnum {
+                    variant,
+                    value: Some(JsonDeserializer { json: value, tree }),
+                })
+            }
+            _ => Err(serde::de::Error::custom(format!(
+                "Expected a string or an object with a single member, found {}",
+                self.json.type_name()
+            ))),
+        };
+
+        result.map_err(|error: DeserializeError| error.at(self.span()))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct JsonSeq<'de> {
+    values: Vec<(&'de Json, Option<&'de SpanTree>)>,
+    index: usize,
+}
+
+impl<'de> SeqAccess<'de> for JsonSeq<'de> {
+    type Error = DeserializeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        match self.values.get(self.index) {
+            Some(&(json, tree)) => {
+                self.index += 1;
+                seed.deserialize(JsonDeserializer { json, tree }).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len() - self.index)
+    }
+}
+
+type Member<'de> = (
+    (&'de String, &'de Json),
+    (Option<&'de SpanTree>, Option<&'de SpanTree>),
+);
+
+struct JsonMap<'de> {
+    members: Vec<Member<'de>>,
+    index: usize,
+}
+
+impl<'de> MapAccess<'de> for JsonMap<'de> {
+    type Error = DeserializeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.members.get(self.index) {
+            Some(&((key, _), (tree, _))) => seed
+                .deserialize(key.as_str().into_deserializer())
+                .map(Some)
+                .map_err(|error: DeserializeError| error.at(tree.map(|tree| tree.span))),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let ((_, json), (_, tree)) = self.members[self.index];
+        self.index += 1;
+
+        seed.deserialize(JsonDeserializer { json, tree })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.members.len() - self.index)
+    }
+}
+
+struct JsonEnum<'de> {
+    variant: &'de str,
+    value: Option<JsonDeserializer<'de>>,
+}
+
+impl<'de> EnumAccess<'de> for JsonEnum<'de> {
+    type Error = DeserializeError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let variant = seed.deserialize(BorrowedStrDeserializer::<DeserializeError>::new(
+            self.variant,
+        ))?;
+
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for JsonEnum<'de> {
+    type Error = DeserializeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        match self.value {
+            None
+            | Some(JsonDeserializer {
+                json: Json::Null, ..
+            }) => Ok(()),
+            Some(value) => Err(serde::de::Error::custom(format!(
+                "Expected null, found {}",
+                value.json.type_name()
+            ))),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        seed.deserialize(self.value.unwrap_or(JsonDeserializer::new(&Json::Null)))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value
+            .unwrap_or(JsonDeserializer::new(&Json::Null))
+            .deserialize_seq(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value
+            .unwrap_or(JsonDeserializer::new(&Json::Null))
+            .deserialize_map(visitor)
+    }
+}
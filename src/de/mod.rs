@@ -0,0 +1,147 @@
+//! Deserializing parsed values into Rust types using [serde](https://serde.rs), with [`from_str`].
+//!
+//! Deserializers are provided for [`Json`](crate::rules::json::Json) ([`JsonDeserializer`]),
+//! [`Xml`](crate::rules::simple_xml::Xml) ([`XmlDeserializer`]) and the [`Cst`](crate::rules::grammar::Cst)
+//! of a loaded grammar ([`CstDeserializer`]).
+
+use std::{error::Error, fmt::Display};
+
+use serde::de::DeserializeOwned;
+
+use crate::{
+    input::IntoInput,
+    result::{ParseError, Span},
+    rule::Rules,
+};
+
+#[cfg(feature = "included_parsers")]
+mod text;
+
+#[cfg(feature = "included_parsers")]
+mod json;
+#[cfg(feature = "included_parsers")]
+pub use json::JsonDeserializer;
+
+#[cfg(feature = "included_parsers")]
+mod xml;
+#[cfg(feature = "included_parsers")]
+pub use xml::XmlDeserializer;
+
+#[cfg(feature = "included_parsers")]
+mod cst;
+#[cfg(feature = "included_parsers")]
+pub use cst::CstDeserializer;
+
+#[derive(Debug)]
+pub enum DeserializeError {
+    Parse(ParseError),
+    /// The start rule gave a value that can't be deserialized.
+    UnsupportedValue,
+    /// The value doesn't fit the type, e.g. a string for a number. The span is that of the offending value, if known.
+    Invalid {
+        message: String,
+        span: Option<Span>,
+    },
+}
+
+impl DeserializeError {
+    // The innermost value that failed is the offending one, so a span that is already there is kept
+    #[cfg(feature = "included_parsers")]
+    fn at(self, span: Option<Span>) -> Self {
+        match self {
+            DeserializeError::Invalid {
+                message,
+                span: None,
+            } => DeserializeError::Invalid { message, span },
+            error => error,
+        }
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            DeserializeError::Invalid { span, .. } => *span,
+            _ => None,
+        }
+    }
+}
+
+impl Error for DeserializeError {}
+
+impl Display for DeserializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeserializeError::Parse(error) => Display::fmt(error, f),
+            DeserializeError::UnsupportedValue => {
+                f.write_str("The parsed value can't be deserialized")
+            }
+            DeserializeError::Invalid {
+                message,
+                span: Some(span),
+            } => f.write_fmt(format_args!(
+                "{message} at {}:{}",
+                span.start.line, span.start.column
+            )),
+            DeserializeError::Invalid {
+                message,
+                span: None,
+            } => f.write_str(message),
+        }
+    }
+}
+
+impl serde::de::Error for DeserializeError {
+    fn custom<T: Display>(message: T) -> Self {
+        DeserializeError::Invalid {
+            message: message.to_string(),
+            span: None,
+        }
+    }
+}
+
+impl From<ParseError> for DeserializeError {
+    fn from(error: ParseError) -> Self {
+        DeserializeError::Parse(error)
+    }
+}
+
+/// Parses the entire input with the `start` rule of `rules`, and deserializes the value.
+///
+/// The value can be a [`Json`](crate::rules::json::Json), an [`Xml`](crate::rules::simple_xml::Xml)
+/// or a [`Cst`](crate::rules::grammar::Cst). Errors in deserializing carry the span of the offending value.
+///
+/// ```text
+/// #[derive(Deserialize)]
+/// struct Config {
+///     name: String,
+///     port: u16,
+/// }
+///
+/// let config: Config = psi_parser::from_str(JsonRules, r#"{"name": "psi", "port": 8080}"#)?;
+/// ```
+pub fn from_str<'a, T: DeserializeOwned>(
+    rules: impl Into<Rules>,
+    input: impl IntoInput<'a>,
+) -> Result<T, DeserializeError> {
+    let (value, tree) = rules.into().parse_entire_spanned("start", input)?;
+
+    #[cfg(feature = "included_parsers")]
+    {
+        use crate::rules::{grammar::Cst, json::Json, simple_xml::Xml};
+
+        if let Some(json) = value.downcast_ref::<Json>() {
+            return T::deserialize(JsonDeserializer::with_spans(json, tree.as_ref()));
+        }
+
+        if let Some(xml) = value.downcast_ref::<Xml>() {
+            return T::deserialize(XmlDeserializer::with_spans(xml, tree.as_ref()));
+        }
+
+        if let Some(cst) = value.downcast_ref::<Cst>() {
+            return T::deserialize(CstDeserializer::with_spans(cst, tree.as_ref()));
+        }
+    }
+
+    let _ = (value, tree);
+
+    Err(DeserializeError::UnsupportedValue)
+}
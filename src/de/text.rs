@@ -0,0 +1,196 @@
+use std::borrow::Cow;
+
+use serde::de::{DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+
+use super::DeserializeError;
+use crate::result::Span;
+
+/// Deserializes text, the way it's written in a document: numbers and booleans are parsed from it.
+/// Used for the attributes and text of XML, and the tokens of a CST.
+pub(super) struct TextDeserializer<'de> {
+    pub(super) text: Cow<'de, str>,
+    pub(super) span: Option<Span>,
+}
+
+impl TextDeserializer<'_> {
+    fn parse<T: std::str::FromStr>(&self, expected: &str) -> Result<T, DeserializeError> {
+        self.text.trim().parse().map_err(|_| {
+            DeserializeError::Invalid {
+                message: format!("Expected {expected}, found {:?}", self.text),
+                span: None,
+            }
+            .at(self.span)
+        })
+    }
+}
+
+macro_rules! parse_text {
+    ($($method:ident => $visit:ident($type:ty, $expected:literal)),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                let span = self.span;
+
+                visitor.$visit(self.parse::<$type>($expected)?).map_err(|error: DeserializeError| error.at(span))
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for TextDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let span = self.span;
+
+        match self.text {
+            Cow::Borrowed(text) => visitor.visit_borrowed_str(text),
+            Cow::Owned(text) => visitor.visit_string(text),
+        }
+        .map_err(|error: DeserializeError| error.at(span))
+    }
+
+    parse_text! {
+        deserialize_bool => visit_bool(bool, "a boolean"),
+        deserialize_i8 => visit_i8(i8, "an i8"),
+        deserialize_i16 => visit_i16(i16, "an i16"),
+        deserialize_i32 => visit_i32(i32, "an i32"),
+        deserialize_i64 => visit_i64(i64, "an i64"),
+        deserialize_i128 => visit_i128(i128, "an i128"),
+        deserialize_u8 => visit_u8(u8, "a u8"),
+        deserialize_u16 => visit_u16(u16, "a u16"),
+        deserialize_u32 => visit_u32(u32, "a u32"),
+        deserialize_u64 => visit_u64(u64, "a u64"),
+        deserialize_u128 => visit_u128(u128, "a u128"),
+        deserialize_f32 => visit_f32(f32, "a number"),
+        deserialize_f64 => visit_f64(f64, "a number"),
+        deserialize_char => visit_char(char, "a character"),
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // The text is the name of a unit variant
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let span = self.span;
+        let variant: serde::de::value::StringDeserializer<DeserializeError> =
+            self.text.trim().to_owned().into_deserializer();
+
+        visitor
+            .visit_enum(variant)
+            .map_err(|error: DeserializeError| error.at(span))
+    }
+
+    serde::forward_to_deserialize_any! {
+        str string bytes byte_buf seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// The values of a sequence, each with its own deserializer.
+pub(super) struct Seq<D> {
+    pub(super) values: std::vec::IntoIter<D>,
+}
+
+impl<'de, D: Deserializer<'de, Error = DeserializeError>> SeqAccess<'de> for Seq<D> {
+    type Error = DeserializeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        self.values
+            .next()
+            .map(|value| seed.deserialize(value))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len())
+    }
+}
+
+/// The entries of a map, with string keys.
+pub(super) struct Entries<D> {
+    pub(super) entries: std::vec::IntoIter<(String, D)>,
+    pub(super) value: Option<D>,
+}
+
+impl<D> Entries<D> {
+    pub(super) fn new(entries: Vec<(String, D)>) -> Self {
+        Self {
+            entries: entries.into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de, D: Deserializer<'de, Error = DeserializeError>> MapAccess<'de> for Entries<D> {
+    type Error = DeserializeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(key.as_str().into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        seed.deserialize(
+            self.value
+                .take()
+                .expect("A value should be read after its key"),
+        )
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// Implements the methods of `Deserializer` for primitives by deserializing the text of the value,
+/// from `self.into_text()`.
+macro_rules! forward_to_text {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: serde::de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                self.into_text()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+pub(super) use forward_to_text;
@@ -0,0 +1,391 @@
+use std::borrow::Cow;
+
+use serde::de::{
+    DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, VariantAccess, Visitor,
+};
+
+use super::{
+    text::{forward_to_text, Entries, Seq, TextDeserializer},
+    DeserializeError,
+};
+use crate::{
+    result::Span,
+    rule::SpanTree,
+    rules::simple_xml::{Element, Xml},
+};
+
+/// Deserializes an [`Xml`] value. Given the [`SpanTree`] it was parsed with (by [`XmlRules`](crate::rules::XmlRules)),
+/// errors carry the span of the offending element.
+///
+/// An element is a map of its attributes (with keys like `@name`), its child elements by tag name, and its text
+/// (with the key `$text`). A tag that is repeated is a sequence, and so is a single element for a sequence.
+/// An element with only text can also be a string, or a number or boolean parsed from the text.
+pub struct XmlDeserializer<'de> {
+    value: XmlValue<'de>,
+    tree: Option<&'de SpanTree>,
+    span: Option<Span>,
+}
+
+enum XmlValue<'de> {
+    Xml(&'de Xml),
+    // An attribute, or the text of an element with child elements
+    Text(Cow<'de, str>),
+    // The child elements with the same tag
+    Group(Vec<XmlDeserializer<'de>>),
+}
+
+impl<'de> XmlDeserializer<'de> {
+    pub fn new(xml: &'de Xml) -> Self {
+        Self::with_tree(xml, None)
+    }
+
+    pub fn with_spans(xml: &'de Xml, tree: Option<&'de SpanTree>) -> Self {
+        // The tree of `start` has the tree of the root `element` inside it
+        let tree = tree.and_then(|tree| match tree.rule.as_str() {
+            "element" => Some(tree),
+            _ => tree.find("element").first().copied(),
+        });
+
+        Self::with_tree(xml, tree)
+    }
+
+    fn with_tree(xml: &'de Xml, tree: Option<&'de SpanTree>) -> Self {
+        Self {
+            value: XmlValue::Xml(xml),
+            tree,
+            span: tree.map(|tree| tree.span),
+        }
+    }
+
+    fn text(text: impl Into<Cow<'de, str>>, span: Option<Span>) -> Self {
+        Self {
+            value: XmlValue::Text(text.into()),
+            tree: None,
+            span,
+        }
+    }
+
+    fn error(&self, message: String) -> DeserializeError {
+        DeserializeError::Invalid {
+            message,
+            span: self.span,
+        }
+    }
+
+    // The child elements, each with its tree
+    fn children(&self) -> Vec<XmlDeserializer<'de>> {
+        let XmlValue::Xml(Xml::Element(element)) = self.value else {
+            return Vec::new();
+        };
+
+        let children = element
+            .children
+            .iter()
+            .filter(|child| matches!(child, Xml::Element(_)))
+            .collect::<Vec<_>>();
+
+        let trees = match self.tree.map(|tree| tree.find("element")) {
+            Some(trees) if trees.len() == children.len() => trees.into_iter().map(Some).collect(),
+            _ => vec![None; children.len()],
+        };
+
+        children
+            .into_iter()
+            .zip(trees)
+            .map(|(child, tree)| Self::with_tree(child, tree))
+            .collect()
+    }
+
+    fn into_text(self) -> Result<TextDeserializer<'de>, DeserializeError> {
+        let text = match &self.value {
+            XmlValue::Xml(Xml::Text(text) | Xml::CData(text)) => Cow::Borrowed(text.as_str()),
+            XmlValue::Xml(Xml::Element(element)) => match element.children.as_slice() {
+                [] => Cow::Borrowed(""),
+                [Xml::Text(text) | Xml::CData(text)] => Cow::Borrowed(text.as_str()),
+                _ => {
+                    if let Some(child) = element.elements().next() {
+                        return Err(self
+                            .error(format!("Expected text, found the element <{}>", child.name)));
+                    }
+
+                    Cow::Owned(element.text())
+                }
+            },
+            XmlValue::Xml(_) => Cow::Borrowed(""),
+            XmlValue::Text(text) => text.clone(),
+            XmlValue::Group(group) if group.len() == 1 => {
+                return self.into_single().into_text();
+            }
+            XmlValue::Group(group) => {
+                return Err(self.error(format!("Expected text, found {} elements", group.len())));
+            }
+        };
+
+        Ok(TextDeserializer {
+            text,
+            span: self.span,
+        })
+    }
+
+    // The element of a group of one
+    fn into_single(self) -> Self {
+        match self.value {
+            XmlValue::Group(mut group) if group.len() == 1 => group.remove(0),
+            _ => self,
+        }
+    }
+
+    fn has_elements(&self) -> bool {
+        match self.value {
+            XmlValue::Xml(Xml::Element(element)) => {
+                !element.attributes.is_empty() || element.elements().next().is_some()
+            }
+            _ => false,
+        }
+    }
+
+    fn into_map(self) -> Entries<XmlDeserializer<'de>> {
+        let children = self.children();
+        let XmlValue::Xml(Xml::Element(element)) = self.value else {
+            return Entries::new(Vec::new());
+        };
+
+        let mut entries = element
+            .attributes
+            .iter()
+            .map(|attribute| {
+                (
+                    format!("@{}", attribute.name),
+                    Self::text(attribute.value.as_str(), self.span),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for child in children {
+            let XmlValue::Xml(Xml::Element(Element { name: tag, .. })) = child.value else {
+                continue;
+            };
+
+            let group = entries
+                .iter_mut()
+                .find_map(|(key, value)| match &mut value.value {
+                    XmlValue::Group(group) if key == tag => Some(group),
+                    _ => None,
+                });
+
+            match group {
+                Some(group) => group.push(child),
+                None => entries.push((
+                    tag.clone(),
+                    Self {
+                        value: XmlValue::Group(vec![child]),
+                        tree: None,
+                        span: self.span,
+                    },
+                )),
+            }
+        }
+
+        let text = element.text();
+
+        // The whitespace between the child elements isn't text
+        if !text.trim().is_empty() {
+            entries.push(("$text".to_owned(), Self::text(text, self.span)));
+        }
+
+        Entries::new(entries)
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeserializeError> for &'de Xml {
+    type Deserializer = XmlDeserializer<'de>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        XmlDeserializer::new(self)
+    }
+}
+
+impl<'de> Deserializer<'de> for XmlDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let span = self.span;
+
+        match self.value {
+            XmlValue::Xml(Xml::Element(_)) if self.has_elements() => {
+                visitor.visit_map(self.into_map())
+            }
+            XmlValue::Group(group) if group.len() != 1 => visitor.visit_seq(Seq {
+                values: group.into_iter(),
+            }),
+            XmlValue::Group(_) => self.into_single().deserialize_any(visitor),
+            _ => self.into_text()?.deserialize_any(visitor),
+        }
+        .map_err(|error: DeserializeError| error.at(span))
+    }
+
+    forward_to_text! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64 deserialize_char deserialize_str deserialize_string
+        deserialize_bytes deserialize_byte_buf deserialize_identifier
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // A group of elements, or the child elements of an element
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let span = self.span;
+
+        let values = match self.value {
+            XmlValue::Group(group) => group,
+            XmlValue::Xml(Xml::Element(_)) => self.children(),
+            _ => return self.deserialize_any(visitor),
+        };
+
+        visitor
+            .visit_seq(Seq {
+                values: values.into_iter(),
+            })
+            .map_err(|error: DeserializeError| error.at(span))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let this = self.into_single();
+        let span = this.span;
+
+        match this.value {
+            XmlValue::Xml(Xml::Element(_)) => visitor.visit_map(this.into_map()),
+            _ => this.deserialize_any(visitor),
+        }
+        .map_err(|error: DeserializeError| error.at(span))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    // The text is the name of a unit variant, or the only child element is a variant with its tag
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let this = self.into_single();
+        let span = this.span;
+
+        let mut elements = this.children();
+
+        match elements.len() {
+            0 => this.into_text()?.deserialize_enum(name, variants, visitor),
+            1 => visitor.visit_enum(XmlEnum(elements.remove(0))),
+            _ => Err(this.error(format!(
+                "Expected a single element for {name}, found {}",
+                elements.len()
+            ))),
+        }
+        .map_err(|error: DeserializeError| error.at(span))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+}
+
+struct XmlEnum<'de>(XmlDeserializer<'de>);
+
+impl<'de> EnumAccess<'de> for XmlEnum<'de> {
+    type Error = DeserializeError;
+    type Variant = XmlDeserializer<'de>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let XmlValue::Xml(Xml::Element(Element { name: tag, .. })) = self.0.value else {
+            unreachable!("Only elements are variants")
+        };
+
+        let variant = seed
+            .deserialize(tag.as_str().into_deserializer())
+            .map_err(|error: DeserializeError| error.at(self.0.span))?;
+
+        Ok((variant, self.0))
+    }
+}
+
+impl<'de> VariantAccess<'de> for XmlDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::*;
+
+impl Rules {
+    /// Renders a GraphViz DOT graph with an edge from each rule to every rule it refers to.
+    /// Rules in a namespace (like `ws::ws_ml`) are grouped in a cluster per namespace,
+    /// matchers are drawn as ellipses and rules that don't exist are drawn dashed.
+    pub fn to_dot(&self) -> String {
+        let rule_names = sorted_rule_names(self);
+
+        let mut nodes: BTreeMap<String, NodeKind> = rule_names
+            .iter()
+            .map(|rule_name| (rule_name.clone(), NodeKind::Rule))
+            .collect();
+
+        let mut edges = BTreeSet::new();
+
+        for rule_name in &rule_names {
+            let trees = self.rule_trees(rule_name).unwrap_or_default();
+
+            for (reference, matcher) in references(trees) {
+                let kind = if matcher {
+                    NodeKind::Matcher
+                } else {
+                    NodeKind::Missing
+                };
+
+                nodes.entry(reference.to_owned()).or_insert(kind);
+                edges.insert((rule_name.clone(), reference.to_owned()));
+            }
+        }
+
+        // Namespace -> nodes in it
+        let mut clusters: BTreeMap<&str, Vec<(&str, NodeKind)>> = BTreeMap::new();
+
+        for (name, kind) in &nodes {
+            let namespace = name
+                .rsplit_once("::")
+                .map_or("", |(namespace, _)| namespace);
+
+            clusters
+                .entry(namespace)
+                .or_default()
+                .push((name.as_str(), *kind));
+        }
+
+        let mut dot = String::from("digraph rules {\n    node [shape=box];\n");
+
+        for (namespace, nodes) in clusters {
+            let indent = if namespace.is_empty() {
+                "    "
+            } else {
+                dot.push_str(&format!(
+                    "\n    subgraph {} {{\n        label = {};\n",
+                    dot_id(&format!("cluster_{namespace}")),
+                    dot_id(namespace)
+                ));
+                "        "
+            };
+
+            for (name, kind) in nodes {
+                let label = name.rsplit_once("::").map_or(name, |(_, name)| name);
+
+                let attributes = match kind {
+                    NodeKind::Rule => "",
+                    NodeKind::Matcher => ", shape=ellipse",
+                    NodeKind::Missing => ", style=dashed",
+                };
+
+                dot.push_str(&format!(
+                    "{indent}{} [label={}{attributes}];\n",
+                    dot_id(name),
+                    dot_id(label)
+                ));
+            }
+
+            if !namespace.is_empty() {
+                dot.push_str("    }\n");
+            }
+        }
+
+        if !edges.is_empty() {
+            dot.push('\n');
+        }
+
+        for (from, to) in edges {
+            dot.push_str(&format!("    {} -> {};\n", dot_id(&from), dot_id(&to)));
+        }
+
+        dot.push_str("}\n");
+
+        dot
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NodeKind {
+    Rule,
+    Matcher,
+    Missing,
+}
+
+fn dot_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,120 @@
+use super::*;
+use crate::rule::{Anchor, CaseFolding, Literal, Predicate};
+
+impl Rules {
+    /// Renders the rules as ISO/IEC 14977 EBNF, one rule per line.
+    ///
+    /// Rule names keep their letters and digits. `_` is written as `__`, and any other character
+    /// as `_` and its hexadecimal code point followed by `_` (e.g. `ws::ws_ml` becomes `ws_3a__3a_ws__ml`),
+    /// so that different names stay different. Parts that EBNF has no notation for, like case-insensitive
+    /// literals, predicates or matchers, are written as special sequences (`? ... ?`), in which `?` is
+    /// written as `\u{3f}`.
+    pub fn to_ebnf(&self) -> String {
+        let mut ebnf = String::new();
+
+        for rule_name in sorted_rule_names(self) {
+            let trees = self.rule_trees(&rule_name).unwrap_or_default();
+
+            let alternatives = alternatives(trees)
+                .into_iter()
+                // The empty alternative is written as nothing at all
+                .map(|parts| {
+                    parts
+                        .into_iter()
+                        .map(|part| format!(" {}", ebnf_part(&rule_name, part)))
+                        .collect::<Vec<_>>()
+                        .join(",")
+                })
+                .collect::<Vec<_>>();
+
+            ebnf.push_str(&format!(
+                "{} ={} ;\n",
+                ebnf_name(&rule_name),
+                alternatives.join(" |")
+            ));
+        }
+
+        ebnf
+    }
+}
+
+fn ebnf_name(rule_name: &str) -> String {
+    rule_name
+        .chars()
+        .map(|c| match c {
+            c if c.is_alphanumeric() => c.to_string(),
+            '_' => "__".to_owned(),
+            c => format!("_{:x}_", c as u32),
+        })
+        .collect()
+}
+
+// A special sequence, which ends at the next `?`
+fn ebnf_special(text: &str) -> String {
+    format!("? {} ?", text.replace('?', "\\u{3f}"))
+}
+
+// ISO EBNF has no escapes, so the quote is chosen to fit the text
+fn ebnf_terminal(text: &str) -> String {
+    if text.chars().any(char::is_control) {
+        ebnf_special(&format!("{text:?}"))
+    } else if !text.contains('"') {
+        format!("\"{text}\"")
+    } else if !text.contains('\'') {
+        format!("'{text}'")
+    } else {
+        ebnf_special(&format!("{text:?}"))
+    }
+}
+
+fn ebnf_literal(literal: &Literal) -> String {
+    match literal.case_folding {
+        None => ebnf_terminal(&literal.text),
+        Some(CaseFolding::Ascii) => ebnf_special(&format!("case-insensitive {:?}", literal.text)),
+        Some(CaseFolding::Unicode) => {
+            ebnf_special(&format!("unicode case-insensitive {:?}", literal.text))
+        }
+    }
+}
+
+fn ebnf_part(rule_name: &str, part: &RulePart) -> String {
+    match part {
+        RulePart::Term(text) => ebnf_terminal(text),
+        RulePart::TermCaseInsensitive(text, case_folding) => {
+            ebnf_literal(&Literal::case_insensitive(text.as_str(), *case_folding))
+        }
+        RulePart::NonTerm(rule) => ebnf_name(rule),
+        RulePart::Recurse => ebnf_name(rule_name),
+        RulePart::Not(literals) => {
+            let mut literals = literals.iter().collect::<Vec<_>>();
+            literals.sort();
+
+            let literals = literals
+                .into_iter()
+                .map(ebnf_literal)
+                .collect::<Vec<_>>()
+                .join(" | ");
+
+            format!("( ? any character ? - ( {literals} ) )")
+        }
+        RulePart::Until(stops) => {
+            let stops = stops
+                .iter()
+                .map(|stop| match stop {
+                    UntilStop::Literal(literal) => ebnf_literal(literal),
+                    UntilStop::Rule(rule) => ebnf_name(rule),
+                })
+                .collect::<Vec<_>>()
+                .join(" | ");
+
+            format!("{{ ? any character ? - ( {stops} ) }}")
+        }
+        RulePart::Predicate(Predicate::Plain(_)) => "? predicate ?".to_owned(),
+        RulePart::Predicate(Predicate::WithContext(_)) => "? context predicate ?".to_owned(),
+        RulePart::Matcher(matcher) => ebnf_special(&format!("matcher {matcher}")),
+        RulePart::Anchor(Anchor::Eof) => "? end of input ?".to_owned(),
+        RulePart::Anchor(Anchor::Bol) => "? start of line ?".to_owned(),
+        RulePart::Anchor(Anchor::Eol) => "? end of line ?".to_owned(),
+        RulePart::Instance(..) => ebnf_special(&part.to_string()),
+    }
+}
@@ -0,0 +1,70 @@
+//! Renders [`Rules`](crate::rule::Rules) for documentation: as ISO EBNF ([`Rules::to_ebnf`](crate::rule::Rules::to_ebnf)),
+//! as a GraphViz DOT graph of rule references ([`Rules::to_dot`](crate::rule::Rules::to_dot))
+//! and as railroad diagrams ([`Rules::railroad_svg`](crate::rule::Rules::railroad_svg) and
+//! [`Rules::railroad_html`](crate::rule::Rules::railroad_html)).
+
+use crate::rule::{RulePart, RuleTree, Rules, UntilStop};
+
+mod dot;
+mod ebnf;
+mod railroad;
+
+impl Rules {
+    /// Returns the alternatives of the given rule, in the order in which they are tried.
+    /// Left recursion is [`RulePart::Recurse`].
+    pub fn alternatives(&self, rule_name: &str) -> Option<Vec<Vec<&RulePart>>> {
+        self.rule_trees(rule_name).map(alternatives)
+    }
+}
+
+// Every sequence of parts from the root of the trees to an end, i.e. the alternatives of the rule
+fn alternatives(trees: &[RuleTree]) -> Vec<Vec<&RulePart>> {
+    let mut alternatives = Vec::new();
+
+    for tree in trees {
+        match tree {
+            RuleTree::Part { part, nexts } => {
+                for mut rest in self::alternatives(nexts) {
+                    rest.insert(0, part);
+                    alternatives.push(rest);
+                }
+            }
+            RuleTree::End { .. } => alternatives.push(Vec::new()),
+        }
+    }
+
+    alternatives
+}
+
+// The names of the rules (and matchers) referred to by the trees, as `(name, is matcher)`
+fn references(trees: &[RuleTree]) -> Vec<(&str, bool)> {
+    let mut references = Vec::new();
+
+    for tree in trees {
+        if let RuleTree::Part { part, nexts } = tree {
+            match part {
+                RulePart::NonTerm(rule) => references.push((rule.as_str(), false)),
+                RulePart::Matcher(matcher) => references.push((matcher.as_str(), true)),
+                RulePart::Until(stops) => {
+                    for stop in stops {
+                        if let UntilStop::Rule(rule) = stop {
+                            references.push((rule.as_str(), false));
+                        }
+                    }
+                }
+                _ => {}
+            }
+
+            references.extend(self::references(nexts));
+        }
+    }
+
+    references
+}
+
+// The rule names in a fixed order, so that the output doesn't change between runs
+fn sorted_rule_names(rules: &Rules) -> Vec<String> {
+    let mut names = rules.rule_names();
+    names.sort();
+    names
+}
@@ -0,0 +1,385 @@
+use super::*;
+use crate::rule::{Anchor, CaseFolding, Predicate};
+
+// Sizes in pixels
+const CHAR_WIDTH: i64 = 8;
+const BOX_HEIGHT: i64 = 22;
+const ARC_RADIUS: i64 = 10;
+const HORIZONTAL_GAP: i64 = 10;
+const VERTICAL_GAP: i64 = 8;
+const PADDING: i64 = 20;
+
+const STYLE: &str = "\
+svg.railroad path { stroke-width: 2; stroke: #333; fill: none; }
+svg.railroad rect { stroke-width: 2; stroke: #333; fill: #ffc; }
+svg.railroad rect.nonterminal { fill: #dfe; }
+svg.railroad rect.special { fill: #eee; stroke-dasharray: 4 2; }
+svg.railroad text { font: 13px monospace; text-anchor: middle; }
+svg.railroad a text { text-decoration: underline; }
+";
+
+impl Rules {
+    /// Renders a railroad diagram of the given rule as a self-contained SVG image,
+    /// or `None` if there is no such rule.
+    ///
+    /// Left recursion is drawn as a loop, and non-terminals link to `#<rule name>`
+    /// (see [`Rules::railroad_html`]).
+    pub fn railroad_svg(&self, rule_name: &str) -> Option<String> {
+        let trees = self.rule_trees(rule_name)?;
+
+        Some(svg(&rule_diagram(trees), true))
+    }
+
+    /// Renders the railroad diagrams of all rules as a self-contained HTML page,
+    /// in which non-terminals link to the diagrams of their rules.
+    pub fn railroad_html(&self) -> String {
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Rules</title>\n<style>\n{STYLE}</style>\n</head>\n<body>\n"
+        );
+
+        for rule_name in sorted_rule_names(self) {
+            let trees = self.rule_trees(&rule_name).unwrap_or_default();
+            let rule_name = escape(&rule_name);
+
+            html.push_str(&format!(
+                "<h2 id=\"{rule_name}\">{rule_name}</h2>\n{}\n",
+                svg(&rule_diagram(trees), false)
+            ));
+        }
+
+        html.push_str("</body>\n</html>\n");
+
+        html
+    }
+}
+
+enum Diagram {
+    Terminal(String),
+    NonTerminal(String),
+    // Anything else, like predicates or `Not`s
+    Special(String),
+    Skip,
+    Sequence(Vec<Diagram>),
+    // The first alternative is on the main line
+    Choice(Vec<Diagram>),
+    OneOrMore(Box<Diagram>),
+}
+
+// The space a diagram takes up around its main line
+#[derive(Debug, Clone, Copy)]
+struct Size {
+    width: i64,
+    up: i64,
+    down: i64,
+}
+
+fn rule_diagram(trees: &[RuleTree]) -> Diagram {
+    let (recursive, rest): (Vec<_>, Vec<_>) = trees.iter().partition(|tree| {
+        matches!(
+            tree,
+            RuleTree::Part {
+                part: RulePart::Recurse,
+                ..
+            }
+        )
+    });
+
+    let base = choice(rest.into_iter().map(tree_diagram).collect());
+
+    if recursive.is_empty() {
+        return base;
+    }
+
+    // A left recursive rule is its other alternatives, followed by any number of the recursive ones
+    let tails = choice(
+        recursive
+            .into_iter()
+            .flat_map(|tree| match tree {
+                RuleTree::Part { nexts, .. } => nexts.iter().map(tree_diagram).collect(),
+                RuleTree::End { .. } => Vec::new(),
+            })
+            .collect(),
+    );
+
+    sequence(vec![
+        base,
+        Diagram::Choice(vec![Diagram::Skip, Diagram::OneOrMore(Box::new(tails))]),
+    ])
+}
+
+fn tree_diagram(tree: &RuleTree) -> Diagram {
+    match tree {
+        RuleTree::Part { part, nexts } => sequence(vec![
+            part_diagram(part),
+            choice(nexts.iter().map(tree_diagram).collect()),
+        ]),
+        RuleTree::End { .. } => Diagram::Skip,
+    }
+}
+
+fn part_diagram(part: &RulePart) -> Diagram {
+    match part {
+        RulePart::Term(text) => Diagram::Terminal(format!("{text:?}")),
+        RulePart::TermCaseInsensitive(text, CaseFolding::Ascii) => {
+            Diagram::Terminal(format!("{text:?} (i)"))
+        }
+        RulePart::TermCaseInsensitive(text, CaseFolding::Unicode) => {
+            Diagram::Terminal(format!("{text:?} (iu)"))
+        }
+        RulePart::NonTerm(rule) => Diagram::NonTerminal(rule.clone()),
+        RulePart::Recurse => Diagram::Special("recurse".to_owned()),
+        RulePart::Matcher(matcher) => Diagram::Special(format!("matcher {matcher}")),
+        RulePart::Predicate(Predicate::Plain(_)) => Diagram::Special("predicate".to_owned()),
+        RulePart::Predicate(Predicate::WithContext(_)) => {
+            Diagram::Special("context predicate".to_owned())
+        }
+        RulePart::Anchor(Anchor::Eof) => Diagram::Special("end of input".to_owned()),
+        RulePart::Anchor(Anchor::Bol) => Diagram::Special("start of line".to_owned()),
+        RulePart::Anchor(Anchor::Eol) => Diagram::Special("end of line".to_owned()),
+        part => Diagram::Special(part.to_string()),
+    }
+}
+
+// Flattens nested sequences and leaves out skips
+fn sequence(items: Vec<Diagram>) -> Diagram {
+    let mut flattened = Vec::new();
+
+    for item in items {
+        match item {
+            Diagram::Skip => {}
+            Diagram::Sequence(items) => flattened.extend(items),
+            item => flattened.push(item),
+        }
+    }
+
+    match flattened.len() {
+        0 => Diagram::Skip,
+        1 => flattened.pop().unwrap(),
+        _ => Diagram::Sequence(flattened),
+    }
+}
+
+// Puts the empty alternative (if any) on the main line
+fn choice(mut items: Vec<Diagram>) -> Diagram {
+    if let Some(i) = items.iter().position(|item| matches!(item, Diagram::Skip)) {
+        let skip = items.remove(i);
+        items.insert(0, skip);
+    }
+
+    match items.len() {
+        0 => Diagram::Skip,
+        1 => items.pop().unwrap(),
+        _ => Diagram::Choice(items),
+    }
+}
+
+impl Diagram {
+    fn size(&self) -> Size {
+        match self {
+            Diagram::Terminal(text) | Diagram::NonTerminal(text) | Diagram::Special(text) => Size {
+                width: text.chars().count() as i64 * CHAR_WIDTH + 2 * ARC_RADIUS,
+                up: BOX_HEIGHT / 2,
+                down: BOX_HEIGHT / 2,
+            },
+            Diagram::Skip => Size {
+                width: 0,
+                up: 0,
+                down: 0,
+            },
+            Diagram::Sequence(items) => {
+                let sizes = items.iter().map(Diagram::size).collect::<Vec<_>>();
+
+                Size {
+                    width: sizes.iter().map(|size| size.width).sum::<i64>()
+                        + HORIZONTAL_GAP * (sizes.len() as i64 - 1),
+                    up: sizes.iter().map(|size| size.up).max().unwrap_or(0),
+                    down: sizes.iter().map(|size| size.down).max().unwrap_or(0),
+                }
+            }
+            Diagram::Choice(items) => {
+                let sizes = items.iter().map(Diagram::size).collect::<Vec<_>>();
+                let offsets = choice_offsets(&sizes);
+
+                Size {
+                    width: sizes.iter().map(|size| size.width).max().unwrap_or(0) + 4 * ARC_RADIUS,
+                    up: sizes[0].up,
+                    down: offsets.last().unwrap() + sizes.last().unwrap().down,
+                }
+            }
+            Diagram::OneOrMore(item) => {
+                let size = item.size();
+
+                Size {
+                    width: size.width + 4 * ARC_RADIUS,
+                    up: size.up,
+                    down: loop_offset(size),
+                }
+            }
+        }
+    }
+
+    // Draws the diagram with its main line starting at (x, y)
+    fn draw(&self, x: i64, y: i64, svg: &mut String) {
+        match self {
+            Diagram::Terminal(text) => draw_box(x, y, text, "terminal", None, svg),
+            Diagram::NonTerminal(rule) => draw_box(x, y, rule, "nonterminal", Some(rule), svg),
+            Diagram::Special(text) => draw_box(x, y, text, "special", None, svg),
+            Diagram::Skip => {}
+            Diagram::Sequence(items) => {
+                let mut x = x;
+
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        svg.push_str(&format!("<path d=\"M{x} {y}h{HORIZONTAL_GAP}\"/>\n"));
+                        x += HORIZONTAL_GAP;
+                    }
+
+                    item.draw(x, y, svg);
+                    x += item.size().width;
+                }
+            }
+            Diagram::Choice(items) => {
+                let sizes = items.iter().map(Diagram::size).collect::<Vec<_>>();
+                let offsets = choice_offsets(&sizes);
+                let width = self.size().width;
+                let r = ARC_RADIUS;
+
+                for ((item, size), offset) in items.iter().zip(&sizes).zip(offsets) {
+                    let rest = width - 2 * r - size.width;
+
+                    if offset == 0 {
+                        svg.push_str(&format!("<path d=\"M{x} {y}h{}\"/>\n", 2 * r));
+                    } else {
+                        let vertical = offset - 2 * r;
+
+                        svg.push_str(&format!(
+                            "<path d=\"M{x} {y}a{r} {r} 0 0 1 {r} {r}v{vertical}a{r} {r} 0 0 0 {r} {r}\"/>\n"
+                        ));
+                    }
+
+                    item.draw(x + 2 * r, y + offset, svg);
+
+                    let end = x + 2 * r + size.width;
+                    let y = y + offset;
+
+                    if offset == 0 {
+                        svg.push_str(&format!("<path d=\"M{end} {y}h{rest}\"/>\n"));
+                    } else {
+                        let vertical = offset - 2 * r;
+
+                        svg.push_str(&format!(
+                            "<path d=\"M{end} {y}h{}a{r} {r} 0 0 0 {r} -{r}v-{vertical}a{r} {r} 0 0 1 {r} -{r}\"/>\n",
+                            rest - 2 * r
+                        ));
+                    }
+                }
+            }
+            Diagram::OneOrMore(item) => {
+                let size = item.size();
+                let r = ARC_RADIUS;
+                let offset = loop_offset(size);
+                let vertical = offset - 2 * r;
+                let end = x + 2 * r + size.width;
+
+                svg.push_str(&format!(
+                    "<path d=\"M{x} {y}h{}\"/>\n<path d=\"M{end} {y}h{}\"/>\n",
+                    2 * r,
+                    2 * r
+                ));
+
+                item.draw(x + 2 * r, y, svg);
+
+                // The way back, below the item
+                svg.push_str(&format!(
+                    "<path d=\"M{end} {y}a{r} {r} 0 0 1 {r} {r}v{vertical}a{r} {r} 0 0 1 -{r} {r}h-{}a{r} {r} 0 0 1 -{r} -{r}v-{vertical}a{r} {r} 0 0 1 {r} -{r}\"/>\n",
+                    size.width
+                ));
+            }
+        }
+    }
+}
+
+// The vertical positions of the alternatives of a choice, relative to its main line
+fn choice_offsets(sizes: &[Size]) -> Vec<i64> {
+    let mut offsets = vec![0];
+
+    for pair in sizes.windows(2) {
+        let previous = *offsets.last().unwrap();
+        let offset = previous + pair[0].down + VERTICAL_GAP + pair[1].up;
+
+        // There has to be room for the arcs down from the main line
+        offsets.push(offset.max(previous + 2 * ARC_RADIUS));
+    }
+
+    offsets
+}
+
+// The vertical position of the way back of a loop, relative to its main line
+fn loop_offset(size: Size) -> i64 {
+    (size.down + VERTICAL_GAP).max(2 * ARC_RADIUS)
+}
+
+fn draw_box(x: i64, y: i64, text: &str, class: &str, link: Option<&str>, svg: &mut String) {
+    let width = text.chars().count() as i64 * CHAR_WIDTH + 2 * ARC_RADIUS;
+    let rounding = if class == "terminal" { ARC_RADIUS } else { 0 };
+    let text = escape(text);
+
+    if let Some(link) = link {
+        svg.push_str(&format!("<a href=\"#{}\">\n", escape(link)));
+    }
+
+    svg.push_str(&format!(
+        "<rect class=\"{class}\" x=\"{x}\" y=\"{}\" width=\"{width}\" height=\"{BOX_HEIGHT}\" rx=\"{rounding}\"/>\n<text x=\"{}\" y=\"{}\">{text}</text>\n",
+        y - BOX_HEIGHT / 2,
+        x + width / 2,
+        y + 4
+    ));
+
+    if link.is_some() {
+        svg.push_str("</a>\n");
+    }
+}
+
+fn svg(diagram: &Diagram, with_style: bool) -> String {
+    let size = diagram.size();
+
+    // The start and end markers take up 20 pixels on each side, and 10 above and below
+    let up = size.up.max(10);
+    let down = size.down.max(10);
+    let width = size.width + 2 * PADDING + 40;
+    let height = up + down + 2 * PADDING;
+
+    let x = PADDING + 20;
+    let y = PADDING + up;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"railroad\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n"
+    );
+
+    if with_style {
+        svg.push_str(&format!("<style>\n{STYLE}</style>\n"));
+    }
+
+    svg.push_str(&format!(
+        "<path d=\"M{PADDING} {}v20m10 -20v20m0 -10h10\"/>\n",
+        y - 10
+    ));
+
+    diagram.draw(x, y, &mut svg);
+
+    svg.push_str(&format!(
+        "<path d=\"M{} {y}h10m0 -10v20m10 -20v20\"/>\n",
+        x + size.width
+    ));
+
+    svg.push_str("</svg>");
+
+    svg
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,405 @@
+//! Generates random sentences of a grammar, e.g. for fuzzing. See [`Generator`].
+
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt::Display,
+};
+
+use crate::rule::{CaseFolding, Literal, RulePart, Rules, UntilStop};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenerateError {
+    RuleNotFound {
+        rule_name: String,
+    },
+    /// The rule can't be expanded into a finite sentence, e.g. `a { (a "x") }`
+    Unbounded {
+        rule_name: String,
+    },
+    /// The rule can't be expanded without going through a matcher.
+    /// Matchers are Rust functions, so it isn't known what they accept
+    Matcher {
+        matcher: String,
+    },
+}
+
+impl Error for GenerateError {}
+
+impl Display for GenerateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenerateError::RuleNotFound { rule_name } => {
+                f.write_fmt(format_args!("Rule '{rule_name}' not found"))
+            }
+            GenerateError::Unbounded { rule_name } => f.write_fmt(format_args!(
+                "Rule '{rule_name}' has no alternative that can be expanded into a finite sentence"
+            )),
+            GenerateError::Matcher { matcher } => f.write_fmt(format_args!(
+                "Can't generate input for the matcher '{matcher}'"
+            )),
+        }
+    }
+}
+
+/// Generates random sentences of [`Rules`], which should be accepted by them.
+///
+/// Alternatives are chosen randomly (see [`Generator::weight`]) until `max_depth` nested rules,
+/// after which only the alternatives that lead to the shortest expansions are chosen.
+/// `Not`s and `until`s are filled with characters from the alphabet.
+///
+/// Predicates and `until`s that stop at rules aren't taken into account, so sentences
+/// of rules which use them may be rejected.
+///
+/// The random number generator is seeded, so the same seed always gives the same sentences.
+#[derive(Clone)]
+pub struct Generator<'r> {
+    rules: &'r Rules,
+    rng: SplitMix64,
+    min_depth: usize,
+    max_depth: usize,
+    max_run: usize,
+    alphabet: Vec<char>,
+    weights: HashMap<(String, usize), f64>,
+    // The fewest nested rules needed to expand each rule (`None` if it can't be)
+    heights: HashMap<String, Option<usize>>,
+}
+
+impl<'r> Generator<'r> {
+    pub fn new(rules: &'r Rules) -> Self {
+        Self {
+            rules,
+            rng: SplitMix64(0),
+            min_depth: 0,
+            max_depth: 16,
+            max_run: 8,
+            alphabet: ('a'..='z')
+                .chain('A'..='Z')
+                .chain('0'..='9')
+                .chain(" _-.,:;!?()[]{}<>=+*/#'\"\\".chars())
+                .collect(),
+            weights: HashMap::new(),
+            heights: heights(rules),
+        }
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = SplitMix64(seed);
+        self
+    }
+
+    /// Until this depth, alternatives that lead to the shortest expansions are avoided (if possible).
+    pub fn min_depth(mut self, min_depth: usize) -> Self {
+        self.min_depth = min_depth;
+        self
+    }
+
+    /// From this depth on, only alternatives that lead to the shortest expansions are chosen.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// The most characters generated for an `until`.
+    pub fn max_run(mut self, max_run: usize) -> Self {
+        self.max_run = max_run;
+        self
+    }
+
+    /// The characters `Not`s and `until`s are filled with.
+    pub fn alphabet(mut self, alphabet: impl IntoIterator<Item = char>) -> Self {
+        self.alphabet = alphabet.into_iter().collect();
+        self
+    }
+
+    /// Sets how likely an alternative of a rule is to be chosen, relative to the others (which default to `1.0`).
+    /// The alternatives are numbered in the order of [`Rules::alternatives`].
+    pub fn weight(mut self, rule_name: impl Into<String>, alternative: usize, weight: f64) -> Self {
+        self.weights.insert((rule_name.into(), alternative), weight);
+        self
+    }
+
+    /// Generates a random sentence of the given rule.
+    pub fn generate(&mut self, start_rule: &str) -> Result<String, GenerateError> {
+        let mut sentence = String::new();
+
+        self.expand(start_rule, 0, &mut sentence)?;
+
+        Ok(sentence)
+    }
+
+    /// Generates `count` sentences and panics if the rules don't accept one of them
+    /// (with [`Rules::parse_entire`]).
+    pub fn assert_round_trip(&mut self, start_rule: &str, count: usize) {
+        for _ in 0..count {
+            let sentence = self
+                .generate(start_rule)
+                .unwrap_or_else(|error| panic!("Couldn't generate a sentence: {error}"));
+
+            if let Err(error) = self.rules.parse_entire(start_rule, sentence.as_str()) {
+                panic!("The generated sentence {sentence:?} was not accepted: {error}");
+            }
+        }
+    }
+
+    fn expand(
+        &mut self,
+        rule_name: &str,
+        depth: usize,
+        sentence: &mut String,
+    ) -> Result<(), GenerateError> {
+        let rules = self.rules;
+
+        let alternatives =
+            rules
+                .alternatives(rule_name)
+                .ok_or_else(|| GenerateError::RuleNotFound {
+                    rule_name: rule_name.to_owned(),
+                })?;
+
+        let heights = alternatives
+            .iter()
+            .map(|parts| alternative_height(&self.heights, rule_name, parts))
+            .collect::<Vec<_>>();
+
+        let Some(lowest) = heights.iter().flatten().min().copied() else {
+            return Err(
+                match blocking_matcher(rules, &self.heights, rule_name, &mut HashSet::new()) {
+                    Some(matcher) => GenerateError::Matcher { matcher },
+                    None => GenerateError::Unbounded {
+                        rule_name: rule_name.to_owned(),
+                    },
+                },
+            );
+        };
+
+        let mut candidates = (0..alternatives.len())
+            .filter(|&i| match heights[i] {
+                None => false,
+                Some(_) if depth >= self.max_depth => heights[i] == Some(lowest),
+                Some(height) if depth < self.min_depth => height > lowest,
+                Some(_) => true,
+            })
+            .collect::<Vec<_>>();
+
+        // Every alternative is as short as it gets
+        if candidates.is_empty() {
+            candidates = (0..alternatives.len())
+                .filter(|&i| heights[i] == Some(lowest))
+                .collect();
+        }
+
+        let weights = candidates
+            .iter()
+            .map(|&i| {
+                self.weights
+                    .get(&(rule_name.to_owned(), i))
+                    .copied()
+                    .unwrap_or(1.0)
+            })
+            .collect::<Vec<_>>();
+
+        let choice = candidates[self.rng.weighted(&weights)];
+
+        for part in &alternatives[choice] {
+            match part {
+                RulePart::Term(text) => sentence.push_str(text),
+                RulePart::TermCaseInsensitive(text, case_folding) => {
+                    for c in text.chars() {
+                        match (case_folding, self.rng.next_bool()) {
+                            (_, false) => sentence.push(c),
+                            (CaseFolding::Ascii, true) => sentence.push(c.to_ascii_uppercase()),
+                            (CaseFolding::Unicode, true) => sentence.extend(c.to_uppercase()),
+                        }
+                    }
+                }
+                RulePart::NonTerm(rule) => self.expand(rule, depth + 1, sentence)?,
+                RulePart::Recurse => self.expand(rule_name, depth + 1, sentence)?,
+                RulePart::Not(literals) => {
+                    let literals = literals.iter().collect::<Vec<_>>();
+
+                    if let Some(c) = self.random_char(&literals) {
+                        sentence.push(c);
+                    }
+                }
+                RulePart::Until(stops) => {
+                    let literals = stops
+                        .iter()
+                        .filter_map(|stop| match stop {
+                            UntilStop::Literal(literal) => Some(literal),
+                            UntilStop::Rule(_) => None,
+                        })
+                        .collect::<Vec<_>>();
+
+                    let len = self.rng.below(self.max_run as u64 + 1);
+
+                    for _ in 0..len {
+                        if let Some(c) = self.random_char(&literals) {
+                            sentence.push(c);
+                        }
+                    }
+                }
+                RulePart::Matcher(_) => {
+                    unreachable!("Alternatives with matchers have no height, so they aren't chosen")
+                }
+                // These don't consume any input
+                RulePart::Predicate(_) | RulePart::Anchor(_) => {}
+                RulePart::Instance(..) => {
+                    unreachable!("Instances of parameterized rules are replaced in Rules::new")
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // A character from the alphabet that none of the literals starts with
+    fn random_char(&mut self, literals: &[&Literal]) -> Option<char> {
+        let allowed = self
+            .alphabet
+            .iter()
+            .copied()
+            .filter(|&c| !literals.iter().any(|literal| starts_literal(c, literal)))
+            .collect::<Vec<_>>();
+
+        if allowed.is_empty() {
+            None
+        } else {
+            Some(allowed[self.rng.below(allowed.len() as u64) as usize])
+        }
+    }
+}
+
+fn starts_literal(c: char, literal: &Literal) -> bool {
+    let Some(first) = literal.text.chars().next() else {
+        return false;
+    };
+
+    match literal.case_folding {
+        None => c == first,
+        Some(_) => c.to_lowercase().eq(first.to_lowercase()),
+    }
+}
+
+// The fewest nested rules needed to expand each rule, found by repeating until nothing changes
+fn heights(rules: &Rules) -> HashMap<String, Option<usize>> {
+    let rule_names = rules.rule_names();
+
+    let mut heights: HashMap<String, Option<usize>> = rule_names
+        .iter()
+        .map(|rule_name| (rule_name.clone(), None))
+        .collect();
+
+    loop {
+        let mut changed = false;
+
+        for rule_name in &rule_names {
+            let height = rules
+                .alternatives(rule_name)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|parts| alternative_height(&heights, rule_name, parts))
+                .min();
+
+            if height != heights[rule_name] {
+                heights.insert(rule_name.clone(), height);
+                changed = true;
+            }
+        }
+
+        if !changed {
+            return heights;
+        }
+    }
+}
+
+fn alternative_height(
+    heights: &HashMap<String, Option<usize>>,
+    rule_name: &str,
+    parts: &[&RulePart],
+) -> Option<usize> {
+    let mut height = 0;
+
+    for part in parts {
+        let part_height = match part {
+            RulePart::NonTerm(rule) => heights.get(rule).copied().flatten()?,
+            RulePart::Recurse => heights.get(rule_name).copied().flatten()?,
+            RulePart::Matcher(_) => return None,
+            _ => 0,
+        };
+
+        height = height.max(part_height);
+    }
+
+    Some(height + 1)
+}
+
+// A matcher in the way of expanding the rule, if there is one, either in one of its alternatives
+// or in a rule they use which can't be expanded either
+fn blocking_matcher(
+    rules: &Rules,
+    heights: &HashMap<String, Option<usize>>,
+    rule_name: &str,
+    visited: &mut HashSet<String>,
+) -> Option<String> {
+    if !visited.insert(rule_name.to_owned()) {
+        return None;
+    }
+
+    rules
+        .alternatives(rule_name)?
+        .iter()
+        .flatten()
+        .find_map(|part| match part {
+            RulePart::Matcher(matcher) => Some(matcher.clone()),
+            RulePart::NonTerm(rule) if heights.get(rule).copied().flatten().is_none() => {
+                blocking_matcher(rules, heights, rule, visited)
+            }
+            _ => None,
+        })
+}
+
+// A small, seedable random number generator (SplitMix64), so that no dependency is needed
+#[derive(Debug, Clone)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    fn next_bool(&mut self) -> bool {
+        self.next() & 1 == 1
+    }
+
+    // A number in 0..n
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    // An index into the weights, chosen in proportion to them
+    fn weighted(&mut self, weights: &[f64]) -> usize {
+        let total = weights.iter().sum::<f64>();
+
+        if total <= 0.0 {
+            return self.below(weights.len() as u64) as usize;
+        }
+
+        let mut point = (self.next() >> 11) as f64 / (1u64 << 53) as f64 * total;
+
+        for (i, weight) in weights.iter().enumerate() {
+            if point < *weight {
+                return i;
+            }
+
+            point -= weight;
+        }
+
+        weights.len() - 1
+    }
+}
@@ -22,7 +22,7 @@ impl<'a> CharsInput<'a> {
 
 impl<'a> Input<'a> for CharsInput<'a> {
     fn next(&mut self) -> Option<char> {
-        self.chars.next().map(|c| {
+        self.chars.next().inspect(|&c| {
             self.pos += 1;
 
             if c == '\n' {
@@ -31,8 +31,6 @@ impl<'a> Input<'a> for CharsInput<'a> {
             } else {
                 self.col += 1;
             }
-
-            c
         })
     }
 
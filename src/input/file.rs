@@ -13,6 +13,8 @@ use utf8_chars::BufReadCharsExt;
 struct FileInputSource {
     source: BufReader<File>,
     buffer: Vec<char>,
+    // the position of the first char in `buffer`, as the ones before it can be discarded
+    start: usize,
 }
 
 impl FileInputSource {
@@ -25,17 +27,27 @@ impl FileInputSource {
         Ok(Self {
             source: BufReader::new(source),
             buffer,
+            start: 0,
         })
     }
 
     fn get(&mut self, pos: usize) -> Option<char> {
-        while pos >= self.buffer.len() {
+        let i = pos.checked_sub(self.start)?;
+
+        while i >= self.buffer.len() {
             let ch = self.source.read_char().ok()??;
 
             self.buffer.push(ch);
         }
 
-        self.buffer.get(pos).cloned()
+        self.buffer.get(i).cloned()
+    }
+
+    fn discard_before(&mut self, pos: usize) {
+        let n = pos.saturating_sub(self.start).min(self.buffer.len());
+
+        self.buffer.drain(..n);
+        self.start += n;
     }
 }
 
@@ -57,15 +69,15 @@ impl<'a> FileInput<'a> {
             file,
             buffer,
             pos: 0,
-            row: 0,
-            col: 0,
+            row: 1,
+            col: 1,
         }
     }
 }
 
 impl<'a> Input<'a> for FileInput<'a> {
     fn next(&mut self) -> Option<char> {
-        self.buffer.borrow_mut().get(self.pos).map(|c| {
+        self.buffer.borrow_mut().get(self.pos).inspect(|&c| {
             self.pos += 1;
 
             if c == '\n' {
@@ -74,8 +86,6 @@ impl<'a> Input<'a> for FileInput<'a> {
             } else {
                 self.col += 1;
             }
-
-            c
         })
     }
 
@@ -87,6 +97,10 @@ impl<'a> Input<'a> for FileInput<'a> {
         (self.row, self.col)
     }
 
+    fn discard_before(&self) {
+        self.buffer.borrow_mut().discard_before(self.pos);
+    }
+
     fn path(&self) -> Option<&Path> {
         Some(self.file)
     }
@@ -20,6 +20,48 @@ pub trait Input<'a>: Clone + Display + 'a {
 
         LineInfo { pos, line, column }
     }
+
+    /// Lets an input which keeps what it read (like a `FileInput` or `TcpInput`, so that the parser can backtrack)
+    /// drop the characters before its position. Clones of it at an earlier position can't read anything afterwards.
+    fn discard_before(&self) {}
+}
+
+/// An object safe view of an [`Input`], given to matchers
+/// (see [`Rules::add_matcher`](crate::rule::Rules::add_matcher)).
+pub trait MatcherInput {
+    fn next(&mut self) -> Option<char>;
+    fn pos(&self) -> usize;
+    fn row_col(&self) -> (usize, usize);
+
+    /// Returns the next character without consuming it.
+    fn peek(&self) -> Option<char>;
+
+    /// Whether the input continues with `literal`, without consuming it.
+    fn starts_with(&self, literal: &str) -> bool;
+}
+
+impl<'a, I: Input<'a>> MatcherInput for I {
+    fn next(&mut self) -> Option<char> {
+        Input::next(self)
+    }
+
+    fn pos(&self) -> usize {
+        Input::pos(self)
+    }
+
+    fn row_col(&self) -> (usize, usize) {
+        Input::row_col(self)
+    }
+
+    fn peek(&self) -> Option<char> {
+        Input::next(&mut self.clone())
+    }
+
+    fn starts_with(&self, literal: &str) -> bool {
+        let mut input = self.clone();
+
+        literal.chars().all(|c| Input::next(&mut input) == Some(c))
+    }
 }
 
 pub trait IntoInput<'a>: 'a {
@@ -13,6 +13,8 @@ use utf8_chars::BufReadCharsExt;
 struct TcpInputSource {
     source: BufReader<TcpStream>,
     buffer: Vec<char>,
+    // the position of the first char in `buffer`, as the ones before it can be discarded
+    start: usize,
 }
 
 impl TcpInputSource {
@@ -22,17 +24,27 @@ impl TcpInputSource {
         Self {
             source: BufReader::new(source),
             buffer,
+            start: 0,
         }
     }
 
     fn get(&mut self, pos: usize) -> Option<char> {
-        while pos >= self.buffer.len() {
+        let i = pos.checked_sub(self.start)?;
+
+        while i >= self.buffer.len() {
             let ch = self.source.read_char().ok()??;
 
             self.buffer.push(ch);
         }
 
-        self.buffer.get(pos).cloned()
+        self.buffer.get(i).cloned()
+    }
+
+    fn discard_before(&mut self, pos: usize) {
+        let n = pos.saturating_sub(self.start).min(self.buffer.len());
+
+        self.buffer.drain(..n);
+        self.start += n;
     }
 }
 
@@ -57,8 +69,8 @@ impl<'a> TcpInput<'a> {
             address,
             buffer,
             pos: 0,
-            row: 0,
-            col: 0,
+            row: 1,
+            col: 1,
             _phantom: PhantomData,
         }
     }
@@ -66,7 +78,7 @@ impl<'a> TcpInput<'a> {
 
 impl<'a> Input<'a> for TcpInput<'a> {
     fn next(&mut self) -> Option<char> {
-        self.buffer.borrow_mut().get(self.pos).map(|c| {
+        self.buffer.borrow_mut().get(self.pos).inspect(|&c| {
             self.pos += 1;
 
             if c == '\n' {
@@ -75,8 +87,6 @@ impl<'a> Input<'a> for TcpInput<'a> {
             } else {
                 self.col += 1;
             }
-
-            c
         })
     }
 
@@ -87,6 +97,10 @@ impl<'a> Input<'a> for TcpInput<'a> {
     fn row_col(&self) -> (usize, usize) {
         (self.row, self.col)
     }
+
+    fn discard_before(&self) {
+        self.buffer.borrow_mut().discard_before(self.pos);
+    }
 }
 
 impl<'a> Display for TcpInput<'a> {
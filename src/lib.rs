@@ -3,7 +3,13 @@ pub mod prelude {
         IntoParseError as _, IntoParseValue as _, ParseError, ParseValue, Token,
     };
 
-    pub use super::rule::{Rule, Rules};
+    pub use super::options::{ImportOptions, ParseOptions};
+
+    pub use super::context::ParseContext;
+
+    pub use super::observer::ParseObserver;
+
+    pub use super::rule::{ChoicePolicy, Rule, Rules};
 
     pub use super::{declare_rules, rules};
 }
@@ -14,10 +20,26 @@ pub mod input;
 
 pub mod rule;
 
+pub mod options;
+
+pub mod observer;
+
+pub mod context;
+
+pub mod export;
+
+pub mod generate;
+
 pub mod macros;
 
 #[cfg(feature = "included_parsers")]
 pub mod rules;
 
+#[cfg(feature = "serde")]
+pub mod de;
+
+#[cfg(feature = "serde")]
+pub use de::from_str;
+
 #[cfg(test)]
 mod tests;
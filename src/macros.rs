@@ -5,68 +5,221 @@ macro_rules! rule_part {
         $crate::rule::RulePart::Term(std::string::String::from($lit))
     };
 
+    (EOF) => {
+        $crate::rule::RulePart::Anchor($crate::rule::Anchor::Eof)
+    };
+
+    (BOL) => {
+        $crate::rule::RulePart::Anchor($crate::rule::Anchor::Bol)
+    };
+
+    (EOL) => {
+        $crate::rule::RulePart::Anchor($crate::rule::Anchor::Eol)
+    };
+
     ($rule:ident) => {
         $crate::rule::RulePart::NonTerm(stringify!($rule).to_owned())
     };
 
+    (($rule:ident < $($arg:tt),+ >)) => {
+        $crate::rule::RulePart::Instance(stringify!($rule).to_owned(), vec![$($crate::rule_part!($arg)),+])
+    };
+
+    ((until $($stop:tt)*)) => {
+        $crate::rule::RulePart::Until(vec![$($crate::until_stop!($stop)),*])
+    };
+
+    ((i $lit:literal)) => {
+        $crate::rule::RulePart::TermCaseInsensitive(std::string::String::from($lit), $crate::rule::CaseFolding::Ascii)
+    };
+
+    ((iu $lit:literal)) => {
+        $crate::rule::RulePart::TermCaseInsensitive(std::string::String::from($lit), $crate::rule::CaseFolding::Unicode)
+    };
+
     (($rule:path)) => {{
 
         $crate::rule::RulePart::NonTerm(stringify!($rule).to_owned())
     }};
 
-    ((! $($lit:literal)*)) => {
-        $crate::rule::RulePart::Not([$(std::string::String::from($lit)),*].into_iter().collect())
-    }
+    ((! $($lit:tt)*)) => {
+        $crate::rule::RulePart::Not([$($crate::not_literal!($lit)),*].into_iter().collect())
+    };
+
+    ((? mut $predicate:expr)) => {
+        $crate::rule::RulePart::Predicate($crate::rule::Predicate::with_context($predicate))
+    };
+
+    ((? $predicate:expr)) => {
+        $crate::rule::RulePart::Predicate($crate::rule::Predicate::new($predicate))
+    };
 
 
 }
 
+#[allow(dead_code)]
+#[macro_export]
+macro_rules! not_literal {
+    ($lit:literal) => {
+        $crate::rule::Literal::from($lit)
+    };
+
+    ((i $lit:literal)) => {
+        $crate::rule::Literal::case_insensitive($lit, $crate::rule::CaseFolding::Ascii)
+    };
+
+    ((iu $lit:literal)) => {
+        $crate::rule::Literal::case_insensitive($lit, $crate::rule::CaseFolding::Unicode)
+    };
+}
+
+#[allow(dead_code)]
+#[macro_export]
+macro_rules! until_stop {
+    ($rule:ident) => {
+        $crate::rule::UntilStop::Rule(stringify!($rule).to_owned())
+    };
+
+    (($rule:path)) => {
+        $crate::rule::UntilStop::Rule(stringify!($rule).to_owned())
+    };
+
+    ($lit:tt) => {
+        $crate::rule::UntilStop::Literal($crate::not_literal!($lit))
+    };
+}
+
 #[allow(dead_code)]
 #[macro_export]
 macro_rules! rule {
-    ($name:ident: ($($tt:tt)*) $(=> $transformer:expr)?) => {{
+    ($name:ident: ($($tt:tt)*) $(=> $transformer:expr)? $(=> mut $context_transformer:expr)?) => {{
 
         #[allow(unused_variables)]
         let transformer: Option<$crate::rule::Transformer> = None;
 
         $(
-            let transformer: Option<$crate::rule::Transformer> = Some(std::rc::Rc::new($transformer));
+            let transformer: Option<$crate::rule::Transformer> = Some($crate::rule::Transformer::new($transformer));
+        )?
+
+        $(
+            let transformer: Option<$crate::rule::Transformer> = Some($crate::rule::Transformer::with_context($context_transformer));
         )?
 
         Rule {
             name: stringify!($name).to_owned(),
+            params: Vec::new(),
             parts: vec![$($crate::rule_part!($tt)),*],
             transformer
         }
     }};
 }
 
+#[allow(dead_code)]
+#[macro_export]
+macro_rules! choice_policy {
+    (heuristic) => {
+        $crate::rule::ChoicePolicy::Heuristic
+    };
+
+    (ordered) => {
+        $crate::rule::ChoicePolicy::Ordered
+    };
+
+    (longest) => {
+        $crate::rule::ChoicePolicy::Longest
+    };
+}
+
+#[macro_export]
+macro_rules! import_options {
+    ($options:expr;) => {
+        $options
+    };
+
+    ($options:expr; override $rule:path $(= $replacement:ident)? $(, $($rest:tt)*)?) => {
+        $crate::import_options!(
+            $options.override_rule(
+                stringify!($rule),
+                None$(.or(Some(stringify!($replacement))))?.unwrap_or(stringify!($rule))
+            );
+            $($($rest)*)?
+        )
+    };
+
+    ($options:expr; extend $rule:path $(= $extended:ident)? $(, $($rest:tt)*)?) => {
+        $crate::import_options!(
+            $options.extend_rule(
+                stringify!($rule),
+                None$(.or(Some(stringify!($extended))))?.unwrap_or(stringify!($rule))
+            );
+            $($($rest)*)?
+        )
+    };
+
+    ($options:expr; hide private $(, $($rest:tt)*)?) => {
+        $crate::import_options!($options.hide_private(); $($($rest)*)?)
+    };
+
+    ($options:expr; export $rule:path as $name:ident $(, $($rest:tt)*)?) => {
+        $crate::import_options!(
+            $options.export_rule(stringify!($rule), stringify!($name));
+            $($($rest)*)?
+        )
+    };
+}
+
 #[allow(dead_code)]
 #[macro_export]
 macro_rules! rules {
     (
-        $(#[import ($rules_expr:expr) $(as $rules_name:ident)?])*
+        $(#[import ($rules_expr:expr) $(as $rules_name:ident)? $({ $($import_option:tt)* })?])*
+        $(#[matcher ($matcher_expr:expr) as $matcher_name:ident])*
         $(
-            $rule_name:ident {
+            $(#[policy($policy:ident)])?
+            $rule_name:ident $(< $($param:ident),+ >)? {
                 $(
                     ($( $tt:tt )*)
                     $(=> $transformer:expr;)?
+                    $(=> mut $context_transformer:expr;)?
                 )+
             }
-        )+
+        )*
     ) => {{
+        #[allow(unused_mut)]
         let mut rules = Vec::new();
 
+        #[allow(unused_mut)]
+        let mut policies: Vec<(std::string::String, $crate::rule::ChoicePolicy)> = Vec::new();
+
+        $(
+            let params: Vec<std::string::String> = vec![$($(stringify!($param).to_owned()),+)?];
+
+            $(
+                let mut rule: $crate::rule::Rule = $crate::rule!($rule_name: ($($tt)*) $(=> $transformer)? $(=> mut $context_transformer)?);
+                rule.params = params.clone();
+                rules.push(rule);
+            )*
+        )*
+
         $($(
-            rules.push($crate::rule!($rule_name: ($($tt)*) $(=> $transformer)?).into());
-        )*)*
+            policies.push((stringify!($rule_name).to_owned(), $crate::choice_policy!($policy)));
+        )?)*
 
-        let rules = $crate::rule::Rules::new(rules);
+        let rules = $crate::rule::Rules::with_policies(rules, policies);
 
         $(
             let mut rules = rules;
             let rules_name: Option<std::string::String> = None$(.or(Some(stringify!($rules_name).to_owned())))?;
-            rules.import(Into::<$crate::rule::Rules>::into($rules_expr), rules_name);
+            let options = $crate::import_options!(
+                $crate::options::ImportOptions { namespace: rules_name, ..Default::default() };
+                $($($import_option)*)?
+            );
+            rules.import_with(Into::<$crate::rule::Rules>::into($rules_expr), options);
+        )*
+
+        $(
+            let mut rules = rules;
+            rules.add_matcher(stringify!($matcher_name), $matcher_expr);
         )*
 
         rules
@@ -102,6 +255,42 @@ macro_rules! declare_rules {
             ) -> Result<$crate::result::ParseValue, $crate::result::ParseError> {
                 $crate::rule::Rules::from(Self).parse_entire(start_rule, input)
             }
+
+            pub fn parse_with<'a, I: $crate::input::IntoInput<'a>>(
+                &self,
+                start_rule: &str,
+                input: I,
+                options: $crate::options::ParseOptions<'_>,
+            ) -> Result<$crate::result::ParseValue, $crate::result::ParseError> {
+                $crate::rule::Rules::from(Self).parse_with(start_rule, input, options)
+            }
+
+            pub fn parse_entire_with<'a, I: $crate::input::IntoInput<'a>>(
+                &self,
+                start_rule: &str,
+                input: I,
+                options: $crate::options::ParseOptions<'_>,
+            ) -> Result<$crate::result::ParseValue, $crate::result::ParseError> {
+                $crate::rule::Rules::from(Self).parse_entire_with(start_rule, input, options)
+            }
+
+            pub fn parse_with_context<'a, I: $crate::input::IntoInput<'a>, C: $crate::context::ParseContext>(
+                &self,
+                start_rule: &str,
+                input: I,
+                context: &mut C,
+            ) -> Result<$crate::result::ParseValue, $crate::result::ParseError> {
+                $crate::rule::Rules::from(Self).parse_with_context(start_rule, input, context)
+            }
+
+            pub fn parse_entire_with_context<'a, I: $crate::input::IntoInput<'a>, C: $crate::context::ParseContext>(
+                &self,
+                start_rule: &str,
+                input: I,
+                context: &mut C,
+            ) -> Result<$crate::result::ParseValue, $crate::result::ParseError> {
+                $crate::rule::Rules::from(Self).parse_entire_with_context(start_rule, input, context)
+            }
         }
 
     };
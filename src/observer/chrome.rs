@@ -0,0 +1,113 @@
+use std::{
+    fmt::Write,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use super::ParseObserver;
+use crate::result::{LineInfo, Span};
+
+/// Records rules, transformers and backtracking in the Chrome trace event format,
+/// which can be viewed using `chrome://tracing` or [Perfetto](https://ui.perfetto.dev).
+#[derive(Debug)]
+pub struct ChromeTraceObserver {
+    start: Instant,
+    events: Vec<String>,
+}
+
+impl Default for ChromeTraceObserver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChromeTraceObserver {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        format!("{{\"traceEvents\":[\n{}\n]}}\n", self.events.join(",\n"))
+    }
+
+    pub fn write_to(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+
+    fn timestamp(&self, time: Instant) -> f64 {
+        time.saturating_duration_since(self.start).as_secs_f64() * 1_000_000.0
+    }
+
+    fn event(&mut self, name: &str, cat: &str, ph: char, time: Instant, extra: &str) {
+        let ts = self.timestamp(time);
+
+        self.events.push(format!(
+            "{{\"name\":\"{}\",\"cat\":\"{cat}\",\"ph\":\"{ph}\",\"ts\":{ts:.3},\"pid\":1,\"tid\":1{extra}}}",
+            escape(name)
+        ));
+    }
+}
+
+impl ParseObserver for ChromeTraceObserver {
+    fn rule_enter(&mut self, rule: &str, at: LineInfo) {
+        let extra = format!(
+            ",\"args\":{{\"pos\":{},\"line\":{},\"column\":{}}}",
+            at.pos, at.line, at.column
+        );
+
+        self.event(rule, "rule", 'B', Instant::now(), &extra);
+    }
+
+    fn rule_exit(&mut self, rule: &str, path: &[usize], span: Span, success: bool) {
+        let extra = format!(
+            ",\"args\":{{\"success\":{success},\"path\":\"{path:?}\",\"end\":{}}}",
+            span.end.pos
+        );
+
+        self.event(rule, "rule", 'E', Instant::now(), &extra);
+    }
+
+    fn backtrack(&mut self, rule: &str, path: &[usize], at: LineInfo) {
+        let extra = format!(
+            ",\"s\":\"t\",\"args\":{{\"rule\":\"{}\",\"path\":\"{path:?}\",\"pos\":{}}}",
+            escape(rule),
+            at.pos
+        );
+
+        self.event("backtrack", "backtrack", 'i', Instant::now(), &extra);
+    }
+
+    fn transformer(&mut self, rule: &str, path: &[usize], span: Span, elapsed: Duration) {
+        let now = Instant::now();
+        let start = now.checked_sub(elapsed).unwrap_or(now);
+
+        let extra = format!(
+            ",\"dur\":{:.3},\"args\":{{\"path\":\"{path:?}\",\"start\":{},\"end\":{}}}",
+            elapsed.as_secs_f64() * 1_000_000.0,
+            span.start.pos,
+            span.end.pos
+        );
+
+        self.event(rule, "transformer", 'X', start, &extra);
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,285 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use super::ParseObserver;
+use crate::{
+    result::{LineInfo, Span},
+    rule::{RulePart, RuleTree, Rules},
+};
+
+/// Records which alternatives of the rules were used over one or more parses,
+/// e.g. to find the branches of a grammar that a test suite never exercises.
+///
+/// ```text
+/// let mut coverage = Coverage::new();
+///
+/// for input in inputs {
+///     rules.parse_entire_with("start", input, ParseOptions::new().observer(&mut coverage))?;
+/// }
+///
+/// println!("{}", coverage.report(&rules));
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Coverage {
+    // the paths to every rule part that was tried
+    reached: HashSet<(String, Vec<usize>)>,
+    // the number of times each alternative (a path ending in `RuleTree::End`) matched
+    successes: HashMap<(String, Vec<usize>), usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CoverageStatus {
+    /// The alternative matched at least once.
+    Matched,
+    /// The alternative was tried, but never matched.
+    Failed,
+    /// The parser never got to the alternative.
+    Unreached,
+}
+
+impl Display for CoverageStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CoverageStatus::Matched => "matched",
+            CoverageStatus::Failed => "failed",
+            CoverageStatus::Unreached => "unreached",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlternativeCoverage {
+    pub rule: String,
+    /// The path to the alternative's end through the rule's merged alternatives,
+    /// as given to [`ParseObserver::rule_exit`].
+    pub path: Vec<usize>,
+    /// The alternative as it would be written in `rules!`.
+    pub alternative: String,
+    pub status: CoverageStatus,
+    pub successes: usize,
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lists every alternative of `rules` with whether it was used.
+    pub fn report(&self, rules: &Rules) -> CoverageReport {
+        let mut rule_names = rules.rule_names();
+        rule_names.sort();
+
+        let mut alternatives = Vec::new();
+
+        for rule in rule_names {
+            let mut ends = Vec::new();
+
+            ends_of(
+                rules.rule_trees(&rule).unwrap_or_default(),
+                &mut Vec::new(),
+                &mut Vec::new(),
+                &mut ends,
+            );
+
+            for (path, parts) in ends {
+                let key = (rule.clone(), path);
+
+                let successes = self.successes.get(&key).copied().unwrap_or(0);
+
+                let status = if successes > 0 {
+                    CoverageStatus::Matched
+                } else if (1..=key.1.len()).any(|len| {
+                    self.reached
+                        .contains(&(rule.clone(), key.1[..len].to_vec()))
+                }) {
+                    CoverageStatus::Failed
+                } else {
+                    CoverageStatus::Unreached
+                };
+
+                let parts = parts
+                    .iter()
+                    .map(|part| match part {
+                        RulePart::Recurse => rule.clone(),
+                        part => part.to_string(),
+                    })
+                    .collect::<Vec<_>>();
+
+                alternatives.push(AlternativeCoverage {
+                    alternative: format!("({})", parts.join(" ")),
+                    rule: key.0,
+                    path: key.1,
+                    status,
+                    successes,
+                });
+            }
+        }
+
+        CoverageReport { alternatives }
+    }
+
+    fn reach(&mut self, rule: &str, path: &[usize]) {
+        if !path.is_empty() {
+            self.reached.insert((rule.to_owned(), path.to_vec()));
+        }
+    }
+}
+
+// The path to every end of the trees, with the parts on the way
+fn ends_of<'r>(
+    trees: &'r [RuleTree],
+    path: &mut Vec<usize>,
+    parts: &mut Vec<&'r RulePart>,
+    ends: &mut Vec<(Vec<usize>, Vec<&'r RulePart>)>,
+) {
+    for (i, tree) in trees.iter().enumerate() {
+        path.push(i);
+
+        match tree {
+            RuleTree::Part { part, nexts } => {
+                parts.push(part);
+                ends_of(nexts, path, parts, ends);
+                parts.pop();
+            }
+            RuleTree::End { .. } => ends.push((path.clone(), parts.clone())),
+        }
+
+        path.pop();
+    }
+}
+
+impl ParseObserver for Coverage {
+    fn rule_exit(&mut self, rule: &str, path: &[usize], _span: Span, success: bool) {
+        // The end of a left recursive rule is reported without a path, after its last alternative
+        if success && !path.is_empty() {
+            self.reach(rule, path);
+
+            *self
+                .successes
+                .entry((rule.to_owned(), path.to_vec()))
+                .or_default() += 1;
+        }
+    }
+
+    fn terminal_match(&mut self, rule: &str, path: &[usize], _part: &RulePart, _span: Span) {
+        self.reach(rule, path);
+    }
+
+    fn terminal_fail(&mut self, rule: &str, path: &[usize], _part: &RulePart, _at: LineInfo) {
+        self.reach(rule, path);
+    }
+
+    fn nonterminal(&mut self, rule: &str, path: &[usize], _part: &RulePart, _at: LineInfo) {
+        self.reach(rule, path);
+    }
+
+    fn backtrack(&mut self, rule: &str, path: &[usize], _at: LineInfo) {
+        self.reach(rule, path);
+    }
+}
+
+/// The alternatives of the rules collected by a [`Coverage`], sorted by rule name and then
+/// in the order in which they are tried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageReport {
+    pub alternatives: Vec<AlternativeCoverage>,
+}
+
+impl CoverageReport {
+    pub fn count(&self, status: CoverageStatus) -> usize {
+        self.alternatives
+            .iter()
+            .filter(|alternative| alternative.status == status)
+            .count()
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "{} of {} alternatives matched, {} failed, {} never reached",
+            self.count(CoverageStatus::Matched),
+            self.alternatives.len(),
+            self.count(CoverageStatus::Failed),
+            self.count(CoverageStatus::Unreached)
+        )
+    }
+
+    /// Renders the report as a self-contained HTML page.
+    pub fn to_html(&self) -> String {
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Grammar coverage</title>\n<style>\n\
+            body { font-family: sans-serif; }\n\
+            td { font-family: monospace; padding: 2px 12px; }\n\
+            .matched { background: #d7f5d7; }\n\
+            .failed { background: #fbe3c0; }\n\
+            .unreached { background: #f8d0d0; }\n\
+            </style>\n</head>\n<body>\n",
+        );
+
+        html.push_str(&format!("<p>{}</p>\n<table>\n", self.summary()));
+        html.push_str("<tr><th>rule</th><th>alternative</th><th>status</th><th>count</th></tr>\n");
+
+        for alternative in &self.alternatives {
+            let status = alternative.status;
+
+            html.push_str(&format!(
+                "<tr class=\"{status}\"><td>{}</td><td>{}</td><td>{status}</td><td>{}</td></tr>\n",
+                escape(&alternative.rule),
+                escape(&alternative.alternative),
+                alternative.successes
+            ));
+        }
+
+        html.push_str("</table>\n</body>\n</html>\n");
+
+        html
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Display for CoverageReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rule_width = self
+            .alternatives
+            .iter()
+            .map(|alternative| alternative.rule.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+
+        let alternative_width = self
+            .alternatives
+            .iter()
+            .map(|alternative| alternative.alternative.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(11);
+
+        writeln!(
+            f,
+            "{:rule_width$}  {:alternative_width$}  {:9}  {:>8}",
+            "rule", "alternative", "status", "count"
+        )?;
+
+        for alternative in &self.alternatives {
+            writeln!(
+                f,
+                "{:rule_width$}  {:alternative_width$}  {:9}  {:>8}",
+                alternative.rule,
+                alternative.alternative,
+                alternative.status.to_string(),
+                alternative.successes
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "{}", self.summary())
+    }
+}
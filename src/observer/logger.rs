@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use super::ParseObserver;
+use crate::{
+    result::{LineInfo, Span},
+    rule::RulePart,
+};
+
+/// Forwards every event to the `log` crate at the `trace` level.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogObserver;
+
+impl ParseObserver for LogObserver {
+    fn rule_enter(&mut self, rule: &str, at: LineInfo) {
+        log::trace!("ENTER {rule} at {}", at.pos);
+    }
+
+    fn rule_exit(&mut self, rule: &str, path: &[usize], span: Span, success: bool) {
+        if success {
+            log::trace!("EXIT {rule}{path:?} {}..{}", span.start.pos, span.end.pos);
+        } else {
+            log::trace!("FAIL {rule}");
+        }
+    }
+
+    fn terminal_match(&mut self, rule: &str, path: &[usize], part: &RulePart, span: Span) {
+        log::trace!(
+            "LEX {part} in {rule}{path:?} {}..{}",
+            span.start.pos,
+            span.end.pos
+        );
+    }
+
+    fn terminal_fail(&mut self, rule: &str, path: &[usize], part: &RulePart, at: LineInfo) {
+        log::trace!("LEX FAIL {part} in {rule}{path:?} at {}", at.pos);
+    }
+
+    fn nonterminal(&mut self, rule: &str, path: &[usize], part: &RulePart, at: LineInfo) {
+        log::trace!("CALL {part} in {rule}{path:?} at {}", at.pos);
+    }
+
+    fn backtrack(&mut self, rule: &str, path: &[usize], at: LineInfo) {
+        log::trace!("BACKTRACK {rule}{path:?} at {}", at.pos);
+    }
+
+    fn transformer(&mut self, rule: &str, path: &[usize], _span: Span, elapsed: Duration) {
+        log::trace!("TRANSFORM {rule}{path:?} in {elapsed:?}");
+    }
+}
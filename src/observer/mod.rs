@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use crate::{
+    result::{LineInfo, Span},
+    rule::RulePart,
+};
+
+mod trace;
+pub use trace::TraceObserver;
+
+mod chrome;
+pub use chrome::ChromeTraceObserver;
+
+mod logger;
+pub use logger::LogObserver;
+
+mod profiler;
+pub use profiler::{AlternativeProfile, ProfileReport, Profiler, RuleProfile};
+
+mod coverage;
+pub use coverage::{AlternativeCoverage, Coverage, CoverageReport, CoverageStatus};
+
+/// Receives events while parsing, set using [`ParseOptions::observer`](crate::options::ParseOptions::observer).
+///
+/// Every method has an empty default implementation, so only the interesting events need to be implemented.
+///
+/// A `path` is the list of indices taken through a rule's (merged) alternatives to reach the current rule part.
+/// The first index is the alternative being tried.
+pub trait ParseObserver {
+    /// A rule (or the left recursive growth of a rule) starts being parsed.
+    fn rule_enter(&mut self, _rule: &str, _at: LineInfo) {}
+
+    /// A rule finished parsing. If it was successful, `path` leads to the alternative that matched,
+    /// otherwise it is empty.
+    fn rule_exit(&mut self, _rule: &str, _path: &[usize], _span: Span, _success: bool) {}
+
+    /// A terminal (`Term` or `Not`) matched.
+    fn terminal_match(&mut self, _rule: &str, _path: &[usize], _part: &RulePart, _span: Span) {}
+
+    /// A terminal (`Term` or `Not`) didn't match.
+    fn terminal_fail(&mut self, _rule: &str, _path: &[usize], _part: &RulePart, _at: LineInfo) {}
+
+    /// A `NonTerm` (or the left recursion of a rule) is about to be parsed, just before
+    /// the rule it refers to is entered.
+    fn nonterminal(&mut self, _rule: &str, _path: &[usize], _part: &RulePart, _at: LineInfo) {}
+
+    /// The parser went back to `at` to try the next alternative, which is at `path`.
+    fn backtrack(&mut self, _rule: &str, _path: &[usize], _at: LineInfo) {}
+
+    /// A transformer was run, taking `elapsed`.
+    fn transformer(&mut self, _rule: &str, _path: &[usize], _span: Span, _elapsed: Duration) {}
+}
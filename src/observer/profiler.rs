@@ -0,0 +1,251 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use super::ParseObserver;
+use crate::{
+    result::{LineInfo, Span},
+    rule::RulePart,
+};
+
+/// Collects statistics about each rule and alternative over one or more parses.
+///
+/// ```text
+/// let mut profiler = Profiler::new();
+///
+/// rules.parse_entire_with("start", input, ParseOptions::new().observer(&mut profiler))?;
+///
+/// println!("{}", profiler.report());
+/// ```
+#[derive(Debug, Default)]
+pub struct Profiler {
+    rules: HashMap<String, RuleProfile>,
+    alternatives: HashMap<(String, Vec<usize>), AlternativeProfile>,
+    // start time of every rule currently being parsed
+    entered: Vec<Instant>,
+    // the furthest position the parser has read up to since it last went back
+    head: usize,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RuleProfile {
+    pub rule: String,
+    pub invocations: usize,
+    pub successes: usize,
+    pub failures: usize,
+    /// Characters consumed by successful invocations.
+    pub consumed: usize,
+    /// Characters that were read, and then read again after backtracking.
+    pub rescanned: usize,
+    /// Time spent in the rule, including the rules it uses.
+    pub time: Duration,
+    /// Time spent in the rule's transformers.
+    pub transformer_time: Duration,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AlternativeProfile {
+    pub rule: String,
+    /// The path to the alternative through the rule's merged alternatives.
+    pub path: Vec<usize>,
+    pub successes: usize,
+    /// The number of times the parser backtracked out of this alternative.
+    pub failures: usize,
+    pub consumed: usize,
+    pub transformer_time: Duration,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn report(&self) -> ProfileReport {
+        let mut rules = self.rules.values().cloned().collect::<Vec<_>>();
+        rules.sort_by(|a, b| {
+            (b.rescanned, b.invocations, &a.rule).cmp(&(a.rescanned, a.invocations, &b.rule))
+        });
+
+        let mut alternatives = self.alternatives.values().cloned().collect::<Vec<_>>();
+        alternatives.sort_by(|a, b| {
+            (b.failures + b.successes, &a.rule, &a.path).cmp(&(
+                a.failures + a.successes,
+                &b.rule,
+                &b.path,
+            ))
+        });
+
+        ProfileReport {
+            rules,
+            alternatives,
+        }
+    }
+
+    fn rule(&mut self, rule: &str) -> &mut RuleProfile {
+        self.rules
+            .entry(rule.to_owned())
+            .or_insert_with(|| RuleProfile {
+                rule: rule.to_owned(),
+                ..Default::default()
+            })
+    }
+
+    fn alternative(&mut self, rule: &str, path: &[usize]) -> &mut AlternativeProfile {
+        self.alternatives
+            .entry((rule.to_owned(), path.to_vec()))
+            .or_insert_with(|| AlternativeProfile {
+                rule: rule.to_owned(),
+                path: path.to_vec(),
+                ..Default::default()
+            })
+    }
+
+    fn go_back(&mut self, rule: &str, pos: usize) {
+        if self.head > pos {
+            let rescanned = self.head - pos;
+
+            self.rule(rule).rescanned += rescanned;
+            self.head = pos;
+        }
+    }
+}
+
+impl ParseObserver for Profiler {
+    fn rule_enter(&mut self, rule: &str, _at: LineInfo) {
+        self.rule(rule).invocations += 1;
+        self.entered.push(Instant::now());
+    }
+
+    fn rule_exit(&mut self, rule: &str, path: &[usize], span: Span, success: bool) {
+        let time = self
+            .entered
+            .pop()
+            .map(|start| start.elapsed())
+            .unwrap_or_default();
+
+        let consumed = span.end.pos.saturating_sub(span.start.pos);
+
+        let profile = self.rule(rule);
+        profile.time += time;
+
+        if success {
+            profile.successes += 1;
+            profile.consumed += consumed;
+
+            let alternative = self.alternative(rule, path);
+            alternative.successes += 1;
+            alternative.consumed += consumed;
+
+            // A rule can end before the furthest point that was read (left recursion, longest match)
+            self.go_back(rule, span.end.pos);
+        } else {
+            profile.failures += 1;
+        }
+    }
+
+    fn terminal_match(&mut self, _rule: &str, _path: &[usize], _part: &RulePart, span: Span) {
+        self.head = self.head.max(span.end.pos);
+    }
+
+    fn backtrack(&mut self, rule: &str, path: &[usize], at: LineInfo) {
+        // The previous sibling of `path` failed
+        if let Some((&last, prefix)) = path.split_last() {
+            let mut failed = prefix.to_vec();
+            failed.push(last.saturating_sub(1));
+
+            self.alternative(rule, &failed).failures += 1;
+        }
+
+        self.go_back(rule, at.pos);
+    }
+
+    fn transformer(&mut self, rule: &str, path: &[usize], _span: Span, elapsed: Duration) {
+        self.rule(rule).transformer_time += elapsed;
+        self.alternative(rule, path).transformer_time += elapsed;
+    }
+}
+
+/// The statistics collected by a [`Profiler`].
+///
+/// Rules are sorted by the number of characters rescanned due to backtracking, then by invocations.
+/// Alternatives are sorted by the number of times they were tried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileReport {
+    pub rules: Vec<RuleProfile>,
+    pub alternatives: Vec<AlternativeProfile>,
+}
+
+impl Display for ProfileReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self
+            .rules
+            .iter()
+            .map(|rule| rule.rule.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+
+        writeln!(
+            f,
+            "{:width$}  {:>8}  {:>8}  {:>8}  {:>10}  {:>10}  {:>12}  {:>12}",
+            "rule", "calls", "ok", "failed", "consumed", "rescanned", "time", "transformers"
+        )?;
+
+        for rule in &self.rules {
+            writeln!(
+                f,
+                "{:width$}  {:>8}  {:>8}  {:>8}  {:>10}  {:>10}  {:>12}  {:>12}",
+                rule.rule,
+                rule.invocations,
+                rule.successes,
+                rule.failures,
+                rule.consumed,
+                rule.rescanned,
+                format!("{:.3?}", rule.time),
+                format!("{:.3?}", rule.transformer_time),
+            )?;
+        }
+
+        writeln!(f)?;
+
+        let alternatives = self
+            .alternatives
+            .iter()
+            .map(|alternative| {
+                (
+                    format!("{}{:?}", alternative.rule, alternative.path),
+                    alternative,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let width = alternatives
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(11);
+
+        writeln!(
+            f,
+            "{:width$}  {:>8}  {:>8}  {:>10}  {:>12}",
+            "alternative", "ok", "failed", "consumed", "transformers"
+        )?;
+
+        for (name, alternative) in alternatives {
+            writeln!(
+                f,
+                "{:width$}  {:>8}  {:>8}  {:>10}  {:>12}",
+                name,
+                alternative.successes,
+                alternative.failures,
+                alternative.consumed,
+                format!("{:.3?}", alternative.transformer_time),
+            )?;
+        }
+
+        Ok(())
+    }
+}
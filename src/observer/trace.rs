@@ -0,0 +1,116 @@
+use std::{fmt::Display, fmt::Write, time::Duration};
+
+use super::ParseObserver;
+use crate::{
+    result::{LineInfo, Span},
+    rule::RulePart,
+};
+
+/// Renders the events of a parse as an indented tree:
+///
+/// ```text
+/// start 1:1
+///   expr 1:1
+///     + "x" 1:1-1:2
+///   expr matched 1:1-1:2 [1]
+/// start matched 1:1-1:2 [0]
+/// ```
+#[derive(Debug)]
+pub struct TraceObserver {
+    output: String,
+    depth: usize,
+    terminals: bool,
+}
+
+impl Default for TraceObserver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TraceObserver {
+    pub fn new() -> Self {
+        Self {
+            output: String::new(),
+            depth: 0,
+            terminals: true,
+        }
+    }
+
+    /// Whether terminal matches and failures are included. Defaults to `true`.
+    pub fn terminals(mut self, terminals: bool) -> Self {
+        self.terminals = terminals;
+        self
+    }
+
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    pub fn into_output(self) -> String {
+        self.output
+    }
+
+    fn line(&mut self, args: std::fmt::Arguments) {
+        for _ in 0..self.depth {
+            self.output.push_str("  ");
+        }
+
+        let _ = self.output.write_fmt(args);
+        self.output.push('\n');
+    }
+}
+
+impl ParseObserver for TraceObserver {
+    fn rule_enter(&mut self, rule: &str, at: LineInfo) {
+        self.line(format_args!("{rule} {}:{}", at.line, at.column));
+        self.depth += 1;
+    }
+
+    fn rule_exit(&mut self, rule: &str, path: &[usize], span: Span, success: bool) {
+        self.depth = self.depth.saturating_sub(1);
+
+        let Span { start, end } = span;
+
+        if success {
+            self.line(format_args!(
+                "{rule} matched {}:{}-{}:{} {path:?}",
+                start.line, start.column, end.line, end.column
+            ));
+        } else {
+            self.line(format_args!("{rule} failed"));
+        }
+    }
+
+    fn terminal_match(&mut self, _rule: &str, _path: &[usize], part: &RulePart, span: Span) {
+        if self.terminals {
+            let Span { start, end } = span;
+
+            self.line(format_args!(
+                "+ {part} {}:{}-{}:{}",
+                start.line, start.column, end.line, end.column
+            ));
+        }
+    }
+
+    fn terminal_fail(&mut self, _rule: &str, _path: &[usize], part: &RulePart, at: LineInfo) {
+        if self.terminals {
+            self.line(format_args!("- {part} {}:{}", at.line, at.column));
+        }
+    }
+
+    fn backtrack(&mut self, _rule: &str, path: &[usize], at: LineInfo) {
+        self.line(format_args!(
+            "backtrack to {path:?} {}:{}",
+            at.line, at.column
+        ));
+    }
+
+    fn transformer(&mut self, _rule: &str, _path: &[usize], _span: Span, _elapsed: Duration) {}
+}
+
+impl Display for TraceObserver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.output)
+    }
+}
@@ -0,0 +1,196 @@
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{context::ParseContext, observer::ParseObserver, result::AbortReason};
+
+/// Options for a single parse.
+///
+/// By default there are no limits. When a limit is exceeded, parsing stops immediately
+/// (without backtracking) with [`ParseError::Aborted`](crate::result::ParseError::Aborted).
+///
+/// The observer and context are borrowed mutably, so a clone of the options has neither.
+#[derive(Default)]
+pub struct ParseOptions<'o> {
+    /// The maximum number of steps the parser may take.
+    /// A step is roughly one attempt to match a rule part.
+    pub max_steps: Option<usize>,
+    /// The maximum nesting depth of rules.
+    pub max_depth: Option<usize>,
+    /// The maximum number of characters that may be consumed from the input.
+    pub max_input_length: Option<usize>,
+    /// The point in time after which parsing is aborted.
+    pub deadline: Option<Instant>,
+    /// Parsing is aborted once this is set to `true`, for example from another thread.
+    pub cancel: Option<Arc<AtomicBool>>,
+    /// Receives events while parsing. See [`ParseObserver`].
+    pub observer: Option<&'o mut dyn ParseObserver>,
+    /// State that transformers can access. See [`ParseContext`].
+    pub context: Option<&'o mut dyn ParseContext>,
+}
+
+impl<'o> ParseOptions<'o> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    pub fn max_input_length(mut self, max_input_length: usize) -> Self {
+        self.max_input_length = Some(max_input_length);
+        self
+    }
+
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Sets the deadline to `timeout` from now.
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.deadline(Instant::now() + timeout)
+    }
+
+    pub fn cancel(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    pub fn observer(mut self, observer: &'o mut dyn ParseObserver) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    pub fn context(mut self, context: &'o mut dyn ParseContext) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    pub(crate) fn check(&self, steps: usize, depth: usize, pos: usize) -> Option<AbortReason> {
+        if let Some(cancel) = &self.cancel {
+            if cancel.load(Ordering::Relaxed) {
+                return Some(AbortReason::Cancelled);
+            }
+        }
+
+        match self.max_steps {
+            Some(max_steps) if steps > max_steps => return Some(AbortReason::Steps(max_steps)),
+            _ => {}
+        }
+
+        match self.max_depth {
+            Some(max_depth) if depth > max_depth => return Some(AbortReason::Depth(max_depth)),
+            _ => {}
+        }
+
+        match self.max_input_length {
+            Some(max_input_length) if pos > max_input_length => {
+                return Some(AbortReason::InputLength(max_input_length))
+            }
+            _ => {}
+        }
+
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Some(AbortReason::Deadline),
+            _ => None,
+        }
+    }
+}
+
+impl<'o> Clone for ParseOptions<'o> {
+    fn clone(&self) -> Self {
+        Self {
+            max_steps: self.max_steps,
+            max_depth: self.max_depth,
+            max_input_length: self.max_input_length,
+            deadline: self.deadline,
+            cancel: self.cancel.clone(),
+            observer: None,
+            context: None,
+        }
+    }
+}
+
+impl<'o> Debug for ParseOptions<'o> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParseOptions")
+            .field("max_steps", &self.max_steps)
+            .field("max_depth", &self.max_depth)
+            .field("max_input_length", &self.max_input_length)
+            .field("deadline", &self.deadline)
+            .field("cancel", &self.cancel)
+            .field("observer", &self.observer.as_ref().map(|_| ".."))
+            .field("context", &self.context.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+/// Options for importing rules with [`Rules::import_with`](crate::rule::Rules::import_with).
+///
+/// By default, imported rules are merged with existing rules of the same name.
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    /// The namespace the imported rules are put in, e.g. `float` for `float::float`.
+    pub namespace: Option<String>,
+    /// Imported rules that are replaced by importing rules, as `(rule, replacement)`.
+    pub overrides: Vec<(String, String)>,
+    /// Imported rules that are merged into importing rules, as `(rule, extended rule)`.
+    pub extends: Vec<(String, String)>,
+    /// Whether rules starting with `_` are hidden, so that the importing rules can't refer to them.
+    pub hide_private: bool,
+    /// Imported rules that are also available under a new name, as `(rule, new name)`.
+    pub exports: Vec<(String, String)>,
+}
+
+impl ImportOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Replaces the imported rule with the importing rule `replacement`, also where
+    /// the imported rules refer to it.
+    pub fn override_rule(
+        mut self,
+        rule: impl Into<String>,
+        replacement: impl Into<String>,
+    ) -> Self {
+        self.overrides.push((rule.into(), replacement.into()));
+        self
+    }
+
+    /// Adds the alternatives of the imported rule to the importing rule `extended`,
+    /// which the imported rules then refer to instead.
+    pub fn extend_rule(mut self, rule: impl Into<String>, extended: impl Into<String>) -> Self {
+        self.extends.push((rule.into(), extended.into()));
+        self
+    }
+
+    pub fn hide_private(mut self) -> Self {
+        self.hide_private = true;
+        self
+    }
+
+    /// Makes the imported rule available as `name` (without a namespace).
+    pub fn export_rule(mut self, rule: impl Into<String>, name: impl Into<String>) -> Self {
+        self.exports.push((rule.into(), name.into()));
+        self
+    }
+}
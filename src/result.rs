@@ -56,6 +56,43 @@ pub enum ParseError {
         col: usize,
         error: Box<dyn Error>,
     },
+    PredicateFailed {
+        current_rule: String,
+        pos: usize,
+        row: usize,
+        col: usize,
+    },
+    Aborted {
+        current_rule: String,
+        reason: AbortReason,
+        pos: usize,
+        row: usize,
+        col: usize,
+    },
+}
+
+/// Why a parse was aborted. See [`ParseOptions`](crate::options::ParseOptions).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AbortReason {
+    Steps(usize),
+    Depth(usize),
+    InputLength(usize),
+    Deadline,
+    Cancelled,
+}
+
+impl Display for AbortReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AbortReason::Steps(max) => f.write_fmt(format_args!("step limit of {max} exceeded")),
+            AbortReason::Depth(max) => f.write_fmt(format_args!("depth limit of {max} exceeded")),
+            AbortReason::InputLength(max) => {
+                f.write_fmt(format_args!("input length limit of {max} exceeded"))
+            }
+            AbortReason::Deadline => f.write_str("deadline exceeded"),
+            AbortReason::Cancelled => f.write_str("cancelled"),
+        }
+    }
 }
 
 impl Error for ParseError {
@@ -107,6 +144,19 @@ impl Display for ParseError {
                 col,
                 error,
             } => f.write_fmt(format_args!("Error while transforming rule '{current_rule}' at position {pos} (row {row}, column {col}): {error}")),
+            ParseError::PredicateFailed {
+                current_rule,
+                pos,
+                row,
+                col,
+            } => f.write_fmt(format_args!("Predicate failed at position {pos} (row {row}, column {col}) while parsing rule '{current_rule}'")),
+            ParseError::Aborted {
+                current_rule,
+                reason,
+                pos,
+                row,
+                col,
+            } => f.write_fmt(format_args!("Parsing aborted at position {pos} (row {row}, column {col}) while parsing rule '{current_rule}': {reason}")),
         }
     }
 }
@@ -149,7 +199,7 @@ impl Default for LineInfo {
 
 impl PartialOrd for LineInfo {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.pos.cmp(&other.pos))
+        Some(self.cmp(other))
     }
 }
 
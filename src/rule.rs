@@ -1,26 +1,290 @@
 use std::{
+    any::Any,
     cmp::Ordering,
     collections::{HashMap, HashSet},
     error::Error,
-    fmt::{Debug, Write},
+    fmt::Display,
     marker::PhantomData,
     rc::Rc,
+    time::Instant,
 };
 
+use crate::context::{ContextError, ParseContext};
 use crate::input::{Input, IntoInput};
+use crate::observer::ParseObserver;
+use crate::options::{ImportOptions, ParseOptions};
 
 use super::result::*;
 
 pub type ParseBuffer<'a> = &'a mut dyn FnMut(usize) -> ParseValue;
 
-pub type Transformer = Rc<dyn Fn(ParseBuffer, Span) -> ParseValue>;
+pub type TransformerFn = dyn Fn(ParseBuffer, Span) -> ParseValue;
+
+pub type ContextTransformerFn = dyn Fn(ParseBuffer, Span, &mut dyn Any) -> ParseValue;
+
+#[derive(Clone)]
+pub enum Transformer {
+    Plain(Rc<TransformerFn>),
+    /// A transformer that also gets the parse context (see [`ParseContext`]).
+    WithContext(Rc<ContextTransformerFn>),
+}
+
+impl Transformer {
+    pub fn new(f: impl Fn(ParseBuffer, Span) -> ParseValue + 'static) -> Self {
+        Transformer::Plain(Rc::new(f))
+    }
+
+    /// Creates a transformer that gets the parse context, which has to be of type `C`.
+    /// If there is no context of that type, the transformer results in a [`ContextError`].
+    pub fn with_context<C: 'static>(
+        f: impl Fn(ParseBuffer, Span, &mut C) -> ParseValue + 'static,
+    ) -> Self {
+        Transformer::WithContext(Rc::new(move |v, span, context| {
+            match context.downcast_mut::<C>() {
+                Some(context) => f(v, span, context),
+                None => ContextError::new::<C>().into_error(),
+            }
+        }))
+    }
+
+    pub fn uses_context(&self) -> bool {
+        matches!(self, Transformer::WithContext(_))
+    }
+}
+
+pub type PredicateFn = dyn Fn(&[ParseValue]) -> bool;
+
+pub type ContextPredicateFn = dyn Fn(&[ParseValue], &mut dyn Any) -> bool;
+
+/// A check on the values parsed so far in an alternative.
+/// If it returns `false`, the alternative fails the same way as when a literal doesn't match,
+/// so the other alternatives are still tried.
+#[derive(Clone)]
+pub enum Predicate {
+    Plain(Rc<PredicateFn>),
+    /// A predicate that also gets the parse context (see [`ParseContext`]).
+    WithContext(Rc<ContextPredicateFn>),
+}
+
+impl Predicate {
+    pub fn new(f: impl Fn(&[ParseValue]) -> bool + 'static) -> Self {
+        Predicate::Plain(Rc::new(f))
+    }
+
+    /// Creates a predicate that gets the parse context, which has to be of type `C`.
+    /// If there is no context of that type, the predicate fails.
+    pub fn with_context<C: 'static>(f: impl Fn(&[ParseValue], &mut C) -> bool + 'static) -> Self {
+        Predicate::WithContext(Rc::new(move |v, context| {
+            context
+                .downcast_mut::<C>()
+                .is_some_and(|context| f(v, context))
+        }))
+    }
+}
+
+// Predicates can't be compared, so only the same predicate is equal to itself
+impl PartialEq for Predicate {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Predicate::Plain(a), Predicate::Plain(b)) => Rc::ptr_eq(a, b),
+            (Predicate::WithContext(a), Predicate::WithContext(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Predicate {}
+
+impl std::fmt::Debug for Predicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Predicate::Plain(_) => f.write_str("Predicate::Plain(..)"),
+            Predicate::WithContext(_) => f.write_str("Predicate::WithContext(..)"),
+        }
+    }
+}
+
+pub type MatcherFn = dyn Fn(&mut dyn crate::input::MatcherInput) -> Option<ParseValue>;
+
+/// How a case-insensitive literal is compared with the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CaseFolding {
+    /// Only ASCII letters are compared case-insensitively.
+    Ascii,
+    /// Characters are compared after Unicode case folding, so `"straße"` also matches `"STRASSE"`.
+    Unicode,
+}
+
+impl CaseFolding {
+    fn fold(self, c: char) -> impl Iterator<Item = char> {
+        let folded: Vec<char> = match self {
+            CaseFolding::Ascii => vec![c.to_ascii_lowercase()],
+            // Close to Unicode full case folding, e.g. 'ß', 'ẞ' and "SS" all become "ss"
+            CaseFolding::Unicode => c
+                .to_lowercase()
+                .flat_map(char::to_uppercase)
+                .flat_map(char::to_lowercase)
+                .collect(),
+        };
+
+        folded.into_iter()
+    }
+}
+
+/// A literal in a `Not` rule part.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Literal {
+    pub text: String,
+    /// `None` if the literal is case-sensitive
+    pub case_folding: Option<CaseFolding>,
+}
+
+impl Literal {
+    pub fn case_insensitive(text: impl Into<String>, case_folding: CaseFolding) -> Self {
+        Self {
+            text: text.into(),
+            case_folding: Some(case_folding),
+        }
+    }
+}
+
+impl<T: Into<String>> From<T> for Literal {
+    fn from(text: T) -> Self {
+        Self {
+            text: text.into(),
+            case_folding: None,
+        }
+    }
+}
+
+// Written the way the literal is written in `rules!`
+impl Display for Literal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = &self.text;
+
+        match self.case_folding {
+            None => f.write_fmt(format_args!("{text:?}")),
+            Some(CaseFolding::Ascii) => f.write_fmt(format_args!("(i {text:?})")),
+            Some(CaseFolding::Unicode) => f.write_fmt(format_args!("(iu {text:?})")),
+        }
+    }
+}
+
+/// A position in the input that a rule can require, without consuming any input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Anchor {
+    /// The end of the input
+    Eof,
+    /// The start of a line (or of the input)
+    Bol,
+    /// The end of a line (before `"\n"` or `"\r\n"`) or of the input
+    Eol,
+}
+
+impl Anchor {
+    fn matches<'i, I: Input<'i>>(self, input: &I) -> bool {
+        match self {
+            Anchor::Eof => input.clone().next().is_none(),
+            Anchor::Bol => input.pos() == 0 || input.row_col().1 == 1,
+            Anchor::Eol => {
+                let mut input = input.clone();
+
+                match input.next() {
+                    None | Some('\n') => true,
+                    Some('\r') => input.next() == Some('\n'),
+                    Some(_) => false,
+                }
+            }
+        }
+    }
+}
+
+impl Display for Anchor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Anchor::Eof => f.write_str("EOF"),
+            Anchor::Bol => f.write_str("BOL"),
+            Anchor::Eol => f.write_str("EOL"),
+        }
+    }
+}
+
+/// Where an `until` rule part stops.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum UntilStop {
+    Literal(Literal),
+    /// A rule (or matcher) that matches at the current position
+    Rule(String),
+}
+
+impl Display for UntilStop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UntilStop::Literal(literal) => Display::fmt(literal, f),
+            UntilStop::Rule(rule) if rule.contains("::") => f.write_fmt(format_args!("({rule})")),
+            UntilStop::Rule(rule) => f.write_str(rule),
+        }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RulePart {
     Term(String),
+    /// A literal that matches the input regardless of case
+    TermCaseInsensitive(String, CaseFolding),
     NonTerm(String),
     Recurse,
-    Not(HashSet<String>),
+    Not(HashSet<Literal>),
+    Predicate(Predicate),
+    /// A matcher registered using [`Rules::add_matcher`]
+    Matcher(String),
+    Anchor(Anchor),
+    /// Consumes characters until the input starts with one of the stops (or ends), returning them as a single token
+    Until(Vec<UntilStop>),
+    /// An instance of a parameterized rule, like `comma_list<item>`.
+    /// These are replaced by ordinary non-terminals when the [`Rules`] are created.
+    Instance(String, Vec<RulePart>),
+}
+
+// Written the way the part is written in `rules!`
+impl Display for RulePart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RulePart::Term(literal) => f.write_fmt(format_args!("{literal:?}")),
+            RulePart::TermCaseInsensitive(literal, case_folding) => Display::fmt(
+                &Literal::case_insensitive(literal.as_str(), *case_folding),
+                f,
+            ),
+            RulePart::NonTerm(rule) | RulePart::Matcher(rule) if rule.contains("::") => {
+                f.write_fmt(format_args!("({rule})"))
+            }
+            RulePart::NonTerm(rule) | RulePart::Matcher(rule) => f.write_str(rule),
+            RulePart::Recurse => f.write_str("<recurse>"),
+            RulePart::Anchor(anchor) => Display::fmt(anchor, f),
+            RulePart::Instance(rule, args) => {
+                f.write_fmt(format_args!("({})", Rules::instance_name(rule, args)))
+            }
+            RulePart::Until(stops) => {
+                f.write_str("(until")?;
+                for stop in stops {
+                    f.write_fmt(format_args!(" {stop}"))?;
+                }
+                f.write_str(")")
+            }
+            RulePart::Not(literals) => {
+                let mut literals = literals.iter().collect::<Vec<_>>();
+                literals.sort();
+
+                f.write_str("(!")?;
+                for literal in literals {
+                    f.write_fmt(format_args!(" {literal}"))?;
+                }
+                f.write_str(")")
+            }
+            RulePart::Predicate(Predicate::Plain(_)) => f.write_str("(? ..)"),
+            RulePart::Predicate(Predicate::WithContext(_)) => f.write_str("(? mut ..)"),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -36,6 +300,22 @@ pub enum RuleTree {
 }
 
 impl RuleTree {
+    // Replaces the transformers so that they also build a `SpanTree`, see `Rules::parse_entire_spanned`
+    fn spanned(self, rule: &str, depth: usize) -> RuleTree {
+        match self {
+            RuleTree::Part { part, nexts } => RuleTree::Part {
+                part,
+                nexts: nexts
+                    .into_iter()
+                    .map(|tree| tree.spanned(rule, depth + 1))
+                    .collect(),
+            },
+            RuleTree::End { transformer } => RuleTree::End {
+                transformer: Some(SpannedValue::transformer(transformer, rule, depth)),
+            },
+        }
+    }
+
     fn length(&self) -> usize {
         match self {
             RuleTree::Part { nexts, .. } => {
@@ -45,34 +325,115 @@ impl RuleTree {
         }
     }
 
-    fn add_namespace(self, namespace: &str) -> RuleTree {
+    // Renames the rules (and matchers) this tree refers to
+    fn rename(self, f: &impl Fn(String) -> String) -> RuleTree {
         match self {
             RuleTree::Part { part, nexts } => {
                 let part = match part {
-                    RulePart::NonTerm(rule) => RulePart::NonTerm(format!("{namespace}::{rule}")),
+                    RulePart::NonTerm(rule) => RulePart::NonTerm(f(rule)),
+                    RulePart::Matcher(rule) => RulePart::Matcher(f(rule)),
+                    RulePart::Until(stops) => RulePart::Until(
+                        stops
+                            .into_iter()
+                            .map(|stop| match stop {
+                                UntilStop::Rule(rule) => UntilStop::Rule(f(rule)),
+                                stop => stop,
+                            })
+                            .collect(),
+                    ),
                     part => part,
                 };
 
                 RuleTree::Part {
                     part,
-                    nexts: nexts
-                        .into_iter()
-                        .map(|tree| tree.add_namespace(namespace))
-                        .collect(),
+                    nexts: nexts.into_iter().map(|tree| tree.rename(f)).collect(),
                 }
             }
             tree => tree,
         }
     }
+
+    // Turns non-terminals which refer to matchers into `RulePart::Matcher`s.
+    // Returns whether anything was changed
+    fn resolve_matchers(&mut self, matchers: &HashMap<String, Rc<MatcherFn>>) -> bool {
+        match self {
+            RuleTree::Part { part, nexts } => {
+                let mut changed = false;
+
+                if let RulePart::NonTerm(rule) = part {
+                    if matchers.contains_key(rule) {
+                        *part = RulePart::Matcher(std::mem::take(rule));
+                        changed = true;
+                    }
+                }
+
+                for tree in nexts {
+                    changed |= tree.resolve_matchers(matchers);
+                }
+
+                changed
+            }
+            RuleTree::End { .. } => false,
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct Rule {
     pub name: String,
+    /// The parameters of a parameterized rule, which are used in `parts` like non-terminals
+    pub params: Vec<String>,
     pub parts: Vec<RulePart>,
     pub transformer: Option<Transformer>,
 }
 
+impl Rule {
+    // Replaces the parameters of this (parameterized) rule with the given arguments.
+    // References to the rule itself (without arguments) become references to `instance`
+    fn substitute(&self, part: &RulePart, args: &[RulePart], instance: &str) -> RulePart {
+        let arg = |name: &String| {
+            self.params
+                .iter()
+                .position(|param| param == name)
+                .map(|i| &args[i])
+        };
+
+        match part {
+            RulePart::NonTerm(name) if *name == self.name => RulePart::NonTerm(instance.to_owned()),
+            RulePart::NonTerm(name) => arg(name).unwrap_or(part).clone(),
+            RulePart::Instance(rule, inner) => RulePart::Instance(
+                rule.clone(),
+                inner
+                    .iter()
+                    .map(|p| self.substitute(p, args, instance))
+                    .collect(),
+            ),
+            RulePart::Until(stops) => RulePart::Until(
+                stops
+                    .iter()
+                    .map(|stop| match stop {
+                        UntilStop::Rule(name) => match arg(name) {
+                            Some(RulePart::Term(literal)) => {
+                                UntilStop::Literal(Literal::from(literal.as_str()))
+                            }
+                            Some(RulePart::TermCaseInsensitive(literal, case_folding)) => {
+                                UntilStop::Literal(Literal::case_insensitive(
+                                    literal.as_str(),
+                                    *case_folding,
+                                ))
+                            }
+                            Some(RulePart::NonTerm(rule)) => UntilStop::Rule(rule.clone()),
+                            _ => stop.clone(),
+                        },
+                        stop => stop.clone(),
+                    })
+                    .collect(),
+            ),
+            part => part.clone(),
+        }
+    }
+}
+
 impl From<Rule> for RuleTree {
     fn from(val: Rule) -> Self {
         let mut tree = RuleTree::End {
@@ -101,11 +462,105 @@ impl From<Rule> for RuleTree {
     }
 }
 
+/// How a rule chooses between its alternatives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ChoicePolicy {
+    /// Alternatives sharing a prefix are merged and tried in a fixed order:
+    /// left recursion first, then predicates and anchors, then terminals (longest literal first), then matchers,
+    /// then non-terminals, then `Not`s and `until`s, and finally the empty alternative.
+    /// The first alternative to match wins.
+    #[default]
+    Heuristic,
+    /// PEG-style ordered choice: alternatives are tried in declaration order
+    /// and the first one to match wins.
+    Ordered,
+    /// Every alternative is tried and the one that consumes the most input wins.
+    /// Ties are resolved in declaration order.
+    ///
+    /// For left recursive rules, this applies to the base alternatives only;
+    /// the recursive alternatives still grow the match greedily.
+    ///
+    /// Results are not memoized: each alternative is parsed in full, including the rules it uses,
+    /// so `Longest` rules which use each other can take time exponential in how deeply they nest.
+    Longest,
+}
+
 #[derive(Clone)]
-pub struct Rules(HashMap<String, Vec<RuleTree>>);
+pub struct Rules {
+    trees: HashMap<String, Vec<RuleTree>>,
+    policies: HashMap<String, ChoicePolicy>,
+    matchers: HashMap<String, Rc<MatcherFn>>,
+    // imported rules (and matchers) that can only be used by the rules imported with them
+    hidden: HashSet<String>,
+}
+
+/// An instance of a parameterized rule which can't be created, found when the [`Rules`] are created.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstanceError {
+    /// There is no parameterized rule with the name of the instance
+    RuleNotFound { rule_name: String, instance: String },
+    /// The parameterized rule has a different number of parameters
+    WrongArgumentCount {
+        rule_name: String,
+        instance: String,
+        expected: usize,
+    },
+}
+
+impl Error for InstanceError {}
+
+impl Display for InstanceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstanceError::RuleNotFound {
+                rule_name,
+                instance,
+            } => f.write_fmt(format_args!(
+                "No parameterized rule for '{instance}' (in rule '{rule_name}')"
+            )),
+            InstanceError::WrongArgumentCount {
+                rule_name,
+                instance,
+                expected,
+            } => f.write_fmt(format_args!(
+                "'{instance}' (in rule '{rule_name}') should have {expected} argument(s)"
+            )),
+        }
+    }
+}
 
 impl Rules {
+    /// # Panics
+    ///
+    /// If an instance of a parameterized rule can't be created. See [`Rules::try_new`].
     pub fn new(rules: impl IntoIterator<Item = Rule>) -> Self {
+        Self::with_policies(rules, [])
+    }
+
+    /// Like [`Rules::new`], but with a [`ChoicePolicy`] for some of the rules.
+    /// Rules that are not given a policy use [`ChoicePolicy::Heuristic`].
+    pub fn with_policies(
+        rules: impl IntoIterator<Item = Rule>,
+        policies: impl IntoIterator<Item = (String, ChoicePolicy)>,
+    ) -> Self {
+        Self::try_with_policies(rules, policies).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Like [`Rules::new`], but returns an error if an instance of a parameterized rule refers to
+    /// a rule which isn't parameterized (or doesn't exist), or has the wrong number of arguments.
+    pub fn try_new(rules: impl IntoIterator<Item = Rule>) -> Result<Self, InstanceError> {
+        Self::try_with_policies(rules, [])
+    }
+
+    /// Like [`Rules::with_policies`], but returns an error like [`Rules::try_new`].
+    pub fn try_with_policies(
+        rules: impl IntoIterator<Item = Rule>,
+        policies: impl IntoIterator<Item = (String, ChoicePolicy)>,
+    ) -> Result<Self, InstanceError> {
+        let mut policies = policies.into_iter().collect::<HashMap<_, _>>();
+
+        let rules = Self::monomorphize(rules, &mut policies)?;
+
         let mut map: HashMap<String, Vec<RuleTree>> = HashMap::new();
 
         for rule in rules {
@@ -119,56 +574,323 @@ impl Rules {
             }
         }
 
-        Rules(
-            map.into_iter()
-                .map(|(rule_name, rule_trees)| (rule_name, Self::smush(rule_trees)))
+        Ok(Rules {
+            trees: map
+                .into_iter()
+                .map(|(rule_name, rule_trees)| {
+                    let policy = policies.get(&rule_name).copied().unwrap_or_default();
+
+                    (rule_name, Self::build(rule_trees, policy))
+                })
                 .collect(),
-        )
+            policies,
+            matchers: HashMap::new(),
+            hidden: HashSet::new(),
+        })
+    }
+
+    // Replaces the instances of parameterized rules with ordinary rules, one for each distinct instance.
+    // The parameterized rules themselves are left out
+    fn monomorphize(
+        rules: impl IntoIterator<Item = Rule>,
+        policies: &mut HashMap<String, ChoicePolicy>,
+    ) -> Result<Vec<Rule>, InstanceError> {
+        let (parameterized, mut rules): (Vec<_>, Vec<_>) =
+            rules.into_iter().partition(|rule| !rule.params.is_empty());
+
+        let mut templates: HashMap<String, Vec<Rule>> = HashMap::new();
+
+        for rule in parameterized {
+            templates.entry(rule.name.clone()).or_default().push(rule);
+        }
+
+        // The name of an instance isn't replaced by arguments, so every instance can be checked up front
+        for rule in rules.iter().chain(templates.values().flatten()) {
+            for part in &rule.parts {
+                Self::check_instances(&rule.name, part, &templates)?;
+            }
+        }
+
+        let mut instances = HashSet::new();
+
+        // New instances are added to the end, so that they get instantiated too
+        let mut i = 0;
+        while i < rules.len() {
+            let mut parts = std::mem::take(&mut rules[i].parts);
+
+            for part in &mut parts {
+                Self::instantiate(part, &templates, &mut instances, &mut rules, policies);
+            }
+
+            rules[i].parts = parts;
+            i += 1;
+        }
+
+        Ok(rules)
+    }
+
+    fn check_instances(
+        rule_name: &str,
+        part: &RulePart,
+        templates: &HashMap<String, Vec<Rule>>,
+    ) -> Result<(), InstanceError> {
+        let RulePart::Instance(rule, args) = part else {
+            return Ok(());
+        };
+
+        for arg in args {
+            Self::check_instances(rule_name, arg, templates)?;
+        }
+
+        let instance = Self::instance_name(rule, args);
+
+        match templates.get(rule) {
+            None => Err(InstanceError::RuleNotFound {
+                rule_name: rule_name.to_owned(),
+                instance,
+            }),
+            Some(templates) if templates.iter().all(|t| t.params.len() != args.len()) => {
+                Err(InstanceError::WrongArgumentCount {
+                    rule_name: rule_name.to_owned(),
+                    instance,
+                    expected: templates[0].params.len(),
+                })
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    fn instantiate(
+        part: &mut RulePart,
+        templates: &HashMap<String, Vec<Rule>>,
+        instances: &mut HashSet<String>,
+        rules: &mut Vec<Rule>,
+        policies: &mut HashMap<String, ChoicePolicy>,
+    ) {
+        let RulePart::Instance(rule, args) = part else {
+            return;
+        };
+
+        for arg in args.iter_mut() {
+            Self::instantiate(arg, templates, instances, rules, policies);
+        }
+
+        let name = Self::instance_name(rule, args);
+
+        if instances.insert(name.clone()) {
+            for template in templates
+                .get(rule.as_str())
+                .into_iter()
+                .flatten()
+                .filter(|template| template.params.len() == args.len())
+            {
+                rules.push(Rule {
+                    name: name.clone(),
+                    params: Vec::new(),
+                    parts: template
+                        .parts
+                        .iter()
+                        .map(|part| template.substitute(part, args, &name))
+                        .collect(),
+                    transformer: template.transformer.clone(),
+                });
+            }
+
+            if let Some(&policy) = policies.get(rule.as_str()) {
+                policies.insert(name.clone(), policy);
+            }
+        }
+
+        *part = RulePart::NonTerm(name);
+    }
+
+    fn instance_name(rule: &str, args: &[RulePart]) -> String {
+        let args = args
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!("{rule}<{args}>")
     }
 
-    #[allow(dead_code)]
     pub(crate) fn rule_names(&self) -> Vec<String> {
-        self.0.keys().cloned().collect()
+        self.trees.keys().cloned().collect()
+    }
+
+    /// Returns the trees of the given rule, in which alternatives that start the same way are merged.
+    pub fn rule_trees(&self, rule_name: &str) -> Option<&[RuleTree]> {
+        self.trees.get(rule_name).map(Vec::as_slice)
+    }
+
+    /// Returns the [`ChoicePolicy`] of the given rule.
+    pub fn policy(&self, rule_name: &str) -> ChoicePolicy {
+        self.policies.get(rule_name).copied().unwrap_or_default()
+    }
+
+    /// Registers a matcher: a function which recognises a terminal at the current position of the input.
+    /// On success, it returns the value of the terminal and leaves the input after it.
+    /// On failure, it returns `None`, and any input it consumed is given back.
+    ///
+    /// Rules refer to a matcher by its name, the same way as to a rule.
+    /// A matcher takes precedence over a rule with the same name.
+    pub fn add_matcher(
+        &mut self,
+        name: impl Into<String>,
+        matcher: impl Fn(&mut dyn crate::input::MatcherInput) -> Option<ParseValue> + 'static,
+    ) {
+        self.matchers.insert(name.into(), Rc::new(matcher));
+        self.resolve_matchers();
+    }
+
+    fn resolve_matchers(&mut self) {
+        for (rule_name, rule_trees) in self.trees.iter_mut() {
+            let mut changed = false;
+
+            for tree in rule_trees.iter_mut() {
+                changed |= tree.resolve_matchers(&self.matchers);
+            }
+
+            // A matcher is tried at a different point than a non-terminal
+            if changed {
+                let policy = self.policies.get(rule_name).copied().unwrap_or_default();
+
+                *rule_trees = Self::build(std::mem::take(rule_trees), policy);
+            }
+        }
     }
 
     // Adds the given Rules to this one, optionally adding a namespace
     pub fn import(&mut self, other: Rules, name: Option<String>) {
-        for (rule_name, rule_trees) in other.0.into_iter() {
-            let rule_name = if let Some(namespace) = &name {
-                format!("{namespace}::{rule_name}")
+        self.import_with(
+            other,
+            ImportOptions {
+                namespace: name,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Adds the given Rules to this one. See [`ImportOptions`] for how they can be combined.
+    pub fn import_with(&mut self, other: Rules, options: ImportOptions) {
+        // Makes hidden names unique, even when importing the same rules more than once
+        let hidden = self.hidden.len() + other.hidden.len();
+
+        let ImportOptions {
+            namespace,
+            overrides,
+            extends,
+            hide_private,
+            exports,
+        } = options;
+
+        // Whether an imported rule (or matcher) can only be used by the rules imported with it
+        let hides = |rule_name: &str| {
+            let private = rule_name
+                .rsplit("::")
+                .next()
+                .is_some_and(|name| name.starts_with('_'));
+
+            let replaced = overrides
+                .iter()
+                .chain(&extends)
+                .any(|(rule, _)| rule == rule_name);
+
+            other.hidden.contains(rule_name) || (hide_private && private && !replaced)
+        };
+
+        // The name of an imported rule (or matcher) in these rules
+        let rename = |rule_name: String| {
+            if let Some((_, name)) = overrides
+                .iter()
+                .chain(&extends)
+                .find(|(rule, _)| *rule == rule_name)
+            {
+                return name.clone();
+            }
+
+            // Rules which were already hidden keep their unique name
+            let unique = hides(&rule_name) && !other.hidden.contains(&rule_name);
+
+            let rule_name = match &namespace {
+                Some(namespace) => format!("{namespace}::{rule_name}"),
+                None => rule_name,
+            };
+
+            // A name which can't be written in `rules!`
+            if unique {
+                format!("{rule_name}#{hidden}")
             } else {
                 rule_name
-            };
+            }
+        };
 
-            match self.0.entry(rule_name) {
-                std::collections::hash_map::Entry::Occupied(mut o) => {
-                    let rule_trees = if let Some(namespace) = &name {
-                        rule_trees
-                            .into_iter()
-                            .map(|tree| tree.add_namespace(namespace))
-                            .collect::<Vec<_>>()
-                    } else {
-                        rule_trees
-                    };
+        let hidden_names = other
+            .trees
+            .keys()
+            .chain(other.matchers.keys())
+            .filter(|name| hides(name))
+            .map(|name| rename(name.clone()))
+            .collect::<Vec<_>>();
+
+        self.hidden.extend(hidden_names);
+
+        for (matcher_name, matcher) in other.matchers {
+            self.matchers.entry(rename(matcher_name)).or_insert(matcher);
+        }
+
+        for (rule_name, rule_trees) in other.trees.into_iter() {
+            if overrides.iter().any(|(rule, _)| *rule == rule_name) {
+                continue;
+            }
+
+            let policy = other.policies.get(&rule_name).copied();
+
+            let rule_name = rename(rule_name);
+
+            // The policy of an existing rule takes precedence over the imported one
+            if let Some(policy) = policy {
+                self.policies.entry(rule_name.clone()).or_insert(policy);
+            }
+
+            let policy = self.policy(&rule_name);
 
+            let rule_trees = rule_trees
+                .into_iter()
+                .map(|tree| tree.rename(&rename))
+                .collect::<Vec<_>>();
+
+            match self.trees.entry(rule_name) {
+                std::collections::hash_map::Entry::Occupied(mut o) => {
                     o.get_mut().extend(rule_trees);
 
-                    *o.get_mut() = Self::smush(std::mem::take(o.get_mut()));
+                    *o.get_mut() = Self::build(std::mem::take(o.get_mut()), policy);
                 }
                 std::collections::hash_map::Entry::Vacant(v) => {
-                    let rule_trees = if let Some(namespace) = &name {
-                        rule_trees
-                            .into_iter()
-                            .map(|tree| tree.add_namespace(namespace))
-                            .collect::<Vec<_>>()
-                    } else {
-                        rule_trees
-                    };
-
                     v.insert(rule_trees);
                 }
             }
         }
+
+        for (rule_name, name) in exports {
+            let rule = Rule {
+                name: name.clone(),
+                params: Vec::new(),
+                parts: vec![RulePart::NonTerm(rename(rule_name))],
+                transformer: None,
+            };
+
+            let policy = self.policy(&name);
+
+            let rule_trees = self.trees.entry(name).or_default();
+            rule_trees.push(rule.into());
+
+            *rule_trees = Self::build(std::mem::take(rule_trees), policy);
+        }
+
+        if !self.matchers.is_empty() {
+            self.resolve_matchers();
+        }
     }
 
     pub fn parse_entire<'a, I: IntoInput<'a>>(
@@ -176,29 +898,207 @@ impl Rules {
         start_rule: &str,
         input: I,
     ) -> Result<ParseValue, ParseError> {
-        parse(self, start_rule, input.into_input()).and_then(|(value, mut input)| {
-            let (row, col) = input.row_col();
+        self.parse_entire_with(start_rule, input, ParseOptions::default())
+    }
+
+    pub fn parse<'a, I: IntoInput<'a>>(
+        &self,
+        start_rule: &str,
+        input: I,
+    ) -> Result<ParseValue, ParseError> {
+        self.parse_with(start_rule, input, ParseOptions::default())
+    }
+
+    pub fn parse_entire_with<'a, I: IntoInput<'a>>(
+        &self,
+        start_rule: &str,
+        input: I,
+        mut options: ParseOptions<'_>,
+    ) -> Result<ParseValue, ParseError> {
+        // Like a `(EOF)` anchor after the start rule, which is where the error is reported
+        parse(self, start_rule, input.into_input(), &mut options).and_then(|(value, input)| {
+            if Anchor::Eof.matches(&input) {
+                Ok(value)
+            } else {
+                let (row, col) = input.row_col();
 
-            if let Some(char) = input.next() {
                 Err(ParseError::UnexpectedChar {
-                    current_rule: "<parse_entire>".to_owned(),
-                    char: Some(char),
-                    pos: input.pos() - 1,
+                    current_rule: start_rule.to_owned(),
+                    char: input.clone().next(),
+                    pos: input.pos(),
                     row,
                     col,
                 })
-            } else {
-                Ok(value)
             }
         })
     }
 
-    pub fn parse<'a, I: IntoInput<'a>>(
+    pub fn parse_with<'a, I: IntoInput<'a>>(
+        &self,
+        start_rule: &str,
+        input: I,
+        mut options: ParseOptions<'_>,
+    ) -> Result<ParseValue, ParseError> {
+        parse(self, start_rule, input.into_input(), &mut options).map(|x| x.0)
+    }
+
+    /// Like [`Rules::parse`], but also returns the input after the parsed value, so that parsing can go on
+    /// from there. This reads a stream (like a `TcpInput`) one value at a time.
+    pub fn parse_prefix<'i, I: Input<'i>>(
+        &self,
+        start_rule: &str,
+        input: I,
+    ) -> Result<(ParseValue, I), ParseError> {
+        self.parse_prefix_with(start_rule, input, ParseOptions::default())
+    }
+
+    /// Like [`Rules::parse_prefix`], but with [`ParseOptions`].
+    pub fn parse_prefix_with<'i, I: Input<'i>>(
+        &self,
+        start_rule: &str,
+        input: I,
+        mut options: ParseOptions<'_>,
+    ) -> Result<(ParseValue, I), ParseError> {
+        parse(self, start_rule, input, &mut options)
+    }
+
+    /// Like [`Rules::parse_entire`], but transformers get access to `context`.
+    /// See [`ParseContext`].
+    pub fn parse_entire_with_context<'a, I: IntoInput<'a>, C: ParseContext>(
+        &self,
+        start_rule: &str,
+        input: I,
+        context: &mut C,
+    ) -> Result<ParseValue, ParseError> {
+        self.parse_entire_with(start_rule, input, ParseOptions::new().context(context))
+    }
+
+    /// Like [`Rules::parse`], but transformers get access to `context`.
+    /// See [`ParseContext`].
+    pub fn parse_with_context<'a, I: IntoInput<'a>, C: ParseContext>(
         &self,
         start_rule: &str,
         input: I,
+        context: &mut C,
     ) -> Result<ParseValue, ParseError> {
-        parse(self, start_rule, input.into_input()).map(|x| x.0)
+        self.parse_with(start_rule, input, ParseOptions::new().context(context))
+    }
+
+    /// Like [`Rules::parse_entire`], but also returns where each value made by a transformer came from.
+    ///
+    /// The tree is `None` if the value wasn't made by a transformer (e.g. a [`Token`]).
+    pub fn parse_entire_spanned<'a, I: IntoInput<'a>>(
+        &self,
+        start_rule: &str,
+        input: I,
+    ) -> Result<(ParseValue, Option<SpanTree>), ParseError> {
+        let spanned = Rules {
+            trees: self
+                .trees
+                .iter()
+                .map(|(rule, trees)| {
+                    let trees = trees
+                        .iter()
+                        .map(|tree| tree.clone().spanned(rule, 0))
+                        .collect();
+
+                    (rule.clone(), trees)
+                })
+                .collect(),
+            policies: self.policies.clone(),
+            matchers: self.matchers.clone(),
+            hidden: self.hidden.clone(),
+        };
+
+        let value = spanned.parse_entire(start_rule, input)?;
+
+        Ok(match value.downcast::<SpannedValue>() {
+            Ok(spanned) => (spanned.value, Some(spanned.tree)),
+            Err(value) => (value, None),
+        })
+    }
+
+    fn build(trees: Vec<RuleTree>, policy: ChoicePolicy) -> Vec<RuleTree> {
+        match policy {
+            ChoicePolicy::Heuristic => Self::smush(trees),
+            ChoicePolicy::Ordered => Self::smush_ordered(trees, true),
+            ChoicePolicy::Longest => Self::smush_ordered(trees, false),
+        }
+    }
+
+    // Keeps the declaration order, except that left recursive alternatives are moved to the front
+    // (the parser expects them there). Only adjacent alternatives which start with the same part
+    // are merged (and only if `merge` is set), so that merging doesn't change which alternative is tried first.
+    fn smush_ordered(trees: Vec<RuleTree>, merge: bool) -> Vec<RuleTree> {
+        let (recursive, rest): (Vec<_>, Vec<_>) = trees.into_iter().partition(|tree| {
+            matches!(
+                tree,
+                RuleTree::Part {
+                    part: RulePart::Recurse,
+                    ..
+                }
+            )
+        });
+
+        let mut v: Vec<RuleTree> = Vec::new();
+
+        if !recursive.is_empty() {
+            let nexts = recursive
+                .into_iter()
+                .flat_map(|tree| match tree {
+                    RuleTree::Part { nexts, .. } => nexts,
+                    RuleTree::End { .. } => unreachable!(),
+                })
+                .collect();
+
+            v.push(RuleTree::Part {
+                part: RulePart::Recurse,
+                nexts: Self::smush_ordered(nexts, true),
+            });
+        }
+
+        for tree in rest {
+            match (v.last_mut(), tree) {
+                (Some(RuleTree::Part { part: p, nexts: n }), RuleTree::Part { part, nexts })
+                    if merge && p == &part =>
+                {
+                    n.extend(nexts);
+
+                    let nexts = std::mem::take(n);
+                    *n = Self::smush_ordered(nexts, true);
+                }
+                // An empty alternative always matches, so the second one could never be reached
+                (Some(RuleTree::End { .. }), RuleTree::End { .. }) if merge => {}
+                (_, RuleTree::Part { part, nexts }) => v.push(RuleTree::Part {
+                    part,
+                    nexts: Self::smush_ordered(nexts, true),
+                }),
+                (_, tree) => v.push(tree),
+            }
+        }
+
+        v
+    }
+
+    // The order in which the heuristic tries the kinds of rule parts
+    fn rank(part: &RulePart) -> u8 {
+        match part {
+            // The parser expects left recursion first
+            RulePart::Recurse => 0,
+            // Predicates and anchors are cheap to check
+            RulePart::Predicate(_) | RulePart::Anchor(_) => 1,
+            RulePart::Term(_) | RulePart::TermCaseInsensitive(..) => 2,
+            RulePart::Matcher(_) => 3,
+            RulePart::NonTerm(_) | RulePart::Instance(..) => 4,
+            RulePart::Not(_) | RulePart::Until(_) => 5,
+        }
+    }
+
+    fn literal(part: &RulePart) -> Option<&str> {
+        match part {
+            RulePart::Term(literal) | RulePart::TermCaseInsensitive(literal, _) => Some(literal),
+            _ => None,
+        }
     }
 
     fn smush(trees: Vec<RuleTree>) -> Vec<RuleTree> {
@@ -259,28 +1159,28 @@ impl Rules {
                 }
             });
 
-        v.sort_by(|a, b| match (b, a) {
-            (RuleTree::Part { .. }, RuleTree::End { .. }) => Ordering::Greater,
-            (RuleTree::End { .. }, RuleTree::Part { .. }) => Ordering::Less,
-            (RuleTree::Part { part: p0, .. }, RuleTree::Part { part: p1, .. }) => match (p0, p1) {
-                (RulePart::Recurse, RulePart::Recurse) => unreachable!(),
-                (RulePart::Recurse, _) => Ordering::Greater,
-                (_, RulePart::Recurse) => Ordering::Less,
-                (RulePart::Term(lit0), RulePart::Term(lit1)) => {
-                    match lit0.chars().count().cmp(&lit1.chars().count()) {
-                        Ordering::Equal => b.length().cmp(&a.length()),
-                        ord => ord,
+        v.sort_by(|a, b| match (a, b) {
+            (RuleTree::Part { .. }, RuleTree::End { .. }) => Ordering::Less,
+            (RuleTree::End { .. }, RuleTree::Part { .. }) => Ordering::Greater,
+            (RuleTree::Part { part: p0, .. }, RuleTree::Part { part: p1, .. }) => {
+                Self::rank(p0).cmp(&Self::rank(p1)).then_with(|| {
+                    match (Self::literal(p0), Self::literal(p1)) {
+                        // The longest literal first
+                        (Some(lit0), Some(lit1)) => lit1
+                            .chars()
+                            .count()
+                            .cmp(&lit0.chars().count())
+                            .then_with(|| b.length().cmp(&a.length())),
+                        _ => match (p0, p1) {
+                            (RulePart::Matcher(_), RulePart::Matcher(_))
+                            | (RulePart::NonTerm(_), RulePart::NonTerm(_)) => {
+                                b.length().cmp(&a.length())
+                            }
+                            _ => Ordering::Equal,
+                        },
                     }
-                }
-                (RulePart::Term(_), RulePart::NonTerm(_)) => Ordering::Greater,
-                (RulePart::Term(_), RulePart::Not(_)) => Ordering::Greater,
-                (RulePart::NonTerm(_), RulePart::Term(_)) => Ordering::Less,
-                (RulePart::NonTerm(_), RulePart::NonTerm(_)) => b.length().cmp(&a.length()),
-                (RulePart::NonTerm(_), RulePart::Not(_)) => Ordering::Greater,
-                // Theoretically, all Nots in the same level are merged, so this is unreachable?
-                (RulePart::Not(_), RulePart::Not(_)) => unreachable!(),
-                (RulePart::Not(_), _) => Ordering::Less,
-            },
+                })
+            }
             _ => std::cmp::Ordering::Equal,
         });
 
@@ -288,9 +1188,139 @@ impl Rules {
     }
 }
 
+/// Where a value made by a transformer came from, returned by [`Rules::parse_entire_spanned`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanTree {
+    pub rule: String,
+    pub span: Span,
+    /// The trees of the values which the transformer used, in the order of the rule parts.
+    pub children: Vec<SpanTree>,
+}
+
+impl SpanTree {
+    /// The closest descendants made by `rule`, in order.
+    pub fn find(&self, rule: &str) -> Vec<&SpanTree> {
+        let mut found = Vec::new();
+
+        for child in &self.children {
+            if child.rule == rule {
+                found.push(child);
+            } else {
+                found.extend(child.find(rule));
+            }
+        }
+
+        found
+    }
+}
+
+// A value and its `SpanTree`, while parsing with `Rules::parse_entire_spanned`
+struct SpannedValue {
+    value: ParseValue,
+    tree: SpanTree,
+}
+
+impl SpannedValue {
+    // Replaces the `SpannedValue`s of a buffer with their values, returning their trees
+    fn unwrap_all(buffer: &mut [ParseValue]) -> Vec<(usize, SpanTree)> {
+        let mut trees = Vec::new();
+
+        for (i, slot) in buffer.iter_mut().enumerate() {
+            if slot.is::<SpannedValue>() {
+                let spanned = std::mem::replace(slot, Nothing.into_value());
+                let spanned = *spanned.downcast::<SpannedValue>().unwrap();
+
+                *slot = spanned.value;
+                trees.push((i, spanned.tree));
+            }
+        }
+
+        trees
+    }
+
+    // Undoes `unwrap_all`
+    fn wrap_all(buffer: &mut [ParseValue], trees: Vec<(usize, SpanTree)>) {
+        for (i, tree) in trees {
+            let value = std::mem::replace(&mut buffer[i], Nothing.into_value());
+
+            buffer[i] = SpannedValue { value, tree }.into_value();
+        }
+    }
+
+    // Wraps the transformer of an alternative with `len` parts (the default value if there's none)
+    fn transformer(transformer: Option<Transformer>, rule: &str, len: usize) -> Transformer {
+        let rule = rule.to_owned();
+
+        let spanned = move |v: ParseBuffer,
+                            span: Span,
+                            f: &mut dyn FnMut(ParseBuffer, Span) -> ParseValue|
+              -> ParseValue {
+            let mut children = Vec::new();
+
+            let value = f(
+                &mut |i| match v(i).downcast::<SpannedValue>() {
+                    Ok(spanned) => {
+                        children.push((i, spanned.tree));
+                        spanned.value
+                    }
+                    Err(value) => value,
+                },
+                span,
+            );
+
+            if value.is::<Box<dyn Error>>() {
+                return value;
+            }
+
+            children.sort_by_key(|(i, _)| *i);
+
+            SpannedValue {
+                value,
+                tree: SpanTree {
+                    rule: rule.clone(),
+                    span,
+                    children: children.into_iter().map(|(_, tree)| tree).collect(),
+                },
+            }
+            .into_value()
+        };
+
+        match transformer {
+            Some(Transformer::Plain(f)) => {
+                Transformer::new(move |v, span| spanned(v, span, &mut |v, span| f(v, span)))
+            }
+            Some(Transformer::WithContext(f)) => {
+                Transformer::WithContext(Rc::new(move |v, span, context| {
+                    spanned(v, span, &mut |v, span| f(v, span, context))
+                }))
+            }
+            // The same value as the parser gives rules without a transformer
+            None => Transformer::new(move |v, span| {
+                spanned(v, span, &mut |v, _| {
+                    let mut buffer = (0..len)
+                        .map(&mut *v)
+                        .filter(|x| !x.is::<Checked>())
+                        .collect::<Vec<_>>();
+
+                    if buffer.len() == 1 {
+                        buffer.remove(0)
+                    } else if buffer.iter().all(|x| x.is::<Nothing>()) {
+                        Nothing.into_value()
+                    } else {
+                        buffer.into_value()
+                    }
+                })
+            }),
+        }
+    }
+}
+
 // represents no parsed content -- private so that no other place can accidentally create it
 struct Nothing;
 
+// the value of a predicate or an anchor that passed -- left out of the value of rules without a transformer
+struct Checked;
+
 #[derive(Clone)]
 
 struct ParseStackItem<'a, 'i, I: Input<'i>> {
@@ -301,66 +1331,77 @@ struct ParseStackItem<'a, 'i, I: Input<'i>> {
     n: usize,
     input: I,
 
-    // primarily for debugging
+    // the path through the rule trees that led to this item
     prev_path: Vec<usize>,
 
     // for constructing spans
     span_start: LineInfo,
 
-    _phantom: PhantomData<&'i I>,
+    // the length of the context history when this item was created
+    version: usize,
+
+    _phantom: PhantomData<&'i I>,
+}
+
+impl<'a, 'i, I: Input<'i>> ParseStackItem<'a, 'i, I> {
+    // The path to the rule part currently being tried
+    fn path(&self) -> Vec<usize> {
+        let mut path = self.prev_path.clone();
+        path.push(self.n);
+
+        path
+    }
+
+    fn span(&self, end: &I) -> Span {
+        Span {
+            start: self.span_start,
+            end: end.line_info(),
+        }
+    }
 }
 
-struct BufferFormatter<'a>(&'a [ParseValue]);
+// A finished alternative of a rule with the `Longest` policy, waiting for the rest of the alternatives to be tried
+struct Candidate<I> {
+    // index of the stack item where the rule's alternatives start
+    index: usize,
+    value: ParseValue,
+    input: I,
+    path: Vec<usize>,
+    // whether this alternative changed the context, which is rolled back when the next alternative is tried
+    changed_context: bool,
+    // whether the alternatives are being parsed again to redo the changes of this one to the context
+    replaying: bool,
+}
 
-impl<'a> Debug for BufferFormatter<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_char('[')?;
+// Everything about a parse in progress, except for the stack
+struct ParseState<'o, I> {
+    buffers: Vec<Vec<ParseValue>>,
+    candidates: Vec<Candidate<I>>,
+    observer: Option<&'o mut dyn ParseObserver>,
+    context: Option<&'o mut dyn ParseContext>,
+    // checkpoints of the context, taken before each transformer or predicate that uses it
+    history: Vec<Box<dyn Any>>,
+}
 
-        let mut first = true;
-        for val in self.0 {
-            if !first {
-                f.write_str(", ")?;
-            } else {
-                first = false;
-            }
+impl<'o, I> ParseState<'o, I> {
+    // Undoes every change made to the context since `version`
+    fn rollback(&mut self, version: usize) {
+        if version < self.history.len() {
+            let checkpoint = self.history.drain(version..).next().unwrap();
 
-            if val.is::<Nothing>() {
-                f.write_str("()")?;
-            } else if val.is::<Vec<ParseValue>>() {
-                f.write_str("Vec<ParseValue>[...]")?;
-            } else if let Some(token) = val.downcast_ref::<Token>() {
-                f.write_fmt(format_args!("Token(\"{token}\")"))?;
-            } else if let Some(string) = val.downcast_ref::<String>() {
-                f.write_fmt(format_args!("\"{string}\""))?;
-            } else {
-                Debug::fmt(val, f)?;
+            if let Some(context) = self.context.as_deref_mut() {
+                context.rollback(checkpoint);
             }
         }
-
-        f.write_char(']')
     }
-}
-
-impl<'a, 'i, I: Input<'i>> Debug for ParseStackItem<'a, 'i, I> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let ParseStackItem {
-            depth,
-            rule,
-            n,
-            input,
-            prev_path,
-            ..
-        } = self;
 
-        let prev_path = if prev_path.is_empty() {
-            "".to_owned()
-        } else {
-            format!("{prev_path:?}")
-        };
-
-        f.write_fmt(format_args!(
-            "({depth}, {rule}{prev_path}, [{n:?}], input = \"{input}\")"
-        ))
+    // Lets the context drop what it kept to undo the changes of a successful parse
+    fn commit(&mut self) {
+        if let (Some(context), Some(checkpoint)) =
+            (self.context.as_deref_mut(), self.history.drain(..).next())
+        {
+            context.commit(checkpoint);
+        }
     }
 }
 
@@ -369,140 +1410,164 @@ fn parse<'a, 'i, I: Input<'i>>(
     rules: &Rules,
     rule: &str,
     input: I,
+    options: &mut ParseOptions,
+) -> Result<(ParseValue, I), ParseError> {
+    if rules.hidden.contains(rule) {
+        return Err(ParseError::RuleNotFound {
+            rule_name: rule.to_owned(),
+        });
+    }
+
+    parse_nested(rules, rule, input, options, &mut 0, 0, false)
+}
+
+// Parses as part of another parse which has taken `steps` so far and is `depth` rules deep,
+// so that the limits of the options apply to both together.
+// The observer and the context are handed back to `options` afterwards.
+// A `lookahead` leaves the context as it was, even if it succeeds
+fn parse_nested<'a, 'i, 'o, I: Input<'i>>(
+    rules: &Rules,
+    rule: &str,
+    input: I,
+    options: &mut ParseOptions<'o>,
+    steps: &mut usize,
+    depth: usize,
+    lookahead: bool,
 ) -> Result<(ParseValue, I), ParseError> {
-    let rule_trees = rules.0.get(rule).ok_or_else(|| ParseError::RuleNotFound {
-        rule_name: rule.to_owned(),
-    })?;
+    let rule_trees = rules
+        .trees
+        .get(rule)
+        .ok_or_else(|| ParseError::RuleNotFound {
+            rule_name: rule.to_owned(),
+        })?;
     let mut stack = vec![ParseStackItem {
         depth: 0,
         rule,
         rule_trees,
         n: 0,
         prev_path: vec![],
+        span_start: input.line_info(),
         input,
-        span_start: LineInfo {
-            pos: 0,
-            line: 1,
-            column: 1,
-        },
+        version: 0,
         _phantom: PhantomData,
     }];
 
-    let mut buffers: Vec<Vec<ParseValue>> = vec![vec![]];
+    let mut state = ParseState {
+        buffers: vec![vec![]],
+        candidates: Vec::new(),
+        observer: options.observer.take(),
+        context: options.context.take(),
+        history: Vec::new(),
+    };
+
+    if let Some(observer) = state.observer.as_deref_mut() {
+        observer.rule_enter(rule, stack[0].span_start);
+    }
+
+    let result = parse_stack(rules, &mut stack, &mut state, options, steps, depth);
+
+    match result {
+        Ok(_) if !lookahead => state.commit(),
+        // Leave the context as it was before parsing
+        _ => state.rollback(0),
+    }
 
+    options.observer = state.observer;
+    options.context = state.context;
+
+    result
+}
+
+fn parse_stack<'a, 'i, I: Input<'i>>(
+    rules: &'a Rules,
+    stack: &mut Vec<ParseStackItem<'a, 'i, I>>,
+    state: &mut ParseState<'_, I>,
+    options: &ParseOptions,
+    steps: &mut usize,
+    depth: usize,
+) -> Result<(ParseValue, I), ParseError> {
     'main: loop {
         let top = stack.last().unwrap().clone();
 
-        log::debug!("\n\n\n\n");
-        log::debug!("STACK: {stack:#?}");
-        log::debug!(
-            "BUFFERS: {:?}",
-            buffers
-                .iter()
-                .map(|b| BufferFormatter(b))
-                .collect::<Vec<_>>()
-        );
+        *steps += 1;
+
+        if let Some(reason) = options.check(*steps, depth + top.depth, top.input.pos()) {
+            return Err(in_visible_rule(
+                rules,
+                stack,
+                aborted(top.rule, &top.input, reason),
+            ));
+        }
 
         let rule_tree = &top.rule_trees[top.n];
 
         let token = match rule_tree {
             RuleTree::Part { part, nexts } => match part {
-                RulePart::Term(literal) => {
-                    log::debug!("LEX \"{literal}\"");
-
-                    let mut literal_chars = literal.chars();
-
-                    let mut term_input = top.input.clone();
+                RulePart::Term(literal) | RulePart::TermCaseInsensitive(literal, _) => {
+                    let case_folding = match part {
+                        RulePart::TermCaseInsensitive(_, case_folding) => Some(*case_folding),
+                        _ => None,
+                    };
 
-                    let success = loop {
-                        let mut i = term_input.clone();
+                    let success = match_literal(&top.input, literal, case_folding).map_err(|at| {
+                        let (row, col) = at.row_col();
 
-                        let pos = i.pos();
-                        let (row, col) = i.row_col();
+                        ParseError::UnexpectedChar {
+                            current_rule: top.rule.to_owned(),
+                            char: at.clone().next(),
+                            pos: at.pos(),
+                            row,
+                            col,
+                        }
+                    });
 
-                        let (i_char, l_char) = (i.next(), literal_chars.next());
-                        match (i_char, l_char) {
-                            (None, None) => break Ok(()),
-                            (None, Some(_)) => {
-                                break Err(ParseError::UnexpectedChar {
-                                    current_rule: top.rule.to_owned(),
-                                    char: None,
-                                    pos,
-                                    row,
-                                    col,
-                                })
-                            }
-                            (Some(_), None) => break Ok(()),
-                            (Some(c0), Some(c1)) if c0 == c1 => {}
-                            (Some(c), Some(_)) => {
-                                break Err(ParseError::UnexpectedChar {
-                                    current_rule: top.rule.to_owned(),
-                                    char: Some(c),
-                                    pos,
-                                    row,
-                                    col,
-                                })
-                            }
+                    if let Some(observer) = state.observer.as_deref_mut() {
+                        match &success {
+                            Ok(term_input) => observer.terminal_match(
+                                top.rule,
+                                &top.path(),
+                                part,
+                                Span {
+                                    start: top.input.line_info(),
+                                    end: term_input.line_info(),
+                                },
+                            ),
+                            Err(_) => observer.terminal_fail(
+                                top.rule,
+                                &top.path(),
+                                part,
+                                top.input.line_info(),
+                            ),
                         }
+                    }
 
-                        term_input = i;
-                    };
+                    success.map(|term_input| {
+                        // The token holds the literal as it appears in the input
+                        let token = match case_folding {
+                            None => Token::from(literal),
+                            Some(_) => Token::from(read_until(&top.input, &term_input)),
+                        };
 
-                    success.map(|_| (Token::from(literal), term_input, nexts))
+                        (token.into_value(), term_input, nexts)
+                    })
                 }
                 RulePart::Not(literals) => {
-                    log::debug!(
-                        "LEX {:?}",
-                        literals
-                            .iter()
-                            .map(|s| format!("\"{s}\""))
-                            .collect::<Vec<_>>()
-                    );
-
                     let mut success = Ok(());
 
-                    'literals: for literal in literals {
-                        let mut literal_chars = literal.chars();
-                        let mut term_input = top.input.clone();
-
-                        loop {
-                            let mut i = term_input.clone();
-
-                            let pos = i.pos();
-                            let (row, col) = i.row_col();
-
-                            let (i_char, l_char) = (i.next(), literal_chars.next());
-                            match (i_char, l_char) {
-                                (None, None) => {
-                                    success = Err(ParseError::UnexpectedToken {
-                                        current_rule: top.rule.to_owned(),
-                                        token: literal.clone(),
-                                        pos,
-                                        row,
-                                        col,
-                                    });
-                                    break 'literals;
-                                }
-                                (None, Some(_)) => {
-                                    break;
-                                }
-                                (Some(_), None) => {
-                                    success = Err(ParseError::UnexpectedToken {
-                                        current_rule: top.rule.to_owned(),
-                                        token: literal.clone(),
-                                        pos,
-                                        row,
-                                        col,
-                                    });
-                                    break 'literals;
-                                }
-                                (Some(c0), Some(c1)) if c0 == c1 => {}
-                                (Some(_), Some(_)) => {
-                                    break;
-                                }
-                            }
+                    for literal in literals {
+                        if let Ok(after) =
+                            match_literal(&top.input, &literal.text, literal.case_folding)
+                        {
+                            let (row, col) = after.row_col();
 
-                            term_input = i;
+                            success = Err(ParseError::UnexpectedToken {
+                                current_rule: top.rule.to_owned(),
+                                token: read_until(&top.input, &after),
+                                pos: after.pos(),
+                                row,
+                                col,
+                            });
+                            break;
                         }
                     }
 
@@ -529,57 +1594,303 @@ fn parse<'a, 'i, I: Input<'i>>(
                         }
                     }
 
-                    success.map(|_| (Token::from(token), input, nexts))
+                    if let Some(observer) = state.observer.as_deref_mut() {
+                        match &success {
+                            Ok(()) => observer.terminal_match(
+                                top.rule,
+                                &top.path(),
+                                part,
+                                Span {
+                                    start: top.input.line_info(),
+                                    end: input.line_info(),
+                                },
+                            ),
+                            Err(_) => observer.terminal_fail(
+                                top.rule,
+                                &top.path(),
+                                part,
+                                top.input.line_info(),
+                            ),
+                        }
+                    }
+
+                    success.map(|_| (Token::from(token).into_value(), input, nexts))
+                }
+
+                RulePart::Instance(..) => {
+                    unreachable!("Instances of parameterized rules are replaced in Rules::new")
+                }
+
+                RulePart::Matcher(name) => {
+                    let matcher =
+                        rules
+                            .matchers
+                            .get(name)
+                            .ok_or_else(|| ParseError::RuleNotFound {
+                                rule_name: name.clone(),
+                            })?;
+
+                    let mut input = top.input.clone();
+
+                    let value = matcher(&mut input);
+
+                    if let Some(observer) = state.observer.as_deref_mut() {
+                        match &value {
+                            Some(_) => observer.terminal_match(
+                                top.rule,
+                                &top.path(),
+                                part,
+                                Span {
+                                    start: top.input.line_info(),
+                                    end: input.line_info(),
+                                },
+                            ),
+                            None => observer.terminal_fail(
+                                top.rule,
+                                &top.path(),
+                                part,
+                                top.input.line_info(),
+                            ),
+                        }
+                    }
+
+                    match value {
+                        Some(value) => Ok((value, input, nexts)),
+                        None => {
+                            let (row, col) = top.input.row_col();
+
+                            Err(ParseError::UnexpectedChar {
+                                current_rule: top.rule.to_owned(),
+                                char: top.input.clone().next(),
+                                pos: top.input.pos(),
+                                row,
+                                col,
+                            })
+                        }
+                    }
+                }
+
+                RulePart::Until(stops) => {
+                    let mut input = top.input.clone();
+                    let mut token = String::new();
+
+                    // Rule stops are parsed with the observer and the context of this parse
+                    let mut stop_options = stops
+                        .iter()
+                        .any(|stop| matches!(stop, UntilStop::Rule(_)))
+                        .then(|| ParseOptions {
+                            observer: state.observer.as_deref_mut().map(|o| o as _),
+                            context: state.context.as_deref_mut().map(|c| c as _),
+                            ..options.clone()
+                        });
+
+                    'until: loop {
+                        // A single `until` can read the whole input,
+                        // so the limits are checked for each character
+                        if let Some(reason) = options.check(*steps, depth + top.depth, input.pos())
+                        {
+                            return Err(in_visible_rule(
+                                rules,
+                                stack,
+                                aborted(top.rule, &input, reason),
+                            ));
+                        }
+
+                        for stop in stops {
+                            let matched = match (stop, stop_options.as_mut()) {
+                                (UntilStop::Literal(literal), _) => {
+                                    match_literal(&input, &literal.text, literal.case_folding)
+                                        .is_ok()
+                                }
+                                (UntilStop::Rule(rule), Some(stop_options)) => until_rule_matches(
+                                    rules,
+                                    rule,
+                                    &input,
+                                    stop_options,
+                                    steps,
+                                    depth + top.depth + 1,
+                                )?,
+                                (UntilStop::Rule(_), None) => {
+                                    unreachable!("Options are built when there are rule stops")
+                                }
+                            };
+
+                            if matched {
+                                break 'until;
+                            }
+                        }
+
+                        match input.next() {
+                            Some(c) => token.push(c),
+                            None => break 'until,
+                        }
+                    }
+
+                    if let Some(observer) = state.observer.as_deref_mut() {
+                        observer.terminal_match(
+                            top.rule,
+                            &top.path(),
+                            part,
+                            Span {
+                                start: top.input.line_info(),
+                                end: input.line_info(),
+                            },
+                        );
+                    }
+
+                    Ok((Token::from(token).into_value(), input, nexts))
+                }
+
+                RulePart::Anchor(anchor) => {
+                    let success = anchor.matches(&top.input);
+
+                    if let Some(observer) = state.observer.as_deref_mut() {
+                        let at = top.input.line_info();
+
+                        if success {
+                            observer.terminal_match(
+                                top.rule,
+                                &top.path(),
+                                part,
+                                Span { start: at, end: at },
+                            );
+                        } else {
+                            observer.terminal_fail(top.rule, &top.path(), part, at);
+                        }
+                    }
+
+                    if success {
+                        Ok((Checked.into_value(), top.input.clone(), nexts))
+                    } else {
+                        let (row, col) = top.input.row_col();
+
+                        Err(ParseError::UnexpectedChar {
+                            current_rule: top.rule.to_owned(),
+                            char: top.input.clone().next(),
+                            pos: top.input.pos(),
+                            row,
+                            col,
+                        })
+                    }
+                }
+
+                RulePart::Predicate(predicate) => {
+                    let buffer = state.buffers.last_mut().unwrap();
+
+                    // Predicates see the values as the transformers made them
+                    let trees = SpannedValue::unwrap_all(buffer);
+
+                    let success = match predicate {
+                        Predicate::Plain(f) => f(buffer),
+                        Predicate::WithContext(f) => match state.context.as_deref_mut() {
+                            Some(context) => {
+                                state.history.push(context.checkpoint());
+                                f(buffer, context.as_any_mut())
+                            }
+                            None => f(buffer, &mut ()),
+                        },
+                    };
+
+                    SpannedValue::wrap_all(buffer, trees);
+
+                    if let Some(observer) = state.observer.as_deref_mut() {
+                        if success {
+                            let at = top.input.line_info();
+
+                            observer.terminal_match(
+                                top.rule,
+                                &top.path(),
+                                part,
+                                Span { start: at, end: at },
+                            );
+                        } else {
+                            observer.terminal_fail(
+                                top.rule,
+                                &top.path(),
+                                part,
+                                top.input.line_info(),
+                            );
+                        }
+                    }
+
+                    if success {
+                        Ok((Checked.into_value(), top.input.clone(), nexts))
+                    } else {
+                        let (row, col) = top.input.row_col();
+
+                        Err(ParseError::PredicateFailed {
+                            current_rule: top.rule.to_owned(),
+                            pos: top.input.pos(),
+                            row,
+                            col,
+                        })
+                    }
                 }
 
                 RulePart::NonTerm(rule) => {
-                    log::debug!("PUSH RULE {rule} ONTO STACK");
+                    let rule_trees =
+                        rules
+                            .trees
+                            .get(rule)
+                            .ok_or_else(|| ParseError::RuleNotFound {
+                                rule_name: rule.clone(),
+                            })?;
+
+                    if let Some(observer) = state.observer.as_deref_mut() {
+                        observer.nonterminal(top.rule, &top.path(), part, top.input.line_info());
+                        observer.rule_enter(rule, top.input.line_info());
+                    }
 
                     stack.push(ParseStackItem {
                         depth: top.depth + 1,
                         rule,
-                        rule_trees: rules.0.get(rule).ok_or_else(|| ParseError::RuleNotFound {
-                            rule_name: rule.clone(),
-                        })?,
+                        rule_trees,
                         n: 0,
                         span_start: top.input.line_info(),
                         input: top.input,
                         prev_path: vec![],
+                        version: state.history.len(),
                         _phantom: PhantomData,
                     });
 
-                    buffers.push(Vec::new());
+                    state.buffers.push(Vec::new());
 
                     continue 'main;
                 }
 
                 RulePart::Recurse => {
-                    log::debug!("RECURSE");
+                    let rule_trees =
+                        rules
+                            .trees
+                            .get(top.rule)
+                            .ok_or_else(|| ParseError::RuleNotFound {
+                                rule_name: top.rule.to_owned(),
+                            })?;
+
+                    if let Some(observer) = state.observer.as_deref_mut() {
+                        observer.nonterminal(top.rule, &top.path(), part, top.input.line_info());
+                        observer.rule_enter(top.rule, top.input.line_info());
+                    }
 
                     stack.push(ParseStackItem {
                         depth: top.depth + 1,
                         rule: top.rule,
-                        rule_trees: rules.0.get(top.rule).ok_or_else(|| {
-                            ParseError::RuleNotFound {
-                                rule_name: top.rule.to_owned(),
-                            }
-                        })?,
+                        rule_trees,
                         n: 1,
                         input: top.input,
                         span_start: top.span_start,
                         prev_path: vec![],
+                        version: state.history.len(),
                         _phantom: PhantomData,
                     });
 
-                    buffers.push(Vec::new());
+                    state.buffers.push(Vec::new());
 
                     continue 'main;
                 }
             },
             RuleTree::End { transformer } => {
-                log::debug!("END");
-
-                let mut buffer = buffers.pop().unwrap();
+                let mut buffer = state.buffers.pop().unwrap();
 
                 let parse_value = if let Some(transformer) = transformer {
                     let mut buffer = buffer.into_iter().map(Some).collect::<Vec<_>>();
@@ -589,23 +1900,34 @@ fn parse<'a, 'i, I: Input<'i>>(
                         value.take().unwrap()
                     };
 
-                    let span = Span {
-                        start: top.span_start,
-                        end: top.input.line_info(),
+                    let span = top.span(&top.input);
+
+                    let start = state.observer.is_some().then(Instant::now);
+
+                    let parse_value = match transformer {
+                        Transformer::Plain(f) => f(&mut parse_buffer, span),
+                        Transformer::WithContext(f) => match state.context.as_deref_mut() {
+                            Some(context) => {
+                                state.history.push(context.checkpoint());
+                                f(&mut parse_buffer, span, context.as_any_mut())
+                            }
+                            None => f(&mut parse_buffer, span, &mut ()),
+                        },
                     };
 
-                    let parse_value = transformer(&mut parse_buffer, span);
+                    if let (Some(observer), Some(start)) = (state.observer.as_deref_mut(), start) {
+                        observer.transformer(top.rule, &top.path(), span, start.elapsed());
+                    }
 
                     match parse_value.downcast::<Box<dyn Error>>() {
                         Ok(error) => {
-                            log::debug!("Transformer error!");
-
                             let pos = top.input.pos();
                             let (row, col) = top.input.row_col();
-                            buffers.push(Vec::new());
+                            state.buffers.push(Vec::new());
                             if let Some(res) = fail(
-                                &mut stack,
-                                &mut buffers,
+                                rules,
+                                stack,
+                                state,
                                 ParseError::TransformerError {
                                     current_rule: top.rule.to_owned(),
                                     pos,
@@ -620,15 +1942,85 @@ fn parse<'a, 'i, I: Input<'i>>(
                         }
                         Err(parse_value) => parse_value,
                     }
-                } else if buffer.len() == 1 {
-                    buffer.remove(0)
-                } else if buffer.is_empty() || buffer.iter().all(|x| x.is::<Nothing>()) {
-                    Nothing.into_value()
                 } else {
-                    buffer.into_value()
+                    buffer.retain(|x| !x.is::<Checked>());
+
+                    if buffer.len() == 1 {
+                        buffer.remove(0)
+                    } else if buffer.is_empty() || buffer.iter().all(|x| x.is::<Nothing>()) {
+                        Nothing.into_value()
+                    } else {
+                        buffer.into_value()
+                    }
                 };
 
-                if let Some(res) = end(&mut stack, &mut buffers, parse_value, None) {
+                if rules.policy(top.rule) == ChoicePolicy::Longest {
+                    let index = stack
+                        .iter()
+                        .rposition(|item| item.depth != top.depth)
+                        .map_or(0, |i| i + 1);
+
+                    let longest = state.candidates.iter().position(|c| c.index == index);
+
+                    if let Some(i) = longest.filter(|&i| {
+                        state.candidates[i].replaying
+                            && state.candidates[i].input.pos() == top.input.pos()
+                    }) {
+                        // The longest alternative was parsed again, which redid its changes to the context
+                        state.candidates.swap_remove(i);
+                    } else if stack[index].prev_path.is_empty() {
+                        // Growing a left recursive rule is always greedy
+                        let changed_context = state.history.len() > stack[index].version;
+
+                        match longest.map(|i| &mut state.candidates[i]) {
+                            Some(candidate)
+                                if candidate.replaying
+                                    || candidate.input.pos() >= top.input.pos() => {}
+                            Some(candidate) => {
+                                candidate.value = parse_value;
+                                candidate.input = top.input.clone();
+                                candidate.path = top.path();
+                                candidate.changed_context = changed_context;
+                            }
+                            None => state.candidates.push(Candidate {
+                                index,
+                                value: parse_value,
+                                input: top.input.clone(),
+                                path: top.path(),
+                                changed_context,
+                                replaying: false,
+                            }),
+                        }
+
+                        // Alternatives of a `Longest` rule never share a prefix, so the buffer can be dropped
+                        state.buffers.push(Vec::new());
+
+                        let pos = top.input.pos();
+                        let (row, col) = top.input.row_col();
+                        if let Some(res) = fail(
+                            rules,
+                            stack,
+                            state,
+                            ParseError::UnexpectedChar {
+                                current_rule: top.rule.to_owned(),
+                                char: None,
+                                pos,
+                                row,
+                                col,
+                            },
+                        )? {
+                            return Ok(res);
+                        }
+
+                        continue 'main;
+                    }
+                }
+
+                if let Some(observer) = state.observer.as_deref_mut() {
+                    observer.rule_exit(top.rule, &top.path(), top.span(&top.input), true);
+                }
+
+                if let Some(res) = end(stack, state, parse_value, None) {
                     return Ok(res);
                 }
 
@@ -638,8 +2030,6 @@ fn parse<'a, 'i, I: Input<'i>>(
 
         match token {
             Ok((token, input, nexts)) => {
-                log::debug!("LEX SUCCESS, PUSH NEXT ONTO STACK");
-
                 let mut prev_path = top.prev_path;
 
                 prev_path.push(top.n);
@@ -652,13 +2042,14 @@ fn parse<'a, 'i, I: Input<'i>>(
                     input,
                     span_start: top.span_start,
                     prev_path,
+                    version: state.history.len(),
                     _phantom: PhantomData,
                 });
 
-                buffers.last_mut().unwrap().push(token.into_value());
+                state.buffers.last_mut().unwrap().push(token);
             }
             Err(error) => {
-                if let Some(result) = fail(&mut stack, &mut buffers, error)? {
+                if let Some(result) = fail(rules, stack, state, error)? {
                     return Ok(result);
                 }
             }
@@ -666,29 +2057,160 @@ fn parse<'a, 'i, I: Input<'i>>(
     }
 }
 
+// Matches `literal` at the start of `input`. Returns the input after the literal,
+// or the input at the first character that doesn't match
+fn match_literal<'i, I: Input<'i>>(
+    input: &I,
+    literal: &str,
+    case_folding: Option<CaseFolding>,
+) -> Result<I, I> {
+    let mut input = input.clone();
+
+    match case_folding {
+        None | Some(CaseFolding::Ascii) => {
+            for l in literal.chars() {
+                let mut i = input.clone();
+
+                match i.next() {
+                    Some(c) if c == l => {}
+                    Some(c) if case_folding.is_some() && c.eq_ignore_ascii_case(&l) => {}
+                    _ => return Err(input),
+                }
+
+                input = i;
+            }
+        }
+        Some(CaseFolding::Unicode) => {
+            let literal = literal
+                .chars()
+                .flat_map(|c| CaseFolding::Unicode.fold(c))
+                .collect::<Vec<_>>();
+
+            let mut matched = 0;
+
+            // A single character can fold into several (and the other way around)
+            while matched < literal.len() {
+                let mut i = input.clone();
+
+                let Some(c) = i.next() else {
+                    return Err(input);
+                };
+
+                let folded = CaseFolding::Unicode.fold(c).collect::<Vec<_>>();
+
+                if !literal[matched..].starts_with(&folded) {
+                    return Err(input);
+                }
+
+                matched += folded.len();
+                input = i;
+            }
+        }
+    }
+
+    Ok(input)
+}
+
+// Whether the stop `rule` matches at the start of `input`.
+// It's checked using a separate parse, which counts towards the limits of the current one
+// and can use its context, but leaves the context as it was
+fn until_rule_matches<'i, I: Input<'i>>(
+    rules: &Rules,
+    rule: &str,
+    input: &I,
+    options: &mut ParseOptions,
+    steps: &mut usize,
+    depth: usize,
+) -> Result<bool, ParseError> {
+    if let Some(matcher) = rules.matchers.get(rule) {
+        return Ok(matcher(&mut input.clone()).is_some());
+    }
+
+    match parse_nested(rules, rule, input.clone(), options, steps, depth, true) {
+        Ok(_) => Ok(true),
+        Err(error @ (ParseError::RuleNotFound { .. } | ParseError::Aborted { .. })) => Err(error),
+        // A missing context is a mistake in the rules, not a stop that doesn't match
+        Err(error)
+            if matches!(
+                &error,
+                ParseError::TransformerError { error, .. } if error.is::<ContextError>()
+            ) =>
+        {
+            Err(error)
+        }
+        Err(_) => Ok(false),
+    }
+}
+
+// The error for a parse aborted in `rule` at `input`
+fn aborted<'i, I: Input<'i>>(rule: &str, input: &I, reason: AbortReason) -> ParseError {
+    let (row, col) = input.row_col();
+
+    ParseError::Aborted {
+        current_rule: rule.to_owned(),
+        reason,
+        pos: input.pos(),
+        row,
+        col,
+    }
+}
+
+// The text of the input from `start` until `end`
+fn read_until<'i, I: Input<'i>>(start: &I, end: &I) -> String {
+    let mut input = start.clone();
+    let mut text = String::new();
+
+    while input.pos() < end.pos() {
+        match input.next() {
+            Some(c) => text.push(c),
+            None => break,
+        }
+    }
+
+    text
+}
+
+// Errors in hidden rules are reported in the innermost rule which isn't hidden
+fn in_visible_rule<'i, I: Input<'i>>(
+    rules: &Rules,
+    stack: &[ParseStackItem<'_, 'i, I>],
+    mut error: ParseError,
+) -> ParseError {
+    if let ParseError::UnexpectedChar { current_rule, .. }
+    | ParseError::UnexpectedToken { current_rule, .. }
+    | ParseError::TransformerError { current_rule, .. }
+    | ParseError::PredicateFailed { current_rule, .. }
+    | ParseError::Aborted { current_rule, .. } = &mut error
+    {
+        if rules.hidden.contains(current_rule.as_str()) {
+            if let Some(item) = stack
+                .iter()
+                .rev()
+                .find(|item| !rules.hidden.contains(item.rule))
+            {
+                *current_rule = item.rule.to_owned();
+            }
+        }
+    }
+
+    error
+}
+
 #[inline]
 fn fail<'a, 'i, I: Input<'i>>(
+    rules: &Rules,
     stack: &mut Vec<ParseStackItem<'a, 'i, I>>,
-    buffers: &mut Vec<Vec<ParseValue>>,
+    state: &mut ParseState<'_, I>,
     error: ParseError,
 ) -> Result<Option<(ParseValue, I)>, ParseError> {
-    log::debug!("ENTER FAIL");
+    let error = in_visible_rule(rules, stack, error);
 
     let mut last_buffer: Option<Vec<ParseValue>> = None;
     let mut last_input: Option<I> = None;
+    let mut last_version = 0;
 
     'fail: loop {
-        log::debug!("\n\n\n\n");
-        log::debug!("FAIL LOOP START");
-        log::debug!("STACK: {stack:#?}");
-        log::debug!(
-            "BUFFERS: {:?}",
-            buffers
-                .iter()
-                .map(|b| BufferFormatter(b))
-                .collect::<Vec<_>>()
-        );
-
+        let index = stack.len() - 1;
         let top = stack.last_mut().unwrap();
 
         if let RuleTree::Part {
@@ -696,27 +2218,21 @@ fn fail<'a, 'i, I: Input<'i>>(
             ..
         } = &top.rule_trees[top.n]
         {
-            log::debug!("Reached Recurse on fail");
-
+            // Growing a left recursive rule failed, so end it with the last successful value
             let mut last_buffer = last_buffer.unwrap();
 
-            log::debug!("STACK TOP: {top:#?}");
-            log::debug!("LAST BUFFER: {:?}", BufferFormatter(&last_buffer));
-            log::debug!(
-                "LAST INPUT: {}",
-                if let Some(input) = &last_input {
-                    input.to_string()
-                } else {
-                    "None".to_owned()
-                }
-            );
-
             if !last_buffer.is_empty() {
                 let parse_value = last_buffer.remove(0);
 
-                buffers.pop();
+                state.buffers.pop();
+                state.rollback(last_version);
+
+                if let (Some(observer), Some(input)) = (state.observer.as_deref_mut(), &last_input)
+                {
+                    observer.rule_exit(top.rule, &[], top.span(input), true);
+                }
 
-                if let Some(res) = end(stack, buffers, parse_value, last_input) {
+                if let Some(res) = end(stack, state, parse_value, last_input) {
                     return Ok(Some(res));
                 }
 
@@ -724,29 +2240,64 @@ fn fail<'a, 'i, I: Input<'i>>(
             }
         }
 
-        log::debug!("PARSE_ERROR: {error:#?}");
-
-        log::debug!(
-            "top.n = {}, top.rule_trees.len() = {}",
-            top.n,
-            top.rule_trees.len()
-        );
-
         last_input = Some(top.input.clone());
+        last_version = top.version;
 
         if top.n + 1 < top.rule_trees.len() {
-            log::debug!("INC N");
             top.n += 1;
+            state.rollback(top.version);
+
+            if let Some(observer) = state.observer.as_deref_mut() {
+                observer.backtrack(top.rule, &top.path(), top.input.line_info());
+            }
+
+            break 'fail;
+        } else if let Some(i) = state.candidates.iter().position(|c| c.index == index) {
+            // All alternatives of a `Longest` rule were tried, so continue with the longest one
+            let candidate = &mut state.candidates[i];
+
+            if candidate.changed_context && !candidate.replaying {
+                // Its changes to the context were rolled back, so parse the alternatives again up to it
+                candidate.replaying = true;
+                top.n = 0;
+                state.rollback(top.version);
+
+                if let Some(observer) = state.observer.as_deref_mut() {
+                    observer.backtrack(top.rule, &top.path(), top.input.line_info());
+                }
+
+                break 'fail;
+            }
+
+            let Candidate {
+                value, input, path, ..
+            } = state.candidates.swap_remove(i);
+
+            state.buffers.pop();
+
+            if let Some(observer) = state.observer.as_deref_mut() {
+                observer.rule_exit(top.rule, &path, top.span(&input), true);
+            }
+
+            if let Some(res) = end(stack, state, value, Some(input)) {
+                return Ok(Some(res));
+            }
+
             break 'fail;
         } else {
-            log::debug!("POP TOP");
             let old_top = stack.pop().unwrap();
 
+            if let Some(observer) = state.observer.as_deref_mut() {
+                if !matches!(stack.last(), Some(top) if top.depth >= old_top.depth) {
+                    observer.rule_exit(old_top.rule, &[], old_top.span(&old_top.input), false);
+                }
+            }
+
             if let Some(top) = stack.last_mut() {
                 if top.depth == old_top.depth {
-                    last_buffer = buffers.last_mut().unwrap().pop().map(|v| vec![v]);
+                    last_buffer = state.buffers.last_mut().unwrap().pop().map(|v| vec![v]);
                 } else {
-                    last_buffer = buffers.pop();
+                    last_buffer = state.buffers.pop();
                 }
             } else {
                 return Err(error);
@@ -759,7 +2310,7 @@ fn fail<'a, 'i, I: Input<'i>>(
 
 fn end<'a, 'i, I: Input<'i>>(
     stack: &mut Vec<ParseStackItem<'a, 'i, I>>,
-    buffers: &mut Vec<Vec<ParseValue>>,
+    state: &mut ParseState<'_, I>,
     parse_value: ParseValue,
     override_input: Option<I>,
 ) -> Option<(ParseValue, I)> {
@@ -769,11 +2320,8 @@ fn end<'a, 'i, I: Input<'i>>(
 
     if top.depth == 0 {
         // End on depth == 0, return value
-        log::debug!("END ON DEPTH 0, FINISHED!");
-
         return Some((parse_value, input));
     } else {
-        log::debug!("REMOVE DEPTH {}", top.depth);
         'depth: loop {
             if let Some(&ParseStackItem { depth: d, .. }) = stack.last() {
                 if d == top.depth {
@@ -786,6 +2334,8 @@ fn end<'a, 'i, I: Input<'i>>(
             }
         }
 
+        state.candidates.retain(|c| c.index < stack.len());
+
         let mut new_top = stack.last().unwrap().clone();
 
         match &new_top.rule_trees[new_top.n] {
@@ -797,13 +2347,14 @@ fn end<'a, 'i, I: Input<'i>>(
                 new_top.n = 0;
                 new_top.input = input;
                 new_top.rule_trees = nexts;
+                new_top.version = state.history.len();
                 stack.push(new_top);
 
-                let buffer = if buffers.is_empty() {
-                    buffers.push(vec![]);
-                    &mut buffers[0]
+                let buffer = if state.buffers.is_empty() {
+                    state.buffers.push(vec![]);
+                    &mut state.buffers[0]
                 } else {
-                    buffers.last_mut().unwrap()
+                    state.buffers.last_mut().unwrap()
                 };
 
                 buffer.push(parse_value);
@@ -813,28 +2364,29 @@ fn end<'a, 'i, I: Input<'i>>(
                 part: RulePart::Recurse,
                 nexts,
             } => {
+                // Try to grow the left recursive rule using the value parsed so far
+                if let Some(observer) = state.observer.as_deref_mut() {
+                    observer.rule_enter(new_top.rule, input.line_info());
+                }
+
                 new_top.depth += 1;
                 new_top.prev_path.push(new_top.n);
                 new_top.n = 0;
                 new_top.input = input;
                 new_top.rule_trees = nexts;
+                new_top.version = state.history.len();
                 stack.push(new_top);
 
-                let buffer = if buffers.is_empty() {
+                let buffer = if state.buffers.is_empty() {
                     unreachable!();
                 } else {
-                    buffers.insert(buffers.len() - 1, Vec::new());
-                    buffers.last_mut().unwrap()
+                    state.buffers.insert(state.buffers.len() - 1, Vec::new());
+                    state.buffers.last_mut().unwrap()
                 };
 
                 buffer.push(parse_value);
             }
 
-            RuleTree::Part { part, .. } => {
-                log::debug!("NON NONTERM PART: {part:?}");
-                unreachable!()
-            }
-
             _ => unreachable!(),
         }
     }
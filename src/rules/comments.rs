@@ -0,0 +1,12 @@
+use super::*;
+
+declare_rules! {
+    pub Comments {
+        // `// line` and `/* block */` comments, as in JSON5 and JSONC
+        // the line break after a line comment isn't part of it
+        comment {
+            ("//" (until "\n" "\r"))
+            ("/*" (until "*/") "*/")
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use super::*;
+use crate::input::{Input, IntoInput, MatcherInput};
+use std::marker::PhantomData;
+
+declare_rules! {
+    // CSV as specified by RFC 4180: fields separated by commas, records ending with "\r\n" or "\n",
+    // and quoted fields (with `""` for a quote) which can contain commas and line breaks. The `start` rule
+    // parses the records into a `Vec<Vec<String>>`. Other delimiters, quotes and escapes are set with
+    // `CsvDialect`.
+    pub CsvRules {
+        #[matcher (field) as field]
+        #[matcher (delimiter) as delimiter]
+        #[matcher (more) as more]
+
+        start /* Vec<Vec<String>> */ {
+            (records)
+        }
+
+        records /* Vec<Vec<String>> */ {
+            () => |_, _| Vec::<Vec<String>>::new().into_value();
+            (records record) => |v, _| {
+                let mut records = v(0).downcast::<Vec<Vec<String>>>().unwrap();
+
+                records.push(*v(1).downcast().unwrap());
+
+                records
+            };
+        }
+
+        // The last record doesn't need a line break, but there's no record after it
+        record /* Vec<String> */ {
+            (more fields line_break) => |v, _| v(1);
+        }
+
+        fields /* Vec<String> */ {
+            (field) => |v, _| vec![*v(0).downcast::<String>().unwrap()].into_value();
+            (fields delimiter field) => |v, _| {
+                let mut fields = v(0).downcast::<Vec<String>>().unwrap();
+
+                fields.push(*v(2).downcast().unwrap());
+
+                fields
+            };
+        }
+
+        line_break {
+            ("\r\n")
+            ("\n")
+            (EOF)
+        }
+    }
+}
+
+/// The characters of a CSV (or TSV) file, and whether it starts with a header row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvDialect {
+    delimiter: char,
+    quote: Option<char>,
+    escape: Option<char>,
+    headers: bool,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quote: Some('"'),
+            escape: None,
+            headers: false,
+        }
+    }
+}
+
+impl CsvDialect {
+    /// The dialect of RFC 4180, which `CsvRules` parses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tab separated values.
+    pub fn tsv() -> Self {
+        Self::new().delimiter('\t')
+    }
+
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// The character fields are quoted with, or `None` if fields can't be quoted.
+    pub fn quote(mut self, quote: Option<char>) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// A character which makes the next character part of a field, like `\` in `a\,b`.
+    /// Without one, quotes in quoted fields are written twice.
+    pub fn escape(mut self, escape: Option<char>) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    /// The first record is the names of the columns.
+    pub fn headers(mut self) -> Self {
+        self.headers = true;
+        self
+    }
+
+    /// `CsvRules` with the matchers of this dialect.
+    pub fn rules(&self) -> Rules {
+        let mut rules = Rules::from(CsvRules);
+
+        let dialect = self.clone();
+        rules.add_matcher("field", move |input| dialect.field(input));
+
+        let delimiter = self.delimiter;
+        rules.add_matcher("delimiter", move |input| {
+            (input.next()? == delimiter).then(|| ().into_value())
+        });
+
+        rules
+    }
+
+    /// Parses all records of the input.
+    pub fn parse<'a, I: IntoInput<'a>>(&self, input: I) -> Result<Csv, ParseError> {
+        let mut rows = *self
+            .rules()
+            .parse_entire("start", input)?
+            .downcast::<Vec<Vec<String>>>()
+            .unwrap();
+
+        let headers = (self.headers && !rows.is_empty()).then(|| rows.remove(0));
+
+        Ok(Csv { headers, rows })
+    }
+
+    /// Parses the records of the input one at a time, reading only as much of it as a record needs:
+    /// a record is given as soon as its line break has been read (the last one at the end of the input).
+    pub fn rows<'a, I: IntoInput<'a>>(&self, input: I) -> CsvRows<'a, I::Input> {
+        CsvRows {
+            rules: self.rules(),
+            input: Some(input.into_input()),
+            headers: None,
+            read_headers: self.headers,
+            _phantom: PhantomData,
+        }
+    }
+
+    // Matches a field, which is empty if the input continues with a delimiter or line break
+    fn field(&self, input: &mut dyn MatcherInput) -> Option<ParseValue> {
+        let mut field = String::new();
+
+        if self.quote.is_some() && input.peek() == self.quote {
+            input.next();
+
+            loop {
+                match input.next()? {
+                    c if Some(c) == self.escape && self.escape != self.quote => {
+                        field.push(input.next()?)
+                    }
+                    c if Some(c) == self.quote => {
+                        if input.peek() != self.quote {
+                            break;
+                        }
+
+                        field.push(input.next().unwrap());
+                    }
+                    c => field.push(c),
+                }
+            }
+        } else {
+            while let Some(c) = input.peek() {
+                if c == self.delimiter || c == '\n' || input.starts_with("\r\n") {
+                    break;
+                }
+
+                input.next();
+
+                if Some(c) == self.escape {
+                    field.push(input.next()?);
+                } else {
+                    field.push(c);
+                }
+            }
+        }
+
+        Some(field.into_value())
+    }
+}
+
+/// The records of a CSV file, and its header row if the dialect has one.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Csv {
+    pub headers: Option<Vec<String>>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Csv {
+    /// The index of the column with a header.
+    pub fn column(&self, header: &str) -> Option<usize> {
+        self.headers
+            .as_ref()?
+            .iter()
+            .position(|name| name == header)
+    }
+
+    /// The field of a row in the column with a header.
+    pub fn get(&self, row: usize, header: &str) -> Option<&str> {
+        let column = self.column(header)?;
+
+        self.rows.get(row)?.get(column).map(String::as_str)
+    }
+}
+
+/// The records of an input, parsed one at a time (see [`CsvDialect::rows`]).
+///
+/// After an error, there are no more records.
+pub struct CsvRows<'a, I: Input<'a>> {
+    rules: Rules,
+    // `None` after the last record or an error
+    input: Option<I>,
+    headers: Option<Vec<String>>,
+    read_headers: bool,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a, I: Input<'a>> CsvRows<'a, I> {
+    /// The header row, once the first row (or the end of the input) has been read.
+    pub fn headers(&self) -> Option<&[String]> {
+        self.headers.as_deref()
+    }
+
+    fn record(&mut self) -> Option<Result<Vec<String>, ParseError>> {
+        let input = self.input.take()?;
+
+        // At the end of the input there isn't another record
+        input.clone().next()?;
+
+        match self.rules.parse_prefix("record", input) {
+            Ok((record, input)) => {
+                // The records before are never read again
+                input.discard_before();
+
+                self.input = Some(input);
+
+                Some(Ok(*record.downcast().unwrap()))
+            }
+            Err(error) => Some(Err(error)),
+        }
+    }
+}
+
+impl<'a, I: Input<'a>> Iterator for CsvRows<'a, I> {
+    type Item = Result<Vec<String>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.read_headers {
+            self.read_headers = false;
+
+            match self.record()? {
+                Ok(headers) => self.headers = Some(headers),
+                Err(error) => return Some(Err(error)),
+            }
+        }
+
+        self.record()
+    }
+}
+
+// Matches at any position but the end of the input, without consuming anything
+fn more(input: &mut dyn MatcherInput) -> Option<ParseValue> {
+    input.peek().map(|_| ().into_value())
+}
+
+fn field(input: &mut dyn MatcherInput) -> Option<ParseValue> {
+    CsvDialect::new().field(input)
+}
+
+fn delimiter(input: &mut dyn MatcherInput) -> Option<ParseValue> {
+    (input.next()? == ',').then(|| ().into_value())
+}
@@ -0,0 +1,324 @@
+use super::*;
+use crate::{
+    input::IntoInput,
+    result::Span,
+    rule::{Anchor, CaseFolding, InstanceError, Literal, RulePart, Transformer, UntilStop},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt::Display,
+};
+
+#[derive(Debug)]
+pub enum GrammarError {
+    UnknownPolicy { policy: String },
+}
+
+impl Error for GrammarError {}
+
+impl Display for GrammarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GrammarError::UnknownPolicy { policy } => {
+                f.write_fmt(format_args!("Unknown choice policy: {policy}"))
+            }
+        }
+    }
+}
+
+/// The value of the rules of a grammar loaded with [`parse_grammar`]: a concrete syntax tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cst {
+    Token(String),
+    Node { rule: String, children: Vec<Cst> },
+}
+
+impl Cst {
+    // Values without any content (like that of an empty alternative) are left out
+    fn from_value(value: ParseValue) -> Option<Cst> {
+        let value = match value.downcast::<Cst>() {
+            Ok(cst) => return Some(*cst),
+            Err(value) => value,
+        };
+
+        let value = match value.downcast::<Token>() {
+            Ok(token) => return Some(Cst::Token(token.to_string())),
+            Err(value) => value,
+        };
+
+        // E.g. the value of a rule that was imported later
+        value
+            .downcast::<String>()
+            .ok()
+            .map(|string| Cst::Token(*string))
+    }
+}
+
+// A rule as it is written in a grammar, with all of its alternatives
+struct GrammarRule {
+    name: String,
+    params: Vec<String>,
+    // The policy is checked once the whole grammar is parsed, so the error isn't lost to backtracking
+    policy: Option<(String, Span)>,
+    alternatives: Vec<Vec<RulePart>>,
+    // Where instances of parameterized rules in the alternatives are reported, once they're checked
+    span: Span,
+}
+
+declare_rules! {
+    pub GrammarRules {
+        #[import (Identifier) as id]
+        #[import (StringRules) as string]
+
+        start /* Vec<GrammarRule> */ {
+            (sp) => |_, _| Vec::<GrammarRule>::new().into_value();
+            (start rule sp) => |v, _| {
+                let mut rules = v(0).downcast::<Vec<GrammarRule>>().unwrap();
+                rules.push(*v(1).downcast::<GrammarRule>().unwrap());
+
+                rules
+            };
+        }
+
+        rule /* GrammarRule */ {
+            (rule_head sp "{" sp alternatives "}") => |v, span| {
+                let (name, params) = *v(0).downcast::<(String, Vec<String>)>().unwrap();
+
+                GrammarRule {
+                    name,
+                    params,
+                    policy: None,
+                    alternatives: *v(4).downcast().unwrap(),
+                    span,
+                }.into_value()
+            };
+            ("#[policy(" sp (id::identifier) sp ")]" sp rule_head sp "{" sp alternatives "}") => |v, span| {
+                let policy = *v(2).downcast::<String>().unwrap();
+                let (name, params) = *v(6).downcast::<(String, Vec<String>)>().unwrap();
+
+                GrammarRule {
+                    name,
+                    params,
+                    policy: Some((policy, span)),
+                    alternatives: *v(10).downcast().unwrap(),
+                    span,
+                }.into_value()
+            };
+        }
+
+        rule_head /* (String, Vec<String>) */ {
+            ((id::identifier)) => |v, _| (*v(0).downcast::<String>().unwrap(), Vec::<String>::new()).into_value();
+            ((id::identifier) sp "<" sp params sp ">") => |v, _| {
+                (*v(0).downcast::<String>().unwrap(), *v(4).downcast::<Vec<String>>().unwrap()).into_value()
+            };
+        }
+
+        params /* Vec<String> */ {
+            ((id::identifier)) => |v, _| vec![*v(0).downcast::<String>().unwrap()].into_value();
+            (params sp "," sp (id::identifier)) => |v, _| {
+                let mut params = v(0).downcast::<Vec<String>>().unwrap();
+                params.push(*v(4).downcast::<String>().unwrap());
+
+                params
+            };
+        }
+
+        alternatives /* Vec<Vec<RulePart>> */ {
+            (alternative sp) => |v, _| vec![*v(0).downcast::<Vec<RulePart>>().unwrap()].into_value();
+            (alternatives alternative sp) => |v, _| {
+                let mut alternatives = v(0).downcast::<Vec<Vec<RulePart>>>().unwrap();
+                alternatives.push(*v(1).downcast::<Vec<RulePart>>().unwrap());
+
+                alternatives
+            };
+        }
+
+        alternative /* Vec<RulePart> */ {
+            ("(" sp parts ")") => |v, _| v(2);
+        }
+
+        parts /* Vec<RulePart> */ {
+            () => |_, _| Vec::<RulePart>::new().into_value();
+            (parts part sp) => |v, _| {
+                let mut parts = v(0).downcast::<Vec<RulePart>>().unwrap();
+                parts.push(*v(1).downcast::<RulePart>().unwrap());
+
+                parts
+            };
+        }
+
+        part /* RulePart */ {
+            ((string::string)) => |v, _| RulePart::Term(*v(0).downcast::<String>().unwrap()).into_value();
+            ((id::identifier)) => |v, _| {
+                let name = *v(0).downcast::<String>().unwrap();
+
+                match name.as_str() {
+                    "EOF" => RulePart::Anchor(Anchor::Eof),
+                    "BOL" => RulePart::Anchor(Anchor::Bol),
+                    "EOL" => RulePart::Anchor(Anchor::Eol),
+                    _ => RulePart::NonTerm(name),
+                }.into_value()
+            };
+            ("(" sp path sp ")") => |v, _| RulePart::NonTerm(*v(2).downcast::<String>().unwrap()).into_value();
+            ("(" sp case_insensitive sp ")") => |v, _| {
+                let literal = *v(2).downcast::<Literal>().unwrap();
+
+                RulePart::TermCaseInsensitive(literal.text, literal.case_folding.unwrap()).into_value()
+            };
+            ("(" sp "!" sp literals ")") => |v, _| RulePart::Not(*v(4).downcast::<HashSet<Literal>>().unwrap()).into_value();
+            ("(" sp "until" sp1 stops ")") => |v, _| RulePart::Until(*v(4).downcast::<Vec<UntilStop>>().unwrap()).into_value();
+            ("(" sp (id::identifier) sp "<" sp args sp ">" sp ")") => |v, _| {
+                RulePart::Instance(*v(2).downcast::<String>().unwrap(), *v(6).downcast::<Vec<RulePart>>().unwrap()).into_value()
+            };
+        }
+
+        path /* String */ {
+            ((id::identifier))
+            (path "::" (id::identifier)) => |v, _| {
+                format!("{}::{}", v(0).downcast::<String>().unwrap(), v(2).downcast::<String>().unwrap()).into_value()
+            };
+        }
+
+        case_insensitive /* Literal */ {
+            ("i" sp (string::string)) => |v, _| {
+                Literal::case_insensitive(*v(2).downcast::<String>().unwrap(), CaseFolding::Ascii).into_value()
+            };
+            ("iu" sp (string::string)) => |v, _| {
+                Literal::case_insensitive(*v(2).downcast::<String>().unwrap(), CaseFolding::Unicode).into_value()
+            };
+        }
+
+        literal /* Literal */ {
+            ((string::string)) => |v, _| Literal::from(*v(0).downcast::<String>().unwrap()).into_value();
+            ("(" sp case_insensitive sp ")") => |v, _| v(2);
+        }
+
+        literals /* HashSet<Literal> */ {
+            (literal sp) => |v, _| HashSet::from([*v(0).downcast::<Literal>().unwrap()]).into_value();
+            (literals literal sp) => |v, _| {
+                let mut literals = v(0).downcast::<HashSet<Literal>>().unwrap();
+                literals.insert(*v(1).downcast::<Literal>().unwrap());
+
+                literals
+            };
+        }
+
+        stop /* UntilStop */ {
+            (literal) => |v, _| UntilStop::Literal(*v(0).downcast::<Literal>().unwrap()).into_value();
+            ((id::identifier)) => |v, _| UntilStop::Rule(*v(0).downcast::<String>().unwrap()).into_value();
+            ("(" sp path sp ")") => |v, _| UntilStop::Rule(*v(2).downcast::<String>().unwrap()).into_value();
+        }
+
+        stops /* Vec<UntilStop> */ {
+            (stop sp) => |v, _| vec![*v(0).downcast::<UntilStop>().unwrap()].into_value();
+            (stops stop sp) => |v, _| {
+                let mut stops = v(0).downcast::<Vec<UntilStop>>().unwrap();
+                stops.push(*v(1).downcast::<UntilStop>().unwrap());
+
+                stops
+            };
+        }
+
+        args /* Vec<RulePart> */ {
+            (part) => |v, _| vec![*v(0).downcast::<RulePart>().unwrap()].into_value();
+            (args sp "," sp part) => |v, _| {
+                let mut args = v(0).downcast::<Vec<RulePart>>().unwrap();
+                args.push(*v(4).downcast::<RulePart>().unwrap());
+
+                args
+            };
+        }
+
+        // Whitespace and comments
+        sp {
+            ()
+            (sp ws_char)
+            (sp "//" (until "\n"))
+            (sp "/*" (until "*/") "*/")
+        }
+
+        // At least one whitespace character
+        sp1 {
+            (ws_char sp)
+        }
+
+        ws_char {
+            (" ")
+            ("\t")
+            ("\r")
+            ("\n")
+        }
+    }
+}
+
+/// Loads a grammar written in the syntax of [`rules!`](crate::rules), without transformers,
+/// matchers and imports. Every alternative produces a [`Cst::Node`] with the values of its parts.
+///
+/// Errors in the grammar are reported like any other parse error, with their position.
+pub fn parse_grammar<'a, I: IntoInput<'a>>(grammar: I) -> Result<Rules, ParseError> {
+    let grammar = GrammarRules
+        .parse_entire("start", grammar)?
+        .downcast::<Vec<GrammarRule>>()
+        .unwrap();
+
+    let mut rules = Vec::new();
+    let mut policies = Vec::new();
+    let mut spans = HashMap::new();
+
+    for rule in *grammar {
+        spans.entry(rule.name.clone()).or_insert(rule.span);
+
+        if let Some((policy, span)) = rule.policy {
+            let policy = match policy.as_str() {
+                "heuristic" => ChoicePolicy::Heuristic,
+                "ordered" => ChoicePolicy::Ordered,
+                "longest" => ChoicePolicy::Longest,
+                _ => {
+                    return Err(ParseError::TransformerError {
+                        current_rule: "rule".to_owned(),
+                        pos: span.start.pos,
+                        row: span.start.line,
+                        col: span.start.column,
+                        error: Box::new(GrammarError::UnknownPolicy { policy }),
+                    })
+                }
+            };
+
+            policies.push((rule.name.clone(), policy));
+        }
+
+        for parts in rule.alternatives {
+            rules.push(Rule {
+                name: rule.name.clone(),
+                params: rule.params.clone(),
+                transformer: Some(cst_transformer(rule.name.clone(), parts.len())),
+                parts,
+            });
+        }
+    }
+
+    Rules::try_with_policies(rules, policies).map_err(|error| {
+        let (InstanceError::RuleNotFound { rule_name, .. }
+        | InstanceError::WrongArgumentCount { rule_name, .. }) = &error;
+        let span = spans[rule_name];
+
+        ParseError::TransformerError {
+            current_rule: "rule".to_owned(),
+            pos: span.start.pos,
+            row: span.start.line,
+            col: span.start.column,
+            error: Box::new(error),
+        }
+    })
+}
+
+fn cst_transformer(rule: String, len: usize) -> Transformer {
+    Transformer::new(move |v, _| {
+        Cst::Node {
+            rule: rule.clone(),
+            children: (0..len).filter_map(|i| Cst::from_value(v(i))).collect(),
+        }
+        .into_value()
+    })
+}
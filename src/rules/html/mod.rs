@@ -0,0 +1,200 @@
+use super::simple_xml::{Attributes, Xml};
+use super::*;
+use crate::input::MatcherInput;
+
+mod entities;
+mod tree;
+
+use tree::TreeBuilder;
+
+// The tags and nodes of a document, before the tree is built
+enum Markup {
+    StartTag {
+        name: String,
+        attributes: Attributes,
+        self_closing: bool,
+    },
+    EndTag(String),
+    Node(Xml),
+    // Doctypes and other declarations, which don't change the tree
+    Ignored,
+}
+
+declare_rules! {
+    // A lenient HTML parser, which parses any input into the nodes at its top level. Names are lowercase,
+    // character references are decoded, and elements are closed like HTML 5 does when end tags are left out
+    // (the `html`, `head` and `body` elements aren't added though). Markup that can't be parsed is text.
+    pub HtmlRules {
+        #[matcher (tag_name) as tag_name]
+        #[matcher (attribute_name) as attribute_name]
+
+        start /* Vec<Xml> */ {
+            (markup) => |v, _| {
+                let mut tree = TreeBuilder::new();
+
+                for markup in *v(0).downcast::<Vec<Markup>>().unwrap() {
+                    tree.push(markup);
+                }
+
+                tree.finish().into_value()
+            };
+        }
+
+        markup /* Vec<Markup> */ {
+            () => |_, _| Vec::<Markup>::new().into_value();
+            (markup item) => |v, _| {
+                let mut markup = v(0).downcast::<Vec<Markup>>().unwrap();
+                markup.push(*v(1).downcast::<Markup>().unwrap());
+
+                markup
+            };
+        }
+
+        // A `<` that doesn't start a tag is text
+        #[policy(ordered)]
+        item /* Markup */ {
+            ("<!--" (until "-->") "-->") => |v, _| {
+                Markup::Node(Xml::Comment(v(1).downcast::<Token>().unwrap().to_string())).into_value()
+            };
+            // Doctypes, and anything else like `<!...>` or `<?...>`
+            ("<!" (until ">") ">") => |_, _| Markup::Ignored.into_value();
+            ("<?" (until ">") ">") => |_, _| Markup::Ignored.into_value();
+            (raw_text_element) => |v, _| Markup::Node(*v(0).downcast::<Xml>().unwrap()).into_value();
+            ("<" tag_name attributes (ws) ">") => |v, _| start_tag(v(1), v(2), false);
+            ("<" tag_name attributes (ws) "/>") => |v, _| start_tag(v(1), v(2), true);
+            ("</" tag_name (until ">") ">") => |v, _| Markup::EndTag(*v(1).downcast().unwrap()).into_value();
+            ((until "<") (? |v| !v[0].downcast_ref::<Token>().unwrap().is_empty())) => |v, _| {
+                Markup::Node(Xml::Text(entities::decode(&v(0).downcast::<Token>().unwrap()))).into_value()
+            };
+            ("<") => |_, _| Markup::Node(Xml::Text("<".to_owned())).into_value();
+        }
+
+        // The text of `script` and `style` is never markup, and that of `textarea` and `title` only has
+        // character references
+        raw_text_element /* Xml */ {
+            ("<" (i "script") attributes (ws) ">" (until (i "</script")) (i "</script") (until ">") ">")
+                => |v, _| raw_text_element(v(1), v(2), v(5), false);
+            ("<" (i "style") attributes (ws) ">" (until (i "</style")) (i "</style") (until ">") ">")
+                => |v, _| raw_text_element(v(1), v(2), v(5), false);
+            ("<" (i "textarea") attributes (ws) ">" (until (i "</textarea")) (i "</textarea") (until ">") ">")
+                => |v, _| raw_text_element(v(1), v(2), v(5), true);
+            ("<" (i "title") attributes (ws) ">" (until (i "</title")) (i "</title") (until ">") ">")
+                => |v, _| raw_text_element(v(1), v(2), v(5), true);
+        }
+
+        // Attributes which are repeated keep their first value
+        attributes /* Attributes */ {
+            () => |_, _| Attributes::new().into_value();
+            (attributes space attribute) => |v, _| {
+                let mut attributes = v(0).downcast::<Attributes>().unwrap();
+                let (name, value) = *v(2).downcast::<(String, String)>().unwrap();
+
+                if !attributes.contains(&name) {
+                    attributes.insert(name, value);
+                }
+
+                attributes
+            };
+        }
+
+        // Attributes without a value (like `disabled`) have an empty one
+        attribute /* (String, String) */ {
+            (attribute_name) => |v, _| (*v(0).downcast::<String>().unwrap(), String::new()).into_value();
+            (attribute_name (ws) "=" (ws) attribute_value) => |v, _| {
+                (*v(0).downcast::<String>().unwrap(), *v(4).downcast::<String>().unwrap()).into_value()
+            };
+        }
+
+        attribute_value /* String */ {
+            ("\"" (until "\"") "\"") => |v, _| entities::decode(&v(1).downcast::<Token>().unwrap()).into_value();
+            ("'" (until "'") "'") => |v, _| entities::decode(&v(1).downcast::<Token>().unwrap()).into_value();
+            ((until " " "\t" "\n" "\r" "\u{c}" ">") (? |v| {
+                let value = v[0].downcast_ref::<Token>().unwrap();
+
+                !value.is_empty() && !value.as_str().starts_with(['"', '\''])
+            })) => |v, _| entities::decode(&v(0).downcast::<Token>().unwrap()).into_value();
+        }
+
+        space {
+            (ws_char (ws))
+        }
+
+        ws {
+            ()
+            (ws ws_char)
+        }
+
+        ws_char {
+            (" ")
+            ("\t")
+            ("\n")
+            ("\r")
+            ("\u{c}")
+        }
+    }
+}
+
+// Matches the name of a tag, which starts with a letter
+fn tag_name(input: &mut dyn MatcherInput) -> Option<ParseValue> {
+    if !input.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let mut name = String::new();
+
+    while input
+        .peek()
+        .is_some_and(|c| !c.is_whitespace() && c != '/' && c != '>')
+    {
+        name.push(input.next().unwrap().to_ascii_lowercase());
+    }
+
+    Some(name.into_value())
+}
+
+fn attribute_name(input: &mut dyn MatcherInput) -> Option<ParseValue> {
+    let mut name = String::new();
+
+    while input.peek().is_some_and(|c| {
+        !c.is_whitespace()
+            && !matches!(c, '/' | '>' | '"' | '\'' | '<')
+            && (c != '=' || name.is_empty())
+    }) {
+        name.push(input.next().unwrap().to_ascii_lowercase());
+    }
+
+    (!name.is_empty()).then(|| name.into_value())
+}
+
+fn start_tag(name: ParseValue, attributes: ParseValue, self_closing: bool) -> ParseValue {
+    Markup::StartTag {
+        name: *name.downcast().unwrap(),
+        attributes: *attributes.downcast().unwrap(),
+        self_closing,
+    }
+    .into_value()
+}
+
+fn raw_text_element(
+    name: ParseValue,
+    attributes: ParseValue,
+    text: ParseValue,
+    escapable: bool,
+) -> ParseValue {
+    let text = text.downcast::<Token>().unwrap();
+    let text = if escapable {
+        entities::decode(&text)
+    } else {
+        text.to_string()
+    };
+
+    let mut element =
+        simple_xml::Element::new(name.downcast::<Token>().unwrap().to_ascii_lowercase());
+    element.attributes = *attributes.downcast().unwrap();
+
+    if !text.is_empty() {
+        element.children.push(Xml::Text(text));
+    }
+
+    Xml::Element(element).into_value()
+}
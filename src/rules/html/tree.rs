@@ -0,0 +1,181 @@
+use super::Markup;
+use crate::rules::simple_xml::{Element, Xml};
+
+// Elements which can't have content, and so have no end tag
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "keygen", "link", "meta", "param",
+    "source", "track", "wbr",
+];
+
+// Elements which end an open `p`
+const CLOSES_P: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "main",
+    "menu",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+
+const CELLS: &[&str] = &["td", "th"];
+
+const SECTIONS: &[&str] = &["thead", "tbody", "tfoot"];
+
+const HEADINGS: &[&str] = &["h1", "h2", "h3", "h4", "h5", "h6"];
+
+// Elements which an implied end tag can't reach past
+const SCOPE: &[&str] = &[
+    "applet", "button", "caption", "html", "marquee", "object", "table", "td", "template", "th",
+];
+
+// Builds the tree from the tags, closing the elements whose end tags can be left out
+pub(super) struct TreeBuilder {
+    nodes: Vec<Xml>,
+    open: Vec<Element>,
+}
+
+impl TreeBuilder {
+    pub(super) fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            open: Vec::new(),
+        }
+    }
+
+    pub(super) fn push(&mut self, markup: Markup) {
+        match markup {
+            Markup::StartTag {
+                name,
+                attributes,
+                self_closing,
+            } => {
+                self.imply_end_tags(&name);
+
+                let mut element = Element::new(name);
+                element.attributes = attributes;
+
+                // `/>` closes any element, which is what's meant in SVG and XHTML
+                if self_closing || VOID_ELEMENTS.contains(&element.name.as_str()) {
+                    self.append(Xml::Element(element));
+                } else {
+                    self.open.push(element);
+                }
+            }
+            // An end tag without an open element is ignored
+            Markup::EndTag(name) => {
+                if let Some(i) = self.open.iter().rposition(|element| element.name == name) {
+                    self.close_to(i);
+                }
+            }
+            Markup::Node(xml) => self.append(xml),
+            Markup::Ignored => {}
+        }
+    }
+
+    // The nodes at the top level, with every element still open closed
+    pub(super) fn finish(mut self) -> Vec<Xml> {
+        self.close_to(0);
+
+        self.nodes
+    }
+
+    fn imply_end_tags(&mut self, name: &str) {
+        if CLOSES_P.contains(&name) {
+            self.close_in_scope(&["p"], &[]);
+        }
+
+        match name {
+            "li" => self.close_in_scope(&["li"], &["ol", "ul"]),
+            "dt" | "dd" => self.close_in_scope(&["dt", "dd"], &["dl"]),
+            // A new cell, row or section of a table ends the ones before it
+            "td" | "th" => self.close_in_scope(CELLS, &["tr"]),
+            "tr" => {
+                self.close_in_scope(CELLS, &["tr"]);
+                self.close_in_scope(&["tr"], SECTIONS);
+            }
+            "thead" | "tbody" | "tfoot" => {
+                self.close_in_scope(CELLS, &["tr"]);
+                self.close_in_scope(&["tr"], SECTIONS);
+                self.close_in_scope(SECTIONS, &[]);
+            }
+            "option" => self.close_current(&["option"]),
+            "optgroup" => self.close_current(&["option", "optgroup"]),
+            name if HEADINGS.contains(&name) => self.close_current(HEADINGS),
+            _ => {}
+        }
+    }
+
+    // Closes the innermost open element named one of `names`, unless an element of the scope is in the way
+    fn close_in_scope(&mut self, names: &[&str], scope: &[&str]) {
+        for i in (0..self.open.len()).rev() {
+            let open = self.open[i].name.as_str();
+
+            if names.contains(&open) {
+                self.close_to(i);
+                return;
+            }
+
+            if SCOPE.contains(&open) || scope.contains(&open) {
+                return;
+            }
+        }
+    }
+
+    // Closes the current element if it's named one of `names`
+    fn close_current(&mut self, names: &[&str]) {
+        if self
+            .open
+            .last()
+            .is_some_and(|element| names.contains(&element.name.as_str()))
+        {
+            self.close_to(self.open.len() - 1);
+        }
+    }
+
+    // Closes the open element at `i` and every element inside it
+    fn close_to(&mut self, i: usize) {
+        while self.open.len() > i {
+            let element = self.open.pop().unwrap();
+
+            self.append(Xml::Element(element));
+        }
+    }
+
+    // Text directly after other text is added to it
+    fn append(&mut self, xml: Xml) {
+        let nodes = match self.open.last_mut() {
+            Some(element) => &mut element.children,
+            None => &mut self.nodes,
+        };
+
+        match (nodes.last_mut(), xml) {
+            (Some(Xml::Text(last)), Xml::Text(text)) => last.push_str(&text),
+            (_, xml) => nodes.push(xml),
+        }
+    }
+}
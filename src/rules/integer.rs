@@ -5,14 +5,18 @@ use super::*;
 declare_rules! {
     pub Integer {
         integer /* isize */ {
-            (unsigned) => |v, _| (*v(0).downcast::<usize>().unwrap() as isize).into_value();
-            ("+" unsigned) => |v, _| (*v(1).downcast::<usize>().unwrap() as isize).into_value();
-            ("-" unsigned) => |v, _| (-(*v(1).downcast::<usize>().unwrap() as isize)).into_value();
+            (unsigned (? |v| fits_isize(&v[0], false)))
+                => |v, _| (*v(0).downcast::<usize>().unwrap() as isize).into_value();
+            ("+" unsigned (? |v| fits_isize(&v[1], false)))
+                => |v, _| (*v(1).downcast::<usize>().unwrap() as isize).into_value();
+            ("-" unsigned (? |v| fits_isize(&v[1], true)))
+                => |v, _| (*v(1).downcast::<usize>().unwrap() as isize).wrapping_neg().into_value();
         }
 
         unsigned /* usize */ {
             ("0") => |_, _| 0_usize.into_value();
-            (_int)
+            // Values that don't fit in a usize are rejected
+            (_int (? |v| v[0].downcast_ref::<String>().unwrap().parse::<usize>().is_ok()))
                 => |v, _| v(0).downcast::<String>().unwrap().parse::<usize>().unwrap().into_value();
         }
 
@@ -45,3 +49,14 @@ declare_rules! {
         }
     }
 }
+
+// Whether the (unsigned) value fits in an isize, optionally after negating it
+fn fits_isize(value: &ParseValue, negative: bool) -> bool {
+    let value = *value.downcast_ref::<usize>().unwrap();
+
+    if negative {
+        value <= isize::MIN.unsigned_abs()
+    } else {
+        value <= isize::MAX as usize
+    }
+}
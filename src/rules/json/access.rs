@@ -0,0 +1,201 @@
+use std::ops::{Index, IndexMut};
+
+use super::{Json, Map};
+
+/// Something a [`Json`] can be indexed by: a key of an object (`&str` or `String`),
+/// or an index into an array (`usize`).
+pub trait JsonIndex {
+    fn index_into<'j>(&self, json: &'j Json) -> Option<&'j Json>;
+
+    fn index_into_mut<'j>(&self, json: &'j mut Json) -> Option<&'j mut Json>;
+}
+
+impl JsonIndex for usize {
+    fn index_into<'j>(&self, json: &'j Json) -> Option<&'j Json> {
+        json.as_array()?.get(*self)
+    }
+
+    fn index_into_mut<'j>(&self, json: &'j mut Json) -> Option<&'j mut Json> {
+        json.as_array_mut()?.get_mut(*self)
+    }
+}
+
+impl JsonIndex for str {
+    fn index_into<'j>(&self, json: &'j Json) -> Option<&'j Json> {
+        json.as_object()?.get(self)
+    }
+
+    fn index_into_mut<'j>(&self, json: &'j mut Json) -> Option<&'j mut Json> {
+        json.as_object_mut()?.get_mut(self)
+    }
+}
+
+impl JsonIndex for String {
+    fn index_into<'j>(&self, json: &'j Json) -> Option<&'j Json> {
+        self.as_str().index_into(json)
+    }
+
+    fn index_into_mut<'j>(&self, json: &'j mut Json) -> Option<&'j mut Json> {
+        self.as_str().index_into_mut(json)
+    }
+}
+
+impl<T: JsonIndex + ?Sized> JsonIndex for &T {
+    fn index_into<'j>(&self, json: &'j Json) -> Option<&'j Json> {
+        (**self).index_into(json)
+    }
+
+    fn index_into_mut<'j>(&self, json: &'j mut Json) -> Option<&'j mut Json> {
+        (**self).index_into_mut(json)
+    }
+}
+
+static NULL: Json = Json::Null;
+
+/// Indexing a value that isn't an object or array, or with a key or index that isn't there,
+/// gives `Json::Null`, so lookups can be chained: `json["a"][0]["b"]`.
+impl<I: JsonIndex> Index<I> for Json {
+    type Output = Json;
+
+    fn index(&self, index: I) -> &Json {
+        index.index_into(self).unwrap_or(&NULL)
+    }
+}
+
+/// Panics if the key or index isn't there.
+impl<I: JsonIndex> IndexMut<I> for Json {
+    fn index_mut(&mut self, index: I) -> &mut Json {
+        index
+            .index_into_mut(self)
+            .expect("The key or index should be in the JSON value")
+    }
+}
+
+impl Json {
+    pub fn get(&self, index: impl JsonIndex) -> Option<&Json> {
+        index.index_into(self)
+    }
+
+    pub fn get_mut(&mut self, index: impl JsonIndex) -> Option<&mut Json> {
+        index.index_into_mut(self)
+    }
+
+    /// Looks up a value using a JSON Pointer (RFC 6901), like `/servers/0/name`.
+    /// The empty pointer refers to the whole value.
+    pub fn pointer(&self, pointer: &str) -> Option<&Json> {
+        let mut json = self;
+
+        for token in pointer_tokens(pointer)? {
+            json = match json {
+                Json::Object(object) => object.get(&token)?,
+                Json::Array(array) => array.get(array_index(&token)?)?,
+                _ => return None,
+            };
+        }
+
+        Some(json)
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Json> {
+        let mut json = self;
+
+        for token in pointer_tokens(pointer)? {
+            json = match json {
+                Json::Object(object) => object.get_mut(&token)?,
+                Json::Array(array) => array.get_mut(array_index(&token)?)?,
+                _ => return None,
+            };
+        }
+
+        Some(json)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Json::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Boolean(boolean) => Some(*boolean),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    /// The number, if it is a whole number that fits in an `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_f64().and_then(whole_number)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Json>> {
+        match self {
+            Json::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Json>> {
+        match self {
+            Json::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Map> {
+        match self {
+            Json::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    pub fn as_object_mut(&mut self) -> Option<&mut Map> {
+        match self {
+            Json::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+}
+
+pub(super) fn whole_number(number: f64) -> Option<i64> {
+    // i64::MAX as f64 rounds up to 2^63, which doesn't fit
+    (number.fract() == 0.0 && number >= i64::MIN as f64 && number < i64::MAX as f64)
+        .then_some(number as i64)
+}
+
+// The reference tokens of a pointer, with `~1` and `~0` unescaped
+fn pointer_tokens(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+
+    pointer.strip_prefix('/').map(|pointer| {
+        pointer
+            .split('/')
+            .map(|token| token.replace("~1", "/").replace("~0", "~"))
+            .collect()
+    })
+}
+
+// Array indices are written without leading zeros
+fn array_index(token: &str) -> Option<usize> {
+    let digits = !token.is_empty() && token.chars().all(|c| c.is_ascii_digit());
+
+    if digits && (token == "0" || !token.starts_with('0')) {
+        token.parse().ok()
+    } else {
+        None
+    }
+}
@@ -0,0 +1,263 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::Infallible,
+    error::Error,
+    fmt::Display,
+};
+
+use super::{access::whole_number, Json, Map};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonConversionError {
+    WrongType {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// The number has a fraction, or doesn't fit in the integer type.
+    NotAnInteger {
+        number: f64,
+        integer_type: &'static str,
+    },
+}
+
+impl Error for JsonConversionError {}
+
+impl Display for JsonConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonConversionError::WrongType { expected, found } => {
+                f.write_fmt(format_args!("Expected {expected}, found {found}"))
+            }
+            JsonConversionError::NotAnInteger {
+                number,
+                integer_type,
+            } => f.write_fmt(format_args!(
+                "The number {number} can't be converted to {integer_type}"
+            )),
+        }
+    }
+}
+
+// So that `Json` itself can be used for the values of arrays and objects
+impl From<Infallible> for JsonConversionError {
+    fn from(infallible: Infallible) -> Self {
+        match infallible {}
+    }
+}
+
+impl Json {
+    /// The name of the kind of value, as used in [`JsonConversionError`].
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Json::Null => "null",
+            Json::Number(_) => "a number",
+            Json::Boolean(_) => "a boolean",
+            Json::String(_) => "a string",
+            Json::Array(_) => "an array",
+            Json::Object(_) => "an object",
+        }
+    }
+
+    fn wrong_type(&self, expected: &'static str) -> JsonConversionError {
+        JsonConversionError::WrongType {
+            expected,
+            found: self.type_name(),
+        }
+    }
+}
+
+impl From<()> for Json {
+    fn from(_: ()) -> Self {
+        Json::Null
+    }
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Json::Boolean(value)
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Json::String(value)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::String(value.to_owned())
+    }
+}
+
+impl From<Map> for Json {
+    fn from(value: Map) -> Self {
+        Json::Object(value)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(value: Vec<T>) -> Self {
+        Json::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+// The members of a HashMap are in no particular order
+impl<T: Into<Json>> From<HashMap<String, T>> for Json {
+    fn from(value: HashMap<String, T>) -> Self {
+        Json::Object(value.into_iter().map(|(k, v)| (k, v.into())).collect())
+    }
+}
+
+impl<T: Into<Json>> From<BTreeMap<String, T>> for Json {
+    fn from(value: BTreeMap<String, T>) -> Self {
+        Json::Object(value.into_iter().map(|(k, v)| (k, v.into())).collect())
+    }
+}
+
+impl TryFrom<Json> for () {
+    type Error = JsonConversionError;
+
+    fn try_from(value: Json) -> Result<Self, Self::Error> {
+        match value {
+            Json::Null => Ok(()),
+            value => Err(value.wrong_type("null")),
+        }
+    }
+}
+
+impl TryFrom<Json> for bool {
+    type Error = JsonConversionError;
+
+    fn try_from(value: Json) -> Result<Self, Self::Error> {
+        value.as_bool().ok_or_else(|| value.wrong_type("a boolean"))
+    }
+}
+
+impl TryFrom<Json> for String {
+    type Error = JsonConversionError;
+
+    fn try_from(value: Json) -> Result<Self, Self::Error> {
+        match value {
+            Json::String(string) => Ok(string),
+            value => Err(value.wrong_type("a string")),
+        }
+    }
+}
+
+impl TryFrom<Json> for Map {
+    type Error = JsonConversionError;
+
+    fn try_from(value: Json) -> Result<Self, Self::Error> {
+        match value {
+            Json::Object(object) => Ok(object),
+            value => Err(value.wrong_type("an object")),
+        }
+    }
+}
+
+impl<T: TryFrom<Json>> TryFrom<Json> for Vec<T>
+where
+    T::Error: Into<JsonConversionError>,
+{
+    type Error = JsonConversionError;
+
+    fn try_from(value: Json) -> Result<Self, Self::Error> {
+        match value {
+            Json::Array(array) => array
+                .into_iter()
+                .map(|value| T::try_from(value).map_err(Into::into))
+                .collect(),
+            value => Err(value.wrong_type("an array")),
+        }
+    }
+}
+
+impl<T: TryFrom<Json>> TryFrom<Json> for HashMap<String, T>
+where
+    T::Error: Into<JsonConversionError>,
+{
+    type Error = JsonConversionError;
+
+    fn try_from(value: Json) -> Result<Self, Self::Error> {
+        Map::try_from(value)?
+            .into_iter()
+            .map(|(key, value)| Ok((key, T::try_from(value).map_err(Into::into)?)))
+            .collect()
+    }
+}
+
+impl<T: TryFrom<Json>> TryFrom<Json> for BTreeMap<String, T>
+where
+    T::Error: Into<JsonConversionError>,
+{
+    type Error = JsonConversionError;
+
+    fn try_from(value: Json) -> Result<Self, Self::Error> {
+        Map::try_from(value)?
+            .into_iter()
+            .map(|(key, value)| Ok((key, T::try_from(value).map_err(Into::into)?)))
+            .collect()
+    }
+}
+
+macro_rules! float_conversions {
+    ($($float:ty),*) => {
+        $(
+            impl From<$float> for Json {
+                fn from(value: $float) -> Self {
+                    Json::Number(value as f64)
+                }
+            }
+
+            impl TryFrom<Json> for $float {
+                type Error = JsonConversionError;
+
+                fn try_from(value: Json) -> Result<Self, Self::Error> {
+                    value
+                        .as_f64()
+                        .map(|number| number as $float)
+                        .ok_or_else(|| value.wrong_type("a number"))
+                }
+            }
+        )*
+    };
+}
+
+float_conversions!(f32, f64);
+
+// Integers are stored as f64s, so only those up to 2^53 are exact
+macro_rules! integer_conversions {
+    ($($integer:ty),*) => {
+        $(
+            impl From<$integer> for Json {
+                fn from(value: $integer) -> Self {
+                    Json::Number(value as f64)
+                }
+            }
+
+            impl TryFrom<Json> for $integer {
+                type Error = JsonConversionError;
+
+                fn try_from(value: Json) -> Result<Self, Self::Error> {
+                    let number = value.as_f64().ok_or_else(|| value.wrong_type("a number"))?;
+
+                    whole_number(number)
+                        .and_then(|integer| <$integer>::try_from(integer).ok())
+                        .ok_or(JsonConversionError::NotAnInteger {
+                            number,
+                            integer_type: stringify!($integer),
+                        })
+                }
+            }
+        )*
+    };
+}
+
+integer_conversions!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
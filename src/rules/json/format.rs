@@ -0,0 +1,176 @@
+use std::fmt::{Display, Write};
+
+use super::Json;
+
+/// How [`Json::format`] writes JSON text.
+///
+/// The default is compact, like `Display`: no whitespace at all, and the members of objects in their order.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct JsonFormat {
+    indent: Option<String>,
+    sort_keys: bool,
+    ascii: bool,
+}
+
+impl JsonFormat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Puts every value of an array or object on its own line, indented by `indent` for each level.
+    pub fn indent(mut self, indent: impl Into<String>) -> Self {
+        self.indent = Some(indent.into());
+        self
+    }
+
+    /// Writes the members of objects sorted by key, instead of in their order.
+    pub fn sort_keys(mut self) -> Self {
+        self.sort_keys = true;
+        self
+    }
+
+    /// Escapes every character outside of ASCII (as `\uXXXX`, using surrogate pairs if needed).
+    pub fn ascii(mut self) -> Self {
+        self.ascii = true;
+        self
+    }
+}
+
+impl Json {
+    /// Writes the JSON text of the value using `format`.
+    pub fn format(&self, format: &JsonFormat) -> String {
+        let mut text = String::new();
+
+        write_json(&mut text, self, format, 0).expect("Writing to a String can't fail");
+
+        text
+    }
+
+    /// Writes the JSON text of the value indented by 2 spaces. The same as `format!("{json:#}")`.
+    pub fn to_string_pretty(&self) -> String {
+        self.format(&JsonFormat::new().indent("  "))
+    }
+}
+
+/// Writes compact JSON text, or pretty printed JSON text with `{:#}`.
+impl Display for Json {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format = if f.alternate() {
+            JsonFormat::new().indent("  ")
+        } else {
+            JsonFormat::new()
+        };
+
+        write_json(f, self, &format, 0)
+    }
+}
+
+fn write_json(
+    f: &mut impl Write,
+    json: &Json,
+    format: &JsonFormat,
+    depth: usize,
+) -> std::fmt::Result {
+    match json {
+        Json::Null => f.write_str("null"),
+        Json::Boolean(boolean) => write!(f, "{boolean}"),
+        // NaN and the infinities can't be written in JSON
+        Json::Number(number) if !number.is_finite() => f.write_str("null"),
+        Json::Number(number) => {
+            // Debug uses exponents for very large and small numbers, unlike Display
+            let number = format!("{number:?}");
+
+            f.write_str(number.strip_suffix(".0").unwrap_or(&number))
+        }
+        Json::String(string) => write_string(f, string, format.ascii),
+        Json::Array(array) => {
+            if array.is_empty() {
+                return f.write_str("[]");
+            }
+
+            f.write_char('[')?;
+
+            for (i, value) in array.iter().enumerate() {
+                if i > 0 {
+                    f.write_char(',')?;
+                }
+
+                write_newline(f, format, depth + 1)?;
+                write_json(f, value, format, depth + 1)?;
+            }
+
+            write_newline(f, format, depth)?;
+            f.write_char(']')
+        }
+        Json::Object(object) => {
+            if object.is_empty() {
+                return f.write_str("{}");
+            }
+
+            let mut members = object.iter().collect::<Vec<_>>();
+
+            if format.sort_keys {
+                members.sort_by_key(|(key, _)| *key);
+            }
+
+            f.write_char('{')?;
+
+            for (i, (key, value)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    f.write_char(',')?;
+                }
+
+                write_newline(f, format, depth + 1)?;
+                write_string(f, key, format.ascii)?;
+                f.write_char(':')?;
+
+                if format.indent.is_some() {
+                    f.write_char(' ')?;
+                }
+
+                write_json(f, value, format, depth + 1)?;
+            }
+
+            write_newline(f, format, depth)?;
+            f.write_char('}')
+        }
+    }
+}
+
+fn write_newline(f: &mut impl Write, format: &JsonFormat, depth: usize) -> std::fmt::Result {
+    if let Some(indent) = &format.indent {
+        f.write_char('\n')?;
+
+        for _ in 0..depth {
+            f.write_str(indent)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_string(f: &mut impl Write, string: &str, ascii: bool) -> std::fmt::Result {
+    f.write_char('"')?;
+
+    for c in string.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\u{8}' => f.write_str("\\b")?,
+            '\u{c}' => f.write_str("\\f")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c < '\u{20}' || (ascii && !c.is_ascii()) => {
+                let mut units = [0; 2];
+
+                for unit in c.encode_utf16(&mut units) {
+                    write!(f, "\\u{unit:04x}")?;
+                }
+            }
+            c => f.write_char(c)?,
+        }
+    }
+
+    f.write_char('"')
+}
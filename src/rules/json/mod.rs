@@ -0,0 +1,219 @@
+use super::*;
+
+mod format;
+pub use format::JsonFormat;
+
+mod access;
+pub use access::JsonIndex;
+
+mod convert;
+pub use convert::JsonConversionError;
+
+/// The members of a JSON object, in the order in which they were inserted.
+pub type Map = OrderedMap<Json>;
+
+/// A JSON value, as parsed by [`JsonRules`].
+///
+/// Values can be written as JSON text using `Display` (or [`Json::format`]),
+/// looked up by indexing (`json["servers"][0]`) or with a JSON Pointer ([`Json::pointer`]),
+/// and converted from and to Rust values using `From` and `TryFrom`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Json {
+    #[default]
+    Null,
+    Number(f64),
+    Boolean(bool),
+    String(String),
+    Array(Vec<Json>),
+    /// The members are kept in the order in which they were written.
+    Object(Map),
+}
+
+declare_rules! {
+    // JSON as specified by RFC 8259
+    pub JsonRules {
+        #[import (Whitespace) as ws]
+        #[import (Integer) as integer]
+        #[import (Hex) as hex]
+
+        // Padded with whitespace on both sides
+        start /* Json */ {
+            ((ws::ws_ml) json (ws::ws_ml)) => |v, _| v(1);
+        }
+
+        json /* Json */ {
+            ("null") => |_, _| Json::Null.into_value();
+            ("true") => |_, _| Json::Boolean(true).into_value();
+            ("false") => |_, _| Json::Boolean(false).into_value();
+            (number) => |v, _| Json::Number(*v(0).downcast().unwrap()).into_value();
+            (string) => |v, _| Json::String(*v(0).downcast().unwrap()).into_value();
+            ("[" (ws::ws_ml) "]") => |_, _| Json::Array(Vec::new()).into_value();
+            ("[" (comma_list<element>) "]") => |v, _| {
+                Json::Array(
+                    v(1).downcast::<Vec<ParseValue>>()
+                        .unwrap()
+                        .into_iter()
+                        .map(|json| *json.downcast::<Json>().unwrap())
+                        .collect()
+                ).into_value()
+            };
+            ("{" (ws::ws_ml) "}") => |_, _| Json::Object(Map::new()).into_value();
+            ("{" (comma_list<member>) "}") => |v, _| {
+                Json::Object(
+                    v(1).downcast::<Vec<ParseValue>>()
+                        .unwrap()
+                        .into_iter()
+                        .map(|pair| *pair.downcast::<(String, Json)>().unwrap())
+                        .collect()
+                ).into_value()
+            };
+        }
+
+        // One or more items separated by commas
+        comma_list<item> /* Vec<ParseValue> */ {
+            (item) => |v, _| vec![v(0)].into_value();
+            (comma_list "," item) => |v, _| {
+                let mut list = v(0).downcast::<Vec<ParseValue>>().unwrap();
+
+                list.push(v(2));
+
+                list
+            };
+        }
+
+        element /* Json */ {
+            ((ws::ws_ml) json (ws::ws_ml)) => |v, _| v(1);
+        }
+
+        member /* (String, Json) */ {
+            ((ws::ws_ml) string (ws::ws_ml) ":" element) => |v, _| {
+                (*v(1).downcast::<String>().unwrap(), *v(4).downcast::<Json>().unwrap()).into_value()
+            };
+        }
+
+        // The text of the number is collected and then parsed as a whole, so it's rounded only once
+        number /* f64 */ {
+            (int fraction exponent) => |v, _| {
+                format!(
+                    "{}{}{}",
+                    v(0).downcast::<String>().unwrap(),
+                    v(1).downcast::<String>().unwrap(),
+                    v(2).downcast::<String>().unwrap()
+                ).parse::<f64>().unwrap().into_value()
+            };
+        }
+
+        int /* String */ {
+            (natural)
+            ("-" natural) => |v, _| format!("-{}", v(1).downcast::<String>().unwrap()).into_value();
+        }
+
+        // Leading zeros aren't allowed
+        natural /* String */ {
+            ("0") => |_, _| "0".to_owned().into_value();
+            ((integer::digit_nonzero) digits) => |v, _| format!(
+                "{}{}",
+                v(0).downcast::<Token>().unwrap(),
+                v(1).downcast::<String>().unwrap()
+            ).into_value();
+        }
+
+        fraction /* String */ {
+            () => |_, _| String::new().into_value();
+            ("." (integer::digit) digits) => |v, _| format!(
+                ".{}{}",
+                v(1).downcast::<Token>().unwrap(),
+                v(2).downcast::<String>().unwrap()
+            ).into_value();
+        }
+
+        exponent /* String */ {
+            () => |_, _| String::new().into_value();
+            ((i "e") sign (integer::digit) digits) => |v, _| format!(
+                "e{}{}{}",
+                v(1).downcast::<Token>().map(|sign| sign.to_string()).unwrap_or_default(),
+                v(2).downcast::<Token>().unwrap(),
+                v(3).downcast::<String>().unwrap()
+            ).into_value();
+        }
+
+        sign {
+            ()
+            ("+")
+            ("-")
+        }
+
+        // Zero or more digits
+        digits /* String */ {
+            () => |_, _| String::new().into_value();
+            (digits (integer::digit)) => |v, _| format!(
+                "{}{}",
+                v(0).downcast::<String>().unwrap(),
+                v(1).downcast::<Token>().unwrap()
+            ).into_value();
+        }
+
+        string /* String */ {
+            ("\"" characters "\"") => |v, _| v(1);
+        }
+
+        characters /* String */ {
+            () => |_, _| String::new().into_value();
+            (characters character) => |v, _| {
+                let mut characters = v(0).downcast::<String>().unwrap();
+
+                characters.push_str(&v(1).downcast::<String>().unwrap());
+
+                characters
+            };
+        }
+
+        character /* String */ {
+            (escape)
+            // Control characters have to be escaped
+            ((until "\"" "\\") (? |v| {
+                let run = v[0].downcast_ref::<Token>().unwrap();
+
+                !run.is_empty() && !run.chars().any(|c| c < '\u{20}')
+            })) => |v, _| v(0).downcast::<Token>().unwrap().to_string().into_value();
+        }
+
+        escape /* String */ {
+            ("\\\"") => |_, _| "\"".to_owned().into_value();
+            ("\\\\") => |_, _| "\\".to_owned().into_value();
+            ("\\/") => |_, _| "/".to_owned().into_value();
+            ("\\b") => |_, _| "\u{8}".to_owned().into_value();
+            ("\\f") => |_, _| "\u{c}".to_owned().into_value();
+            ("\\n") => |_, _| "\n".to_owned().into_value();
+            ("\\r") => |_, _| "\r".to_owned().into_value();
+            ("\\t") => |_, _| "\t".to_owned().into_value();
+            // Lone surrogates can't be represented in a String, so they are rejected
+            ("\\u" hex4 (? |v| char::from_u32(*v[1].downcast_ref::<u32>().unwrap()).is_some()))
+                => |v, _| char::from_u32(*v(1).downcast::<u32>().unwrap()).unwrap().to_string().into_value();
+            ("\\u" hex4 "\\u" hex4 (? |v| {
+                let high = *v[1].downcast_ref::<u32>().unwrap();
+                let low = *v[3].downcast_ref::<u32>().unwrap();
+
+                (0xD800..0xDC00).contains(&high) && (0xDC00..0xE000).contains(&low)
+            })) => |v, _| {
+                let high = *v(1).downcast::<u32>().unwrap();
+                let low = *v(3).downcast::<u32>().unwrap();
+
+                char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+                    .unwrap()
+                    .to_string()
+                    .into_value()
+            };
+        }
+
+        hex4 /* u32 */ {
+            ((hex::digit) (hex::digit) (hex::digit) (hex::digit)) => |v, _| {
+                let hex = (0..4)
+                    .map(|i| v(i).downcast::<Token>().unwrap().to_string())
+                    .collect::<String>();
+
+                u32::from_str_radix(&hex, 16).unwrap().into_value()
+            };
+        }
+    }
+}
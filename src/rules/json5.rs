@@ -0,0 +1,210 @@
+use super::json::{Json, JsonRules, Map};
+use super::*;
+
+declare_rules! {
+    // JSON5 (https://spec.json5.org), which parses into the same `Json` values as JsonRules,
+    // and uses its rules for the parts which JSON5 doesn't change
+    pub Json5Rules {
+        #[import (JsonRules) as json]
+        #[import (Whitespace) as ws]
+        #[import (Integer) as integer]
+        #[import (Hex) as hex]
+        #[import (Identifier) as id]
+        #[import (Comments) as comments]
+
+        // Padded with whitespace and comments on both sides
+        start /* Json */ {
+            (sp json sp) => |v, _| v(1);
+        }
+
+        json /* Json */ {
+            ("null") => |_, _| Json::Null.into_value();
+            ("true") => |_, _| Json::Boolean(true).into_value();
+            ("false") => |_, _| Json::Boolean(false).into_value();
+            (number) => |v, _| Json::Number(*v(0).downcast().unwrap()).into_value();
+            (string) => |v, _| Json::String(*v(0).downcast().unwrap()).into_value();
+            ("[" sp "]") => |_, _| Json::Array(Vec::new()).into_value();
+            ("[" (comma_list<element>) "]") => |v, _| array(v(1));
+            ("[" (comma_list<element>) "," sp "]") => |v, _| array(v(1));
+            ("{" sp "}") => |_, _| Json::Object(Map::new()).into_value();
+            ("{" (comma_list<member>) "}") => |v, _| object(v(1));
+            ("{" (comma_list<member>) "," sp "}") => |v, _| object(v(1));
+        }
+
+        // One or more items separated by commas
+        comma_list<item> /* Vec<ParseValue> */ {
+            (item) => |v, _| vec![v(0)].into_value();
+            (comma_list "," item) => |v, _| {
+                let mut list = v(0).downcast::<Vec<ParseValue>>().unwrap();
+
+                list.push(v(2));
+
+                list
+            };
+        }
+
+        element /* Json */ {
+            (sp json sp) => |v, _| v(1);
+        }
+
+        member /* (String, Json) */ {
+            (sp key sp ":" element) => |v, _| {
+                (*v(1).downcast::<String>().unwrap(), *v(4).downcast::<Json>().unwrap()).into_value()
+            };
+        }
+
+        // Keys can also be written without quotes if they are identifiers
+        key /* String */ {
+            (string)
+            ((id::identifier))
+            ("$" (id::identifier)) => |v, _| format!("${}", v(1).downcast::<String>().unwrap()).into_value();
+        }
+
+        // Whitespace and comments
+        sp {
+            ((ws::ws_ml))
+            (sp (comments::comment) (ws::ws_ml))
+            (sp space (ws::ws_ml))
+        }
+
+        // The whitespace of JSON5 that isn't in `ws::ws_ml`
+        space {
+            ("\u{b}")
+            ("\u{c}")
+            ("\u{a0}")
+            ("\u{feff}")
+            ("\u{2028}")
+            ("\u{2029}")
+        }
+
+        number /* f64 */ {
+            (magnitude)
+            ("+" magnitude) => |v, _| v(1);
+            ("-" magnitude) => |v, _| (-*v(1).downcast::<f64>().unwrap()).into_value();
+        }
+
+        magnitude /* f64 */ {
+            ("Infinity") => |_, _| f64::INFINITY.into_value();
+            ("NaN") => |_, _| f64::NAN.into_value();
+            ((i "0x") (hex::raw_hex)) => |v, _| {
+                v(1).downcast::<String>()
+                    .unwrap()
+                    .chars()
+                    .fold(0.0, |number, digit| number * 16.0 + digit.to_digit(16).unwrap() as f64)
+                    .into_value()
+            };
+            // The text of the number is collected and then parsed as a whole, so it's rounded only once
+            (decimal) => |v, _| v(0).downcast::<String>().unwrap().parse::<f64>().unwrap().into_value();
+        }
+
+        // Either side of the decimal point can be left out, but not both
+        decimal /* String */ {
+            ((json::natural) fraction (json::exponent)) => |v, _| format!(
+                "{}{}{}",
+                v(0).downcast::<String>().unwrap(),
+                v(1).downcast::<String>().unwrap(),
+                v(2).downcast::<String>().unwrap()
+            ).into_value();
+            ("." (integer::digit) (json::digits) (json::exponent)) => |v, _| format!(
+                "0.{}{}{}",
+                v(1).downcast::<Token>().unwrap(),
+                v(2).downcast::<String>().unwrap(),
+                v(3).downcast::<String>().unwrap()
+            ).into_value();
+        }
+
+        // Unlike in JSON, the decimal point can be the last character
+        fraction /* String */ {
+            () => |_, _| String::new().into_value();
+            ("." (json::digits)) => |v, _| format!(".{}", v(1).downcast::<String>().unwrap()).into_value();
+        }
+
+        string /* String */ {
+            ("\"" (characters<"\"">) "\"") => |v, _| v(1);
+            ("'" (characters<"'">) "'") => |v, _| v(1);
+        }
+
+        characters<quote> /* String */ {
+            () => |_, _| String::new().into_value();
+            (characters (character<quote>)) => |v, _| {
+                let mut characters = v(0).downcast::<String>().unwrap();
+
+                characters.push_str(&v(1).downcast::<String>().unwrap());
+
+                characters
+            };
+        }
+
+        // Line breaks have to be escaped
+        character<quote> /* String */ {
+            (escape)
+            ((until quote "\\" "\n" "\r") (? |v| !v[0].downcast_ref::<Token>().unwrap().is_empty()))
+                => |v, _| v(0).downcast::<Token>().unwrap().to_string().into_value();
+        }
+
+        // The escapes of JSON, and some more
+        escape /* String */ {
+            ("\\'") => |_, _| "'".to_owned().into_value();
+            ("\\v") => |_, _| "\u{b}".to_owned().into_value();
+            ("\\0") => |_, _| "\0".to_owned().into_value();
+            // Any byte value is allowed
+            ("\\x" (hex::digit) (hex::digit)) => |v, _| {
+                let hex = format!("{}{}", v(1).downcast::<Token>().unwrap(), v(2).downcast::<Token>().unwrap());
+
+                char::from(u8::from_str_radix(&hex, 16).unwrap()).to_string().into_value()
+            };
+            // A line continuation
+            ("\\" line_break) => |_, _| String::new().into_value();
+            ((json::escape))
+            // Any other character is itself. The escapes of `json::escape` are left out,
+            // as the alternatives starting with a literal are tried first
+            ("\\" (! "\"" "\\" "/" "b" "f" "n" "r" "t" "u" "0" "1" "2" "3" "4" "5" "6" "7" "8" "9" "x" "\n" "\r"))
+                => |v, _| v(1).downcast::<Token>().unwrap().to_string().into_value();
+        }
+
+        line_break {
+            ("\r\n")
+            ("\n")
+            ("\r")
+            ("\u{2028}")
+            ("\u{2029}")
+        }
+    }
+}
+
+declare_rules! {
+    // JSON with comments (as in the settings of many editors): JsonRules, with comments allowed wherever
+    // whitespace is. Unlike JSON5, nothing else is added.
+    pub JsoncRules {
+        #[import (JsonRules) { override ws::ws_ml = sp }]
+        #[import (Comments) as comments]
+        #[import (Whitespace) as whitespace]
+
+        sp {
+            ((whitespace::ws_ml))
+            (sp (comments::comment) (whitespace::ws_ml))
+        }
+    }
+}
+
+fn array(list: ParseValue) -> ParseValue {
+    Json::Array(
+        list.downcast::<Vec<ParseValue>>()
+            .unwrap()
+            .into_iter()
+            .map(|json| *json.downcast::<Json>().unwrap())
+            .collect(),
+    )
+    .into_value()
+}
+
+fn object(list: ParseValue) -> ParseValue {
+    Json::Object(
+        list.downcast::<Vec<ParseValue>>()
+            .unwrap()
+            .into_iter()
+            .map(|member| *member.downcast::<(String, Json)>().unwrap())
+            .collect(),
+    )
+    .into_value()
+}
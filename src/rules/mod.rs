@@ -4,6 +4,9 @@ use psi_parser::prelude::*;
 mod whitespace;
 pub use whitespace::Whitespace;
 
+mod comments;
+pub use comments::Comments;
+
 mod integer;
 pub use integer::Integer;
 
@@ -17,7 +20,7 @@ mod identifier;
 pub use identifier::Identifier;
 
 mod string;
-pub use string::StringRules;
+pub use string::{StringParseError, StringRules};
 
 mod boolean;
 pub use boolean::Boolean;
@@ -25,8 +28,26 @@ pub use boolean::Boolean;
 mod float;
 pub use float::Float;
 
+mod map;
+pub use map::OrderedMap;
+
 pub mod json;
 pub use json::JsonRules;
 
+mod json5;
+pub use json5::{Json5Rules, JsoncRules};
+
 pub mod simple_xml;
 pub use simple_xml::XmlRules;
+
+mod html;
+pub use html::HtmlRules;
+
+pub mod toml;
+pub use toml::TomlRules;
+
+pub mod csv;
+pub use csv::CsvRules;
+
+pub mod grammar;
+pub use grammar::GrammarRules;
@@ -0,0 +1,127 @@
+use crate::rules::OrderedMap;
+
+/// An attribute of an [`Element`](super::Element).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attribute {
+    /// The name as it was written, like `xlink:href`.
+    pub name: String,
+    /// The namespace URI, if the name has a prefix (or is an `xmlns` declaration).
+    pub namespace: Option<String>,
+    pub value: String,
+}
+
+/// The attributes of an element, in the order in which they were written.
+///
+/// Two sets of attributes are equal if they have the same attributes, in any order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Attributes {
+    // by name
+    attributes: OrderedMap<Attribute>,
+}
+
+impl Attributes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.attributes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.attributes.is_empty()
+    }
+
+    /// The value of the attribute named `name` (as written, with any prefix).
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.attribute(name)
+            .map(|attribute| attribute.value.as_str())
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.attributes.contains_key(name)
+    }
+
+    /// Sets the value of an attribute. An attribute that is already there keeps its position,
+    /// and its old value is returned.
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        let name = name.into();
+        let value = value.into();
+
+        match self.attributes.get_mut(&name) {
+            Some(attribute) => Some(std::mem::replace(&mut attribute.value, value)),
+            None => {
+                self.attributes.insert(
+                    name.clone(),
+                    Attribute {
+                        name,
+                        namespace: None,
+                        value,
+                    },
+                );
+
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        self.attributes
+            .remove(name)
+            .map(|attribute| attribute.value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Attribute> {
+        self.attributes.values()
+    }
+
+    /// The attributes can be changed, except for their names, which they are looked up by.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Attribute> {
+        self.attributes.values_mut()
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for Attributes {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut attributes = Attributes::new();
+
+        for (name, value) in iter {
+            attributes.insert(name, value);
+        }
+
+        attributes
+    }
+}
+
+impl IntoIterator for Attributes {
+    type Item = Attribute;
+    type IntoIter = std::iter::Map<
+        std::vec::IntoIter<(String, Attribute)>,
+        fn((String, Attribute)) -> Attribute,
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.attributes.into_iter().map(|(_, attribute)| attribute)
+    }
+}
+
+impl<'a> IntoIterator for &'a Attributes {
+    type Item = &'a Attribute;
+    type IntoIter = std::iter::Map<
+        std::iter::Map<
+            std::slice::Iter<'a, (String, Attribute)>,
+            fn(&'a (String, Attribute)) -> (&'a String, &'a Attribute),
+        >,
+        fn((&'a String, &'a Attribute)) -> &'a Attribute,
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {
+        (&self.attributes)
+            .into_iter()
+            .map(|(_, attribute)| attribute)
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use super::{Element, Xml, XmlParseError, REFERENCE};
+
+/// The namespace which the `xml` prefix is bound to.
+pub const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// The namespace of `xmlns` attributes, which declare namespaces.
+pub const XMLNS_NAMESPACE: &str = "http://www.w3.org/2000/xmlns/";
+
+/// The most entity references that are expanded in a document, counting those inside other entities.
+pub const MAX_ENTITY_REFERENCES: usize = 100_000;
+
+/// The most characters that the entity references of a document expand to in total.
+pub const MAX_EXPANDED_LENGTH: usize = 10_000_000;
+
+/// An XML document, as parsed by the `document` rule of [`XmlRules`](super::XmlRules).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmlDocument {
+    pub declaration: Option<XmlDeclaration>,
+    pub doctype: Option<Doctype>,
+    /// The comments and processing instructions before the root element.
+    pub prolog: Vec<Xml>,
+    pub root: Element,
+    /// The comments and processing instructions after the root element.
+    pub epilog: Vec<Xml>,
+}
+
+/// The `<?xml version="1.0" ...?>` declaration at the start of a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmlDeclaration {
+    pub version: String,
+    pub encoding: Option<String>,
+    pub standalone: Option<bool>,
+}
+
+/// The `<!DOCTYPE ...>` declaration of a document.
+///
+/// Of the markup declarations in its internal subset, only those of internal general entities
+/// (`<!ENTITY name "text">`) are kept. Their references are expanded as text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Doctype {
+    /// The name of the root element.
+    pub name: String,
+    pub public_id: Option<String>,
+    pub system_id: Option<String>,
+    /// The replacement text of each entity, by name.
+    ///
+    /// References to other entities are not expanded in it: their names are kept between two `U+FFFF` characters,
+    /// which can't appear in XML text, and are written back as references when the document is formatted.
+    pub entities: HashMap<String, String>,
+}
+
+impl XmlDocument {
+    // Expands the references to entities and resolves the namespaces of the names
+    pub(super) fn resolve(&mut self) -> Result<(), XmlParseError> {
+        let entities = self
+            .doctype
+            .as_ref()
+            .map(|doctype| doctype.entities.clone())
+            .unwrap_or_default();
+
+        expand_element(&mut self.root, &entities, &mut Expansion::default())?;
+
+        let scope = HashMap::from([("xml".to_owned(), XML_NAMESPACE.to_owned())]);
+
+        resolve_namespaces(&mut self.root, &scope)
+    }
+}
+
+// How far the entities of a document have been expanded, so that entities
+// which refer to each other many times (like "billion laughs") can't expand without bound
+#[derive(Default)]
+struct Expansion {
+    // the entities being expanded, innermost last
+    expanding: Vec<String>,
+    references: usize,
+    length: usize,
+}
+
+fn expand_element(
+    element: &mut Element,
+    entities: &HashMap<String, String>,
+    expansion: &mut Expansion,
+) -> Result<(), XmlParseError> {
+    for attribute in element.attributes.iter_mut() {
+        attribute.value = expand(&attribute.value, entities, expansion)?;
+    }
+
+    for child in &mut element.children {
+        match child {
+            Xml::Element(child) => expand_element(child, entities, expansion)?,
+            Xml::Text(text) => *text = expand(text, entities, expansion)?,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+// References to entities are marked by REFERENCE on both sides of the name while parsing,
+// as the doctype declaring them is only known once the whole document is parsed
+fn expand(
+    text: &str,
+    entities: &HashMap<String, String>,
+    expansion: &mut Expansion,
+) -> Result<String, XmlParseError> {
+    let mut expanded = String::new();
+
+    for (i, part) in text.split(REFERENCE).enumerate() {
+        if i % 2 == 0 {
+            if let Some(name) = expansion.expanding.last() {
+                expansion.length += part.chars().count();
+
+                if expansion.length > MAX_EXPANDED_LENGTH {
+                    return Err(XmlParseError::EntityExpansionLimit { name: name.clone() });
+                }
+            }
+
+            expanded.push_str(part);
+            continue;
+        }
+
+        expansion.references += 1;
+
+        if expansion.references > MAX_ENTITY_REFERENCES {
+            return Err(XmlParseError::EntityExpansionLimit {
+                name: part.to_owned(),
+            });
+        }
+
+        if expansion.expanding.iter().any(|name| name == part) {
+            return Err(XmlParseError::RecursiveEntity {
+                name: part.to_owned(),
+            });
+        }
+
+        let replacement = entities
+            .get(part)
+            .ok_or_else(|| XmlParseError::UndeclaredEntity {
+                name: part.to_owned(),
+            })?;
+
+        expansion.expanding.push(part.to_owned());
+        expanded.push_str(&expand(replacement, entities, expansion)?);
+        expansion.expanding.pop();
+    }
+
+    Ok(expanded)
+}
+
+// `scope` maps the prefixes in scope to their namespaces, with the default namespace as ""
+fn resolve_namespaces(
+    element: &mut Element,
+    scope: &HashMap<String, String>,
+) -> Result<(), XmlParseError> {
+    let mut scope = scope.clone();
+
+    for attribute in element.attributes.iter() {
+        if attribute.name == "xmlns" {
+            // An empty namespace undeclares the default namespace
+            if attribute.value.is_empty() {
+                scope.remove("");
+            } else {
+                scope.insert(String::new(), attribute.value.clone());
+            }
+        } else if let Some(prefix) = attribute.name.strip_prefix("xmlns:") {
+            if attribute.value.is_empty() || prefix == "xmlns" {
+                return Err(XmlParseError::IllegalName {
+                    name: attribute.name.clone(),
+                });
+            }
+
+            scope.insert(prefix.to_owned(), attribute.value.clone());
+        }
+    }
+
+    element.namespace = namespace(&element.name, &scope, true)?;
+
+    let mut names = Vec::new();
+
+    for attribute in element.attributes.iter_mut() {
+        attribute.namespace = if attribute.name == "xmlns" || attribute.name.starts_with("xmlns:") {
+            Some(XMLNS_NAMESPACE.to_owned())
+        } else {
+            namespace(&attribute.name, &scope, false)?
+        };
+
+        // Attributes with different prefixes can still have the same name
+        let name = (attribute.namespace.clone(), local_name(&attribute.name));
+
+        if names.contains(&name) {
+            return Err(XmlParseError::DuplicateAttribute {
+                attribute: attribute.name.clone(),
+            });
+        }
+
+        names.push(name);
+    }
+
+    for child in &mut element.children {
+        if let Xml::Element(child) = child {
+            resolve_namespaces(child, &scope)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Names without a prefix are in the default namespace, except for attributes
+fn namespace(
+    name: &str,
+    scope: &HashMap<String, String>,
+    default: bool,
+) -> Result<Option<String>, XmlParseError> {
+    match name.split_once(':') {
+        Some((prefix, local_name))
+            if !prefix.is_empty() && !local_name.is_empty() && !local_name.contains(':') =>
+        {
+            scope
+                .get(prefix)
+                .cloned()
+                .map(Some)
+                .ok_or_else(|| XmlParseError::UndeclaredPrefix {
+                    prefix: prefix.to_owned(),
+                })
+        }
+        Some(_) => Err(XmlParseError::IllegalName {
+            name: name.to_owned(),
+        }),
+        None if default => Ok(scope.get("").cloned()),
+        None => Ok(None),
+    }
+}
+
+pub(super) fn local_name(name: &str) -> &str {
+    name.split_once(':')
+        .map_or(name, |(_, local_name)| local_name)
+}
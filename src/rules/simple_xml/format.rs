@@ -0,0 +1,337 @@
+use std::fmt::{Display, Write};
+
+use super::{Doctype, Element, Xml, XmlDeclaration, XmlDocument, REFERENCE};
+
+/// How [`Xml::format`] writes XML text.
+///
+/// The default is compact, like `Display`: no whitespace is added, attributes are written in their order
+/// with double quotes, and empty elements are self-closing (`<a/>`).
+///
+/// Text and attribute values are escaped so that the text parses back into an equal tree. Content that can't be
+/// written as it is changes as little as possible: a CDATA section containing `]]>` is split in two, and a space
+/// is put between the dashes of `--` in a comment and between `?>` in a processing instruction.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct XmlFormat {
+    indent: Option<String>,
+    expand_empty: bool,
+}
+
+impl XmlFormat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Puts every child node on its own line, indented by `indent` for each level. The children of elements
+    /// which contain text are left as they are, as the added whitespace would change the text.
+    pub fn indent(mut self, indent: impl Into<String>) -> Self {
+        self.indent = Some(indent.into());
+        self
+    }
+
+    /// Writes empty elements as `<a></a>` instead of `<a/>`.
+    pub fn expand_empty(mut self) -> Self {
+        self.expand_empty = true;
+        self
+    }
+}
+
+impl Xml {
+    /// Writes the XML text of the node using `format`.
+    pub fn format(&self, format: &XmlFormat) -> String {
+        let mut text = String::new();
+
+        write_xml(&mut text, self, format, 0).expect("Writing to a String can't fail");
+
+        text
+    }
+
+    /// Writes the XML text of the node indented by 2 spaces. The same as `format!("{xml:#}")`.
+    pub fn to_string_pretty(&self) -> String {
+        self.format(&XmlFormat::new().indent("  "))
+    }
+}
+
+impl Element {
+    /// Writes the XML text of the element using `format`.
+    pub fn format(&self, format: &XmlFormat) -> String {
+        let mut text = String::new();
+
+        write_element(&mut text, self, format, 0).expect("Writing to a String can't fail");
+
+        text
+    }
+}
+
+impl XmlDocument {
+    /// Writes the XML text of the document using `format`.
+    ///
+    /// The comments and processing instructions of the prolog are written after the `<!DOCTYPE>`, and the entities
+    /// of the `<!DOCTYPE>` in the order of their names.
+    pub fn format(&self, format: &XmlFormat) -> String {
+        let mut text = String::new();
+
+        write_document(&mut text, self, format).expect("Writing to a String can't fail");
+
+        text
+    }
+}
+
+/// Writes compact XML text, or indented XML text with `{:#}`.
+impl Display for Xml {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_xml(f, self, &display_format(f), 0)
+    }
+}
+
+/// Writes compact XML text, or indented XML text with `{:#}`.
+impl Display for Element {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_element(f, self, &display_format(f), 0)
+    }
+}
+
+/// Writes compact XML text, or indented XML text with `{:#}`.
+impl Display for XmlDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_document(f, self, &display_format(f))
+    }
+}
+
+fn display_format(f: &std::fmt::Formatter<'_>) -> XmlFormat {
+    if f.alternate() {
+        XmlFormat::new().indent("  ")
+    } else {
+        XmlFormat::new()
+    }
+}
+
+fn write_document(
+    f: &mut impl Write,
+    document: &XmlDocument,
+    format: &XmlFormat,
+) -> std::fmt::Result {
+    let mut nodes = 0;
+
+    // Only the root is written without a line break before it
+    let mut separate = |f: &mut dyn Write| {
+        nodes += 1;
+
+        match &format.indent {
+            Some(_) if nodes > 1 => f.write_char('\n'),
+            _ => Ok(()),
+        }
+    };
+
+    if let Some(declaration) = &document.declaration {
+        separate(f)?;
+        write_declaration(f, declaration)?;
+    }
+
+    if let Some(doctype) = &document.doctype {
+        separate(f)?;
+        write_doctype(f, doctype, format)?;
+    }
+
+    for xml in &document.prolog {
+        separate(f)?;
+        write_xml(f, xml, format, 0)?;
+    }
+
+    separate(f)?;
+    write_element(f, &document.root, format, 0)?;
+
+    for xml in &document.epilog {
+        separate(f)?;
+        write_xml(f, xml, format, 0)?;
+    }
+
+    Ok(())
+}
+
+fn write_declaration(f: &mut impl Write, declaration: &XmlDeclaration) -> std::fmt::Result {
+    write!(f, "<?xml version=\"{}\"", declaration.version)?;
+
+    if let Some(encoding) = &declaration.encoding {
+        write!(f, " encoding=\"{encoding}\"")?;
+    }
+
+    if let Some(standalone) = declaration.standalone {
+        let standalone = if standalone { "yes" } else { "no" };
+
+        write!(f, " standalone=\"{standalone}\"")?;
+    }
+
+    f.write_str("?>")
+}
+
+fn write_doctype(f: &mut impl Write, doctype: &Doctype, format: &XmlFormat) -> std::fmt::Result {
+    write!(f, "<!DOCTYPE {}", doctype.name)?;
+
+    match (&doctype.public_id, &doctype.system_id) {
+        (Some(public_id), Some(system_id)) => {
+            write!(f, " PUBLIC \"{public_id}\" {}", literal(system_id))?
+        }
+        (None, Some(system_id)) => write!(f, " SYSTEM {}", literal(system_id))?,
+        _ => {}
+    }
+
+    if !doctype.entities.is_empty() {
+        let mut entities = doctype.entities.iter().collect::<Vec<_>>();
+        entities.sort();
+
+        f.write_str(" [")?;
+
+        for (name, value) in entities {
+            write_newline(f, format, 1)?;
+            write!(f, "<!ENTITY {name} \"")?;
+            write_entity_value(f, value)?;
+            f.write_str("\">")?;
+        }
+
+        write_newline(f, format, 0)?;
+        f.write_char(']')?;
+    }
+
+    f.write_char('>')
+}
+
+// A system literal can't be escaped, so it's quoted with whichever quote it doesn't contain
+fn literal(text: &str) -> String {
+    if text.contains('"') {
+        format!("'{text}'")
+    } else {
+        format!("\"{text}\"")
+    }
+}
+
+fn write_xml(f: &mut impl Write, xml: &Xml, format: &XmlFormat, depth: usize) -> std::fmt::Result {
+    match xml {
+        Xml::Element(element) => write_element(f, element, format, depth),
+        Xml::Text(text) => write_text(f, text),
+        Xml::CData(text) => write!(f, "<![CDATA[{}]]>", text.replace("]]>", "]]]]><![CDATA[>")),
+        Xml::Comment(comment) => {
+            let mut comment = comment.replace("--", "- -").replace("--", "- -");
+
+            if comment.ends_with('-') {
+                comment.push(' ');
+            }
+
+            write!(f, "<!--{comment}-->")
+        }
+        Xml::ProcessingInstruction { target, data } if data.is_empty() => write!(f, "<?{target}?>"),
+        Xml::ProcessingInstruction { target, data } => {
+            write!(f, "<?{target} {}?>", data.replace("?>", "? >"))
+        }
+    }
+}
+
+fn write_element(
+    f: &mut impl Write,
+    element: &Element,
+    format: &XmlFormat,
+    depth: usize,
+) -> std::fmt::Result {
+    write!(f, "<{}", element.name)?;
+
+    for attribute in &element.attributes {
+        write!(f, " {}=\"", attribute.name)?;
+        write_attribute_value(f, &attribute.value)?;
+        f.write_char('"')?;
+    }
+
+    if element.children.is_empty() {
+        return if format.expand_empty {
+            write!(f, "></{}>", element.name)
+        } else {
+            f.write_str("/>")
+        };
+    }
+
+    f.write_char('>')?;
+
+    // Whitespace can only be added between nodes if it isn't part of any text
+    let indent = !element
+        .children
+        .iter()
+        .any(|child| matches!(child, Xml::Text(_) | Xml::CData(_)));
+
+    for child in &element.children {
+        if indent {
+            write_newline(f, format, depth + 1)?;
+        }
+
+        write_xml(f, child, format, depth + 1)?;
+    }
+
+    if indent {
+        write_newline(f, format, depth)?;
+    }
+
+    write!(f, "</{}>", element.name)
+}
+
+fn write_newline(f: &mut impl Write, format: &XmlFormat, depth: usize) -> std::fmt::Result {
+    if let Some(indent) = &format.indent {
+        f.write_char('\n')?;
+
+        for _ in 0..depth {
+            f.write_str(indent)?;
+        }
+    }
+
+    Ok(())
+}
+
+// `>` is escaped so that `]]>` can't appear, and carriage returns so that they aren't normalized to line feeds
+fn write_text(f: &mut impl Write, text: &str) -> std::fmt::Result {
+    for c in text.chars() {
+        match c {
+            '&' => f.write_str("&amp;")?,
+            '<' => f.write_str("&lt;")?,
+            '>' => f.write_str("&gt;")?,
+            '\r' => f.write_str("&#13;")?,
+            c => f.write_char(c)?,
+        }
+    }
+
+    Ok(())
+}
+
+// Whitespace other than spaces is escaped, as it would be normalized to spaces
+fn write_attribute_value(f: &mut impl Write, value: &str) -> std::fmt::Result {
+    for c in value.chars() {
+        match c {
+            '&' => f.write_str("&amp;")?,
+            '<' => f.write_str("&lt;")?,
+            '"' => f.write_str("&quot;")?,
+            '\t' => f.write_str("&#9;")?,
+            '\n' => f.write_str("&#10;")?,
+            '\r' => f.write_str("&#13;")?,
+            c => f.write_char(c)?,
+        }
+    }
+
+    Ok(())
+}
+
+// The references to other entities in the value are written back as they were
+fn write_entity_value(f: &mut impl Write, value: &str) -> std::fmt::Result {
+    for (i, part) in value.split(REFERENCE).enumerate() {
+        if i % 2 == 1 {
+            write!(f, "&{part};")?;
+            continue;
+        }
+
+        for c in part.chars() {
+            match c {
+                '&' => f.write_str("&#38;")?,
+                '%' => f.write_str("&#37;")?,
+                '"' => f.write_str("&#34;")?,
+                '\r' => f.write_str("&#13;")?,
+                c => f.write_char(c)?,
+            }
+        }
+    }
+
+    Ok(())
+}
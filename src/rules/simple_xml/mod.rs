@@ -0,0 +1,642 @@
+use super::*;
+use crate::input::MatcherInput;
+use std::{collections::HashMap, error::Error, fmt::Display};
+
+mod attributes;
+pub use attributes::{Attribute, Attributes};
+
+mod document;
+pub use document::{
+    Doctype, XmlDeclaration, XmlDocument, MAX_ENTITY_REFERENCES, MAX_EXPANDED_LENGTH,
+    XMLNS_NAMESPACE, XML_NAMESPACE,
+};
+
+mod format;
+pub use format::XmlFormat;
+
+// Marks the name of an entity reference on both sides until the entities are known
+// (U+FFFF can't appear in XML text)
+const REFERENCE: char = '\u{FFFF}';
+
+#[derive(Debug)]
+pub enum XmlParseError {
+    IllegalName { name: String },
+    DuplicateAttribute { attribute: String },
+    UndeclaredEntity { name: String },
+    RecursiveEntity { name: String },
+    EntityExpansionLimit { name: String },
+    UndeclaredPrefix { prefix: String },
+}
+
+impl Error for XmlParseError {}
+
+impl Display for XmlParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XmlParseError::IllegalName { name } => {
+                f.write_fmt(format_args!("Illegal name: {name}"))
+            }
+            XmlParseError::DuplicateAttribute { attribute } => {
+                f.write_fmt(format_args!("Duplicate attribute: {attribute}"))
+            }
+            XmlParseError::UndeclaredEntity { name } => {
+                f.write_fmt(format_args!("Undeclared entity: &{name};"))
+            }
+            XmlParseError::RecursiveEntity { name } => {
+                f.write_fmt(format_args!("Entity refers to itself: &{name};"))
+            }
+            XmlParseError::EntityExpansionLimit { name } => f.write_fmt(format_args!(
+                "Expanding entities exceeds the limits at: &{name};"
+            )),
+            XmlParseError::UndeclaredPrefix { prefix } => {
+                f.write_fmt(format_args!("Undeclared namespace prefix: {prefix}"))
+            }
+        }
+    }
+}
+
+/// A node of an XML tree, as parsed by [`XmlRules`].
+///
+/// Nodes can be written as XML text using `Display` (or [`Xml::format`]). The text of a parsed tree
+/// parses back into an equal tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Xml {
+    Element(Element),
+    Text(String),
+    CData(String),
+    Comment(String),
+    ProcessingInstruction { target: String, data: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Element {
+    /// The name as it was written, like `svg:rect`.
+    pub name: String,
+    /// The namespace URI, from the prefix of the name or the default namespace in scope.
+    pub namespace: Option<String>,
+    pub attributes: Attributes,
+    pub children: Vec<Xml>,
+}
+
+impl Element {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            namespace: None,
+            attributes: Attributes::new(),
+            children: Vec::new(),
+        }
+    }
+
+    pub fn prefix(&self) -> Option<&str> {
+        self.name.split_once(':').map(|(prefix, _)| prefix)
+    }
+
+    pub fn local_name(&self) -> &str {
+        document::local_name(&self.name)
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name)
+    }
+
+    /// The child elements.
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|child| match child {
+            Xml::Element(element) => Some(element),
+            _ => None,
+        })
+    }
+
+    /// The text and CDATA sections directly inside the element.
+    pub fn text(&self) -> String {
+        self.children
+            .iter()
+            .filter_map(|child| match child {
+                Xml::Text(text) | Xml::CData(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl From<Element> for Xml {
+    fn from(element: Element) -> Self {
+        Xml::Element(element)
+    }
+}
+
+declare_rules! {
+    // XML 1.0 (https://www.w3.org/TR/xml/) with namespaces (https://www.w3.org/TR/xml-names/).
+    // Of the DTD, only the declarations of internal entities are used, and external entities aren't loaded.
+    pub XmlRules {
+        #[import (rules::Whitespace) as ws]
+
+        #[matcher (name) as name]
+        #[matcher (declaration) as declaration]
+
+        // The root element of a document
+        start /* Xml */ {
+            (document) => |v, _| Xml::Element(v(0).downcast::<XmlDocument>().unwrap().root).into_value();
+        }
+
+        // Entities are expanded and namespaces resolved once the whole document is parsed
+        document /* XmlDocument */ {
+            (prolog element misc) => |v, _| document(v(0), v(1), v(2));
+            // A byte order mark
+            ("\u{feff}" prolog element misc) => |v, _| document(v(1), v(2), v(3));
+        }
+
+        prolog /* (Option<XmlDeclaration>, Option<Doctype>, Vec<Xml>) */ {
+            (misc) => |v, _| (None::<XmlDeclaration>, None::<Doctype>, *v(0).downcast::<Vec<Xml>>().unwrap()).into_value();
+            (xml_decl misc) => |v, _| (
+                Some(*v(0).downcast::<XmlDeclaration>().unwrap()),
+                None::<Doctype>,
+                *v(1).downcast::<Vec<Xml>>().unwrap()
+            ).into_value();
+            (misc doctype misc) => |v, _| {
+                let mut misc = v(0).downcast::<Vec<Xml>>().unwrap();
+                misc.extend(*v(2).downcast::<Vec<Xml>>().unwrap());
+
+                (None::<XmlDeclaration>, Some(*v(1).downcast::<Doctype>().unwrap()), *misc).into_value()
+            };
+            (xml_decl misc doctype misc) => |v, _| {
+                let mut misc = v(1).downcast::<Vec<Xml>>().unwrap();
+                misc.extend(*v(3).downcast::<Vec<Xml>>().unwrap());
+
+                (
+                    Some(*v(0).downcast::<XmlDeclaration>().unwrap()),
+                    Some(*v(2).downcast::<Doctype>().unwrap()),
+                    *misc
+                ).into_value()
+            };
+        }
+
+        // Comments, processing instructions and whitespace
+        misc /* Vec<Xml> */ {
+            () => |_, _| Vec::<Xml>::new().into_value();
+            (misc comment) => |v, _| push(v(0), v(1));
+            (misc pi) => |v, _| push(v(0), v(1));
+            (misc ws_char) => |v, _| v(0);
+        }
+
+        xml_decl /* XmlDeclaration */ {
+            ("<?xml" version_info encoding_decl standalone_decl (ws::ws_ml) "?>") => |v, _| XmlDeclaration {
+                version: *v(1).downcast().unwrap(),
+                encoding: *v(2).downcast().unwrap(),
+                standalone: *v(3).downcast().unwrap(),
+            }.into_value();
+        }
+
+        version_info /* String */ {
+            (space "version" eq "'" (version_num<"'">) "'") => |v, _| v(4);
+            (space "version" eq "\"" (version_num<"\"">) "\"") => |v, _| v(4);
+        }
+
+        version_num<quote> /* String */ {
+            ((until quote) (? |v| {
+                let version = v[0].downcast_ref::<Token>().unwrap();
+
+                version
+                    .as_str()
+                    .strip_prefix("1.")
+                    .is_some_and(|digits| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()))
+            })) => |v, _| v(0).downcast::<Token>().unwrap().to_string().into_value();
+        }
+
+        encoding_decl /* Option<String> */ {
+            () => |_, _| None::<String>.into_value();
+            (space "encoding" eq "'" (enc_name<"'">) "'") => |v, _| Some(*v(4).downcast::<String>().unwrap()).into_value();
+            (space "encoding" eq "\"" (enc_name<"\"">) "\"") => |v, _| Some(*v(4).downcast::<String>().unwrap()).into_value();
+        }
+
+        enc_name<quote> /* String */ {
+            ((until quote) (? |v| {
+                let mut name = v[0].downcast_ref::<Token>().unwrap().as_str().chars();
+
+                name.next().is_some_and(|c| c.is_ascii_alphabetic())
+                    && name.all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+            })) => |v, _| v(0).downcast::<Token>().unwrap().to_string().into_value();
+        }
+
+        standalone_decl /* Option<bool> */ {
+            () => |_, _| None::<bool>.into_value();
+            (space "standalone" eq "'" yes_no "'") => |v, _| Some(*v(4).downcast::<bool>().unwrap()).into_value();
+            (space "standalone" eq "\"" yes_no "\"") => |v, _| Some(*v(4).downcast::<bool>().unwrap()).into_value();
+        }
+
+        yes_no /* bool */ {
+            ("yes") => |_, _| true.into_value();
+            ("no") => |_, _| false.into_value();
+        }
+
+        eq {
+            ((ws::ws_ml) "=" (ws::ws_ml))
+        }
+
+        doctype /* Doctype */ {
+            ("<!DOCTYPE" space name (ws::ws_ml) internal_subset ">") => |v, _| Doctype {
+                name: *v(2).downcast().unwrap(),
+                public_id: None,
+                system_id: None,
+                entities: *v(4).downcast().unwrap(),
+            }.into_value();
+            ("<!DOCTYPE" space name space external_id (ws::ws_ml) internal_subset ">") => |v, _| {
+                let (public_id, system_id) = *v(4).downcast::<(Option<String>, String)>().unwrap();
+
+                Doctype {
+                    name: *v(2).downcast().unwrap(),
+                    public_id,
+                    system_id: Some(system_id),
+                    entities: *v(6).downcast().unwrap(),
+                }.into_value()
+            };
+        }
+
+        external_id /* (Option<String>, String) */ {
+            ("SYSTEM" space system_literal) => |v, _| (None::<String>, *v(2).downcast::<String>().unwrap()).into_value();
+            ("PUBLIC" space pubid_literal space system_literal) => |v, _| (
+                Some(*v(2).downcast::<String>().unwrap()),
+                *v(4).downcast::<String>().unwrap()
+            ).into_value();
+        }
+
+        system_literal /* String */ {
+            ("'" (until "'") (? |v| is_text(&v[1])) "'") => |v, _| v(1).downcast::<Token>().unwrap().to_string().into_value();
+            ("\"" (until "\"") (? |v| is_text(&v[1])) "\"") => |v, _| v(1).downcast::<Token>().unwrap().to_string().into_value();
+        }
+
+        pubid_literal /* String */ {
+            ("'" (until "'") (? |v| is_pubid(&v[1])) "'") => |v, _| v(1).downcast::<Token>().unwrap().to_string().into_value();
+            ("\"" (until "\"") (? |v| is_pubid(&v[1])) "\"") => |v, _| v(1).downcast::<Token>().unwrap().to_string().into_value();
+        }
+
+        internal_subset /* HashMap<String, String> */ {
+            () => |_, _| HashMap::<String, String>::new().into_value();
+            ("[" int_subset "]" (ws::ws_ml)) => |v, _| v(1);
+        }
+
+        // The entities declared in the internal subset. The first declaration of an entity is binding.
+        int_subset /* HashMap<String, String> */ {
+            () => |_, _| HashMap::<String, String>::new().into_value();
+            (int_subset markup_decl) => |v, _| {
+                let mut entities = v(0).downcast::<HashMap<String, String>>().unwrap();
+
+                if let Some((name, value)) = *v(1).downcast::<Option<(String, String)>>().unwrap() {
+                    entities.entry(name).or_insert(value);
+                }
+
+                entities
+            };
+        }
+
+        // Declarations other than those of internal entities are skipped
+        #[policy(ordered)]
+        markup_decl /* Option<(String, String)> */ {
+            (entity_decl) => |v, _| Some(*v(0).downcast::<(String, String)>().unwrap()).into_value();
+            (declaration) => |_, _| None::<(String, String)>.into_value();
+            (comment) => |_, _| None::<(String, String)>.into_value();
+            (pi) => |_, _| None::<(String, String)>.into_value();
+            (ws_char) => |_, _| None::<(String, String)>.into_value();
+            // A parameter entity reference
+            ("%" name ";") => |_, _| None::<(String, String)>.into_value();
+        }
+
+        entity_decl /* (String, String) */ {
+            ("<!ENTITY" space name space entity_value (ws::ws_ml) ">") => |v, _| {
+                (*v(2).downcast::<String>().unwrap(), *v(4).downcast::<String>().unwrap()).into_value()
+            };
+        }
+
+        entity_value /* String */ {
+            ("\"" (entity_chars<"\"">) "\"") => |v, _| v(1);
+            ("'" (entity_chars<"'">) "'") => |v, _| v(1);
+        }
+
+        entity_chars<quote> /* String */ {
+            () => |_, _| String::new().into_value();
+            (entity_chars reference) => |v, _| append(v(0), v(1));
+            (entity_chars (entity_run<quote>)) => |v, _| append(v(0), v(1));
+        }
+
+        // At least one character that isn't the quote or a reference
+        entity_run<quote> /* String */ {
+            ((until quote "%" "&") (? |v| is_run(&v[0]))) => |v, _| normalize(v(0));
+        }
+
+        element /* Element */ {
+            ("<" name attributes (ws::ws_ml) "/>") => |v, _| Element {
+                name: *v(1).downcast().unwrap(),
+                namespace: None,
+                attributes: *v(2).downcast().unwrap(),
+                children: Vec::new(),
+            }.into_value();
+            ("<" name attributes (ws::ws_ml) ">"
+             content
+             "</" name (? |v| matching_tags(v, 7)) (ws::ws_ml) ">") => |v, _| Element {
+                name: *v(1).downcast().unwrap(),
+                namespace: None,
+                attributes: *v(2).downcast().unwrap(),
+                children: *v(5).downcast().unwrap(),
+            }.into_value();
+        }
+
+        // Attributes are separated by whitespace, and can't be repeated
+        attributes /* Attributes */ {
+            () => |_, _| Attributes::new().into_value();
+            (attributes space attribute) => |v, _| {
+                let mut attributes = v(0).downcast::<Attributes>().unwrap();
+                let (name, value) = *v(2).downcast::<(String, String)>().unwrap();
+
+                if attributes.contains(&name) {
+                    return XmlParseError::DuplicateAttribute { attribute: name }.into_error();
+                }
+
+                attributes.insert(name, value);
+
+                attributes
+            };
+        }
+
+        attribute /* (String, String) */ {
+            (name eq att_value) => |v, _| (*v(0).downcast::<String>().unwrap(), *v(2).downcast::<String>().unwrap()).into_value();
+        }
+
+        att_value /* String */ {
+            ("\"" (att_chars<"\"">) "\"") => |v, _| v(1);
+            ("'" (att_chars<"'">) "'") => |v, _| v(1);
+        }
+
+        att_chars<quote> /* String */ {
+            () => |_, _| String::new().into_value();
+            (att_chars reference) => |v, _| append(v(0), v(1));
+            (att_chars (att_run<quote>)) => |v, _| append(v(0), v(1));
+        }
+
+        // At least one character that isn't the quote or a reference. Whitespace is normalized to spaces,
+        // but not whitespace written as a character reference.
+        att_run<quote> /* String */ {
+            ((until quote "<" "&") (? |v| is_run(&v[0]))) => |v, _| {
+                normalize(v(0))
+                    .downcast::<String>()
+                    .unwrap()
+                    .replace(['\n', '\t'], " ")
+                    .into_value()
+            };
+        }
+
+        // The text, elements and other nodes of an element. Adjacent text and references are one text node.
+        content /* Vec<Xml> */ {
+            () => |_, _| Vec::<Xml>::new().into_value();
+            (content element) => |v, _| {
+                let mut content = v(0).downcast::<Vec<Xml>>().unwrap();
+                content.push(Xml::Element(*v(1).downcast::<Element>().unwrap()));
+
+                content
+            };
+            (content char_data) => |v, _| push_text(v(0), v(1));
+            (content reference) => |v, _| push_text(v(0), v(1));
+            (content cdata) => |v, _| push(v(0), v(1));
+            (content comment) => |v, _| push(v(0), v(1));
+            (content pi) => |v, _| push(v(0), v(1));
+        }
+
+        // At least one character of text
+        char_data /* String */ {
+            ((until "<" "&" "]]>") (? |v| is_run(&v[0]))) => |v, _| normalize(v(0));
+        }
+
+        // A reference to a character or an entity
+        reference /* String */ {
+            ("&#x" (until ";") (? |v| hex_reference(&v[1]).is_some()) ";")
+                => |v, _| hex_reference(&v(1)).unwrap().to_string().into_value();
+            ("&#" (until ";") (? |v| decimal_reference(&v[1]).is_some()) ";")
+                => |v, _| decimal_reference(&v(1)).unwrap().to_string().into_value();
+            ("&" name ";") => |v, _| {
+                let name = *v(1).downcast::<String>().unwrap();
+
+                match predefined_entity(&name) {
+                    Some(c) => c.to_string().into_value(),
+                    None => format!("{REFERENCE}{name}{REFERENCE}").into_value(),
+                }
+            };
+        }
+
+        cdata /* Xml */ {
+            ("<![CDATA[" (until "]]>") (? |v| is_text(&v[1])) "]]>") => |v, _| {
+                Xml::CData(*normalize(v(1)).downcast::<String>().unwrap()).into_value()
+            };
+        }
+
+        // Comments can't contain "--"
+        comment /* Xml */ {
+            ("<!--" (until "--") (? |v| is_text(&v[1])) "-->") => |v, _| {
+                Xml::Comment(*normalize(v(1)).downcast::<String>().unwrap()).into_value()
+            };
+        }
+
+        // The target `xml` is reserved for the XML declaration
+        pi /* Xml */ {
+            ("<?" name (? |v| !v[1].downcast_ref::<String>().unwrap().eq_ignore_ascii_case("xml")) "?>")
+                => |v, _| Xml::ProcessingInstruction {
+                    target: *v(1).downcast().unwrap(),
+                    data: String::new(),
+                }.into_value();
+            ("<?" name (? |v| !v[1].downcast_ref::<String>().unwrap().eq_ignore_ascii_case("xml"))
+             space (until "?>") (? |v| is_text(&v[4])) "?>")
+                => |v, _| Xml::ProcessingInstruction {
+                    target: *v(1).downcast().unwrap(),
+                    data: normalize(v(4)).downcast::<String>().unwrap().trim_start().to_owned(),
+                }.into_value();
+        }
+
+        // At least one character of whitespace
+        space {
+            (ws_char (ws::ws_ml))
+        }
+
+        ws_char {
+            (" ")
+            ("\t")
+            ("\r")
+            ("\n")
+        }
+    }
+}
+
+// Matches a name, which can contain colons (namespaces are resolved after parsing)
+fn name(input: &mut dyn MatcherInput) -> Option<ParseValue> {
+    let mut name = String::new();
+
+    while input
+        .peek()
+        .is_some_and(|c| is_name_start_char(c) || (!name.is_empty() && is_name_char(c)))
+    {
+        name.push(input.next().unwrap());
+    }
+
+    (!name.is_empty()).then(|| name.into_value())
+}
+
+// Skips a markup declaration like `<!ELEMENT ...>` or `<!ATTLIST ...>`, with `>` allowed in quotes
+fn declaration(input: &mut dyn MatcherInput) -> Option<ParseValue> {
+    if input.next() != Some('<') || input.next() != Some('!') {
+        return None;
+    }
+
+    if !input.peek().is_some_and(|c| c.is_ascii_uppercase()) {
+        return None;
+    }
+
+    let mut quote = None;
+
+    loop {
+        match (input.next()?, quote) {
+            ('>', None) => return Some(().into_value()),
+            (c @ ('"' | '\''), None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            _ => {}
+        }
+    }
+}
+
+fn is_name_start_char(c: char) -> bool {
+    matches!(c,
+        ':' | 'A'..='Z' | '_' | 'a'..='z'
+        | '\u{C0}'..='\u{D6}' | '\u{D8}'..='\u{F6}' | '\u{F8}'..='\u{2FF}'
+        | '\u{370}'..='\u{37D}' | '\u{37F}'..='\u{1FFF}' | '\u{200C}'..='\u{200D}'
+        | '\u{2070}'..='\u{218F}' | '\u{2C00}'..='\u{2FEF}' | '\u{3001}'..='\u{D7FF}'
+        | '\u{F900}'..='\u{FDCF}' | '\u{FDF0}'..='\u{FFFD}' | '\u{10000}'..='\u{EFFFF}'
+    )
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_start_char(c)
+        || matches!(c,
+            '-' | '.' | '0'..='9' | '\u{B7}' | '\u{300}'..='\u{36F}' | '\u{203F}'..='\u{2040}'
+        )
+}
+
+// Whether `c` may appear in a document
+fn is_char(c: char) -> bool {
+    matches!(c,
+        '\t' | '\n' | '\r' | '\u{20}'..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}' | '\u{10000}'..='\u{10FFFF}'
+    )
+}
+
+fn is_text(token: &ParseValue) -> bool {
+    token
+        .downcast_ref::<Token>()
+        .unwrap()
+        .as_str()
+        .chars()
+        .all(is_char)
+}
+
+// Text that isn't empty
+fn is_run(token: &ParseValue) -> bool {
+    !token.downcast_ref::<Token>().unwrap().is_empty() && is_text(token)
+}
+
+fn is_pubid(token: &ParseValue) -> bool {
+    token
+        .downcast_ref::<Token>()
+        .unwrap()
+        .as_str()
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || " \r\n-'()+,./:=?;!*#@$_%".contains(c))
+}
+
+fn hex_reference(token: &ParseValue) -> Option<char> {
+    character(token, 16)
+}
+
+fn decimal_reference(token: &ParseValue) -> Option<char> {
+    character(token, 10)
+}
+
+fn character(token: &ParseValue, radix: u32) -> Option<char> {
+    let digits = token.downcast_ref::<Token>().unwrap().as_str();
+
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+
+    u32::from_str_radix(digits, radix)
+        .ok()
+        .and_then(char::from_u32)
+        .filter(|&c| is_char(c))
+}
+
+fn predefined_entity(name: &str) -> Option<char> {
+    match name {
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "amp" => Some('&'),
+        "apos" => Some('\''),
+        "quot" => Some('"'),
+        _ => None,
+    }
+}
+
+// Line breaks are normalized to "\n"
+fn normalize(token: ParseValue) -> ParseValue {
+    token
+        .downcast::<Token>()
+        .unwrap()
+        .as_str()
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .into_value()
+}
+
+fn append(text: ParseValue, more: ParseValue) -> ParseValue {
+    let mut text = text.downcast::<String>().unwrap();
+    text.push_str(&more.downcast::<String>().unwrap());
+
+    text
+}
+
+fn push(content: ParseValue, xml: ParseValue) -> ParseValue {
+    let mut content = content.downcast::<Vec<Xml>>().unwrap();
+    content.push(*xml.downcast::<Xml>().unwrap());
+
+    content
+}
+
+// Text directly after other text is added to it
+fn push_text(content: ParseValue, text: ParseValue) -> ParseValue {
+    let mut content = content.downcast::<Vec<Xml>>().unwrap();
+    let text = *text.downcast::<String>().unwrap();
+
+    match content.last_mut() {
+        Some(Xml::Text(last)) => last.push_str(&text),
+        _ => content.push(Xml::Text(text)),
+    }
+
+    content
+}
+
+fn document(prolog: ParseValue, root: ParseValue, epilog: ParseValue) -> ParseValue {
+    let (declaration, doctype, prolog) = *prolog
+        .downcast::<(Option<XmlDeclaration>, Option<Doctype>, Vec<Xml>)>()
+        .unwrap();
+
+    let mut document = XmlDocument {
+        declaration,
+        doctype,
+        prolog,
+        root: *root.downcast().unwrap(),
+        epilog: *epilog.downcast().unwrap(),
+    };
+
+    match document.resolve() {
+        Ok(()) => document.into_value(),
+        Err(error) => error.into_error(),
+    }
+}
+
+// Whether the closing tag name at `end` matches the opening tag name
+fn matching_tags(v: &[ParseValue], end: usize) -> bool {
+    v[1].downcast_ref::<String>() == v[end].downcast_ref::<String>()
+}
@@ -1,4 +1,22 @@
 use super::*;
+use std::{error::Error, fmt::Display};
+
+#[derive(Debug)]
+pub enum StringParseError {
+    IllegalEscape { escape: String },
+}
+
+impl Error for StringParseError {}
+
+impl Display for StringParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StringParseError::IllegalEscape { escape } => {
+                f.write_fmt(format_args!("Illegal escape code: {escape}"))
+            }
+        }
+    }
+}
 
 declare_rules! {
     // Name ends in Rules because otherwise std::string::String is shadowed
@@ -22,8 +40,12 @@ declare_rules! {
 
         string_char {
             (escape)
-            ((! "\""))
-                => |v, _| v(0).downcast::<Token>().unwrap().to_string().into_value();
+            ((! "\"") (until "\"" "\\"))
+                => |v, _| format!(
+                    "{}{}",
+                    v(0).downcast::<Token>().unwrap(),
+                    v(1).downcast::<Token>().unwrap()
+                ).into_value();
         }
 
         escape {
@@ -43,19 +65,17 @@ declare_rules! {
                 if char <= 0x7F {
                     [(char as char)].into_iter().collect::<String>().into_value()
                 } else {
-                    panic!("Illegal escape code: \\x{digit1}{digit2}");
+                    StringParseError::IllegalEscape { escape: format!("\\x{digit1}{digit2}") }.into_error()
                 }
 
             };
             ("\\u{" (hex::raw_hex) "}") => |v, _| {
                 let hex = v(1).downcast::<String>().unwrap();
-                if hex.chars().count() > 6 {
-                    panic!("Illegal escape code: \\u{{{hex}}}");
-                } else {
-                    let unicode_char = u32::from_str_radix(&hex, 16).unwrap();
-                    let unicode_char = char::from_u32(unicode_char).unwrap();
 
-                    [unicode_char].into_iter().collect::<String>().into_value()
+                // Also rejects surrogates and values above 0x10FFFF
+                match u32::from_str_radix(&hex, 16).ok().filter(|_| hex.chars().count() <= 6).and_then(char::from_u32) {
+                    Some(unicode_char) => [unicode_char].into_iter().collect::<String>().into_value(),
+                    None => StringParseError::IllegalEscape { escape: format!("\\u{{{hex}}}") }.into_error(),
                 }
             };
         }
@@ -0,0 +1,220 @@
+use std::fmt::Display;
+
+use crate::input::MatcherInput;
+use crate::result::{IntoParseValue, ParseValue};
+
+/// A TOML date-time: an offset date-time, a local date-time, a local date or a local time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Datetime {
+    pub date: Option<Date>,
+    pub time: Option<Time>,
+    /// Only date-times with both a date and a time can have an offset.
+    pub offset: Option<Offset>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+/// A time of day. Fractional seconds beyond nanoseconds are truncated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub nanosecond: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Offset {
+    /// UTC, written as `Z`.
+    Z,
+    /// The difference to UTC in minutes.
+    Custom { minutes: i16 },
+}
+
+impl Datetime {
+    // Whether the fields are in range, like the day in its month
+    pub(super) fn is_valid(&self) -> bool {
+        let date = match self.date {
+            Some(Date { year, month, day }) => {
+                let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+                let days = match month {
+                    2 if leap => 29,
+                    2 => 28,
+                    4 | 6 | 9 | 11 => 30,
+                    _ => 31,
+                };
+
+                (1..=12).contains(&month) && (1..=days).contains(&day)
+            }
+            None => true,
+        };
+
+        // A second of 60 is a leap second
+        let time = match &self.time {
+            Some(time) => time.hour < 24 && time.minute < 60 && time.second <= 60,
+            None => true,
+        };
+
+        let offset = match self.offset {
+            Some(Offset::Custom { minutes }) => minutes.abs() < 24 * 60,
+            _ => true,
+        };
+
+        date && time && offset
+    }
+}
+
+/// Writes the date-time as in RFC 3339, like `1979-05-27T07:32:00Z`.
+impl Display for Datetime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(Date { year, month, day }) = self.date {
+            write!(f, "{year:04}-{month:02}-{day:02}")?;
+        }
+
+        if let Some(time) = self.time {
+            if self.date.is_some() {
+                f.write_str("T")?;
+            }
+
+            write!(f, "{:02}:{:02}:{:02}", time.hour, time.minute, time.second)?;
+
+            if time.nanosecond > 0 {
+                let fraction = format!("{:09}", time.nanosecond);
+
+                write!(f, ".{}", fraction.trim_end_matches('0'))?;
+            }
+        }
+
+        match self.offset {
+            Some(Offset::Z) => f.write_str("Z"),
+            Some(Offset::Custom { minutes }) => {
+                let sign = if minutes < 0 { '-' } else { '+' };
+                let minutes = minutes.abs();
+
+                write!(f, "{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+// Matches a date-time, which isn't checked to be valid yet
+pub(super) fn datetime(input: &mut dyn MatcherInput) -> Option<ParseValue> {
+    let first = digits(input);
+
+    let (date, time) = match (first.len(), input.peek()) {
+        (4, Some('-')) => {
+            input.next();
+
+            let date = date(first.parse().ok()?, input)?;
+
+            // The date and time can also be separated by a space
+            let separated = matches!(input.peek(), Some('T' | 't'))
+                || ('0'..='9').any(|digit| input.starts_with(&format!(" {digit}")));
+
+            if separated {
+                input.next();
+
+                (Some(date), Some(time(digits(input), input)?))
+            } else {
+                (Some(date), None)
+            }
+        }
+        (2, Some(':')) => (None, Some(time(first, input)?)),
+        _ => return None,
+    };
+
+    let offset = match (date, time, input.peek()) {
+        (Some(_), Some(_), Some('Z' | 'z')) => {
+            input.next();
+
+            Some(Offset::Z)
+        }
+        (Some(_), Some(_), Some(sign @ ('+' | '-'))) => {
+            input.next();
+
+            let hours = two_digits(input)?;
+            expect(input, ':')?;
+            let minutes = hours as i16 * 60 + two_digits(input)? as i16;
+
+            Some(Offset::Custom {
+                minutes: if sign == '-' { -minutes } else { minutes },
+            })
+        }
+        _ => None,
+    };
+
+    Some(Datetime { date, time, offset }.into_value())
+}
+
+fn date(year: u16, input: &mut dyn MatcherInput) -> Option<Date> {
+    let month = two_digits(input)?;
+    expect(input, '-')?;
+    let day = two_digits(input)?;
+
+    Some(Date { year, month, day })
+}
+
+// The hour has already been read
+fn time(hour: String, input: &mut dyn MatcherInput) -> Option<Time> {
+    if hour.len() != 2 {
+        return None;
+    }
+
+    expect(input, ':')?;
+    let minute = two_digits(input)?;
+    expect(input, ':')?;
+    let second = two_digits(input)?;
+
+    let mut nanosecond = 0;
+
+    if input.peek() == Some('.') {
+        input.next();
+
+        let fraction = digits(input);
+
+        if fraction.is_empty() {
+            return None;
+        }
+
+        nanosecond = format!("{:0<9}", &fraction[..fraction.len().min(9)])
+            .parse()
+            .ok()?;
+    }
+
+    Some(Time {
+        hour: hour.parse().ok()?,
+        minute,
+        second,
+        nanosecond,
+    })
+}
+
+fn digits(input: &mut dyn MatcherInput) -> String {
+    let mut digits = String::new();
+
+    while input.peek().is_some_and(|c| c.is_ascii_digit()) {
+        digits.push(input.next().unwrap());
+    }
+
+    digits
+}
+
+fn two_digits(input: &mut dyn MatcherInput) -> Option<u8> {
+    let mut number = 0;
+
+    for _ in 0..2 {
+        number = number * 10 + input.next()?.to_digit(10)? as u8;
+    }
+
+    Some(number)
+}
+
+fn expect(input: &mut dyn MatcherInput, c: char) -> Option<()> {
+    (input.next()? == c).then_some(())
+}
@@ -0,0 +1,216 @@
+use super::{Expression, Key, KeyValue, OrderedMap, Table, Toml, TomlParseError, Value};
+
+// A table while the document is built, which remembers how it was defined
+struct TableNode {
+    entries: OrderedMap<Node>,
+    kind: Kind,
+}
+
+enum Node {
+    // Values which can't be added to: scalars, static arrays and inline tables
+    Value(Toml),
+    Table(TableNode),
+    ArrayOfTables(Vec<TableNode>),
+}
+
+#[derive(PartialEq)]
+enum Kind {
+    // A parent of a `[table]`, which can still be defined by its own header
+    Implicit,
+    // Defined by a `[table]` or `[[table]]` header, or the root
+    Header,
+    // Defined by dotted keys, which can add to it
+    Dotted,
+}
+
+impl TableNode {
+    fn new(kind: Kind) -> Self {
+        Self {
+            entries: OrderedMap::new(),
+            kind,
+        }
+    }
+
+    // The node of a key, which is added first if it's missing
+    fn entry(&mut self, name: &str, node: impl FnOnce() -> Node) -> &mut Node {
+        self.entries.get_or_insert_with(name, node)
+    }
+
+    fn into_table(self) -> Table {
+        self.entries
+            .into_iter()
+            .map(|(key, node)| {
+                let value = match node {
+                    Node::Value(value) => value,
+                    Node::Table(table) => Toml::Table(table.into_table()),
+                    Node::ArrayOfTables(tables) => Toml::Array(
+                        tables
+                            .into_iter()
+                            .map(|table| Toml::Table(table.into_table()))
+                            .collect(),
+                    ),
+                };
+
+                (key, value)
+            })
+            .collect()
+    }
+}
+
+// Builds the root table of a document, checking that no key or table is defined twice
+pub(super) fn build(expressions: Vec<Expression>) -> Result<Table, TomlParseError> {
+    let mut root = TableNode::new(Kind::Header);
+    // The names of the table which key/value pairs are added to
+    let mut current = Vec::new();
+
+    for expression in expressions {
+        match expression {
+            Expression::KeyValue(key_value) => insert(table_at(&mut root, &current), key_value)?,
+            Expression::Table(key) => {
+                let (last, parents) = key.split_last().unwrap();
+                let table = header_parent(&mut root, parents)?;
+
+                // A table which was only a parent of other headers so far is now defined
+                match table.entry(&last.0, || Node::Table(TableNode::new(Kind::Implicit))) {
+                    Node::Table(table) if table.kind == Kind::Implicit => table.kind = Kind::Header,
+                    _ => return Err(duplicate_key(last)),
+                }
+
+                current = key.into_iter().map(|(name, _)| name).collect();
+            }
+            Expression::ArrayTable(key) => {
+                let (last, parents) = key.split_last().unwrap();
+                let table = header_parent(&mut root, parents)?;
+
+                match table.entry(&last.0, || Node::ArrayOfTables(Vec::new())) {
+                    Node::ArrayOfTables(tables) => tables.push(TableNode::new(Kind::Header)),
+                    _ => return Err(duplicate_key(last)),
+                }
+
+                current = key.into_iter().map(|(name, _)| name).collect();
+            }
+        }
+    }
+
+    Ok(root.into_table())
+}
+
+// The table of a header's names, which all exist. Arrays of tables lead to their last table.
+fn table_at<'a>(mut table: &'a mut TableNode, names: &[String]) -> &'a mut TableNode {
+    for name in names {
+        table = match table.entries.get_mut(name) {
+            Some(Node::Table(table)) => table,
+            Some(Node::ArrayOfTables(tables)) => tables.last_mut().unwrap(),
+            _ => unreachable!("The tables of a header should have been added"),
+        };
+    }
+
+    table
+}
+
+// Walks to the table a header is defined in, adding the missing tables on the way
+fn header_parent<'a>(
+    mut table: &'a mut TableNode,
+    keys: &[Key],
+) -> Result<&'a mut TableNode, TomlParseError> {
+    for key in keys {
+        table = match table.entry(&key.0, || Node::Table(TableNode::new(Kind::Implicit))) {
+            Node::Table(table) => table,
+            Node::ArrayOfTables(tables) => tables.last_mut().unwrap(),
+            Node::Value(value) => return Err(not_a_table(key, value)),
+        };
+    }
+
+    Ok(table)
+}
+
+// Adds a key/value pair to a table. The tables of a dotted key are defined by it, so they can't be
+// tables which were defined otherwise.
+fn insert(mut table: &mut TableNode, key_value: KeyValue) -> Result<(), TomlParseError> {
+    let (last, parents) = key_value.key.split_last().unwrap();
+
+    for key in parents {
+        table = match table.entry(&key.0, || Node::Table(TableNode::new(Kind::Dotted))) {
+            Node::Table(table) if table.kind != Kind::Header => {
+                table.kind = Kind::Dotted;
+
+                table
+            }
+            Node::Value(value) => return Err(not_a_table(key, value)),
+            _ => return Err(duplicate_key(key)),
+        };
+    }
+
+    if table.entries.contains_key(&last.0) {
+        return Err(duplicate_key(last));
+    }
+
+    let value = into_toml(key_value.value)?;
+
+    table.entries.insert(last.0.clone(), Node::Value(value));
+
+    Ok(())
+}
+
+fn into_toml(value: Value) -> Result<Toml, TomlParseError> {
+    match value {
+        Value::Toml(toml) => Ok(toml),
+        Value::Integer {
+            digits,
+            radix,
+            span,
+        } => i64::from_str_radix(&digits, radix)
+            .map(Toml::Integer)
+            .map_err(|_| {
+                let prefix = match radix {
+                    16 => "0x",
+                    8 => "0o",
+                    2 => "0b",
+                    _ => "",
+                };
+
+                TomlParseError::IntegerOutOfRange {
+                    integer: format!("{prefix}{digits}"),
+                    span,
+                }
+            }),
+        Value::Datetime { datetime, .. } if datetime.is_valid() => Ok(Toml::Datetime(datetime)),
+        Value::Datetime { datetime, span } => Err(TomlParseError::InvalidDatetime {
+            datetime: datetime.to_string(),
+            span,
+        }),
+        Value::Array(values) => values
+            .into_iter()
+            .map(into_toml)
+            .collect::<Result<_, _>>()
+            .map(Toml::Array),
+        // Inline tables are built on their own, and can't be added to later
+        Value::InlineTable(key_values) => {
+            let mut table = TableNode::new(Kind::Header);
+
+            for key_value in key_values {
+                insert(&mut table, key_value)?;
+            }
+
+            Ok(Toml::Table(table.into_table()))
+        }
+    }
+}
+
+fn duplicate_key((key, span): &Key) -> TomlParseError {
+    TomlParseError::DuplicateKey {
+        key: key.clone(),
+        span: *span,
+    }
+}
+
+// Inline tables can't be added to, like other tables which were already defined
+fn not_a_table(key: &Key, value: &Toml) -> TomlParseError {
+    match value {
+        Toml::Table(_) => duplicate_key(key),
+        _ => TomlParseError::NotATable {
+            key: key.0.clone(),
+            span: key.1,
+        },
+    }
+}
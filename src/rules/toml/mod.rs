@@ -0,0 +1,544 @@
+use super::*;
+use crate::input::MatcherInput;
+use crate::result::Span;
+use std::{error::Error, fmt::Display};
+
+mod datetime;
+pub use datetime::{Date, Datetime, Offset, Time};
+
+mod document;
+
+/// The keys and values of a TOML table, in the order in which they were written.
+pub type Table = OrderedMap<Toml>;
+
+/// A TOML value, as parsed by [`TomlRules`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Toml {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Datetime(Datetime),
+    Array(Vec<Toml>),
+    /// The keys are kept in the order in which they were written.
+    Table(Table),
+}
+
+impl Toml {
+    /// The value of a key, if this is a table.
+    pub fn get(&self, key: &str) -> Option<&Toml> {
+        self.as_table()?.get(key)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Toml::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Toml::Integer(integer) => Some(*integer),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Toml::Float(float) => Some(*float),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Toml::Boolean(boolean) => Some(*boolean),
+            _ => None,
+        }
+    }
+
+    pub fn as_datetime(&self) -> Option<&Datetime> {
+        match self {
+            Toml::Datetime(datetime) => Some(datetime),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Toml>> {
+        match self {
+            Toml::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn as_table(&self) -> Option<&Table> {
+        match self {
+            Toml::Table(table) => Some(table),
+            _ => None,
+        }
+    }
+}
+
+/// The errors of documents which match the grammar but aren't valid TOML, with the span of the
+/// key or value at fault.
+#[derive(Debug)]
+pub enum TomlParseError {
+    DuplicateKey { key: String, span: Span },
+    NotATable { key: String, span: Span },
+    IntegerOutOfRange { integer: String, span: Span },
+    InvalidDatetime { datetime: String, span: Span },
+}
+
+impl TomlParseError {
+    pub fn span(&self) -> Span {
+        match self {
+            TomlParseError::DuplicateKey { span, .. }
+            | TomlParseError::NotATable { span, .. }
+            | TomlParseError::IntegerOutOfRange { span, .. }
+            | TomlParseError::InvalidDatetime { span, .. } => *span,
+        }
+    }
+}
+
+impl Error for TomlParseError {}
+
+impl Display for TomlParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TomlParseError::DuplicateKey { key, .. } => {
+                f.write_fmt(format_args!("Duplicate key: {key}"))?
+            }
+            TomlParseError::NotATable { key, .. } => {
+                f.write_fmt(format_args!("Key is not a table: {key}"))?
+            }
+            TomlParseError::IntegerOutOfRange { integer, .. } => {
+                f.write_fmt(format_args!("Integer out of range: {integer}"))?
+            }
+            TomlParseError::InvalidDatetime { datetime, .. } => {
+                f.write_fmt(format_args!("Invalid date-time: {datetime}"))?
+            }
+        }
+
+        let start = self.span().start;
+
+        f.write_fmt(format_args!(
+            " at line {}, column {}",
+            start.line, start.column
+        ))
+    }
+}
+
+// A line of the document, before the tables are built
+enum Expression {
+    KeyValue(KeyValue),
+    Table(Vec<Key>),
+    ArrayTable(Vec<Key>),
+}
+
+// A part of a dotted key, with where it was written
+type Key = (String, Span);
+
+struct KeyValue {
+    key: Vec<Key>,
+    value: Value,
+}
+
+// A value whose integers and date-times are checked when the document is built, so their errors
+// are the ones reported
+enum Value {
+    Toml(Toml),
+    Integer {
+        digits: String,
+        radix: u32,
+        span: Span,
+    },
+    Datetime {
+        datetime: Datetime,
+        span: Span,
+    },
+    Array(Vec<Value>),
+    InlineTable(Vec<KeyValue>),
+}
+
+declare_rules! {
+    // TOML 1.0 (https://toml.io/en/v1.0.0). The `start` rule parses a document into a `Toml::Table`.
+    pub TomlRules {
+        #[import (Whitespace) as ws]
+        #[import (Hex) as hex]
+
+        #[matcher (bare_key) as bare_key]
+        #[matcher (integer) as integer]
+        #[matcher (float) as float]
+        #[matcher (datetime::datetime) as datetime]
+
+        start /* Toml */ {
+            (expressions) => |v, _| match document::build(*v(0).downcast().unwrap()) {
+                Ok(table) => Toml::Table(table).into_value(),
+                Err(error) => error.into_error(),
+            };
+        }
+
+        expressions /* Vec<Expression> */ {
+            (expression) => |v, _| {
+                (*v(0).downcast::<Option<Expression>>().unwrap()).into_iter().collect::<Vec<_>>().into_value()
+            };
+            (expressions newline expression) => |v, _| {
+                let mut expressions = v(0).downcast::<Vec<Expression>>().unwrap();
+
+                expressions.extend(*v(2).downcast::<Option<Expression>>().unwrap());
+
+                expressions
+            };
+        }
+
+        // Blank lines and lines with only a comment are `None`
+        #[policy(ordered)]
+        expression /* Option<Expression> */ {
+            ((ws::ws) keyval (ws::ws) comment) => |v, _| {
+                Some(Expression::KeyValue(*v(1).downcast().unwrap())).into_value()
+            };
+            ((ws::ws) "[[" (ws::ws) key (ws::ws) "]]" (ws::ws) comment) => |v, _| {
+                Some(Expression::ArrayTable(*v(3).downcast().unwrap())).into_value()
+            };
+            ((ws::ws) "[" (ws::ws) key (ws::ws) "]" (ws::ws) comment) => |v, _| {
+                Some(Expression::Table(*v(3).downcast().unwrap())).into_value()
+            };
+            ((ws::ws) comment) => |_, _| None::<Expression>.into_value();
+        }
+
+        comment {
+            ()
+            (comment_text)
+        }
+
+        // Control characters other than tabs aren't allowed in comments
+        comment_text {
+            ("#" (until "\n" "\r\n") (? |v| is_allowed(&v[1])))
+        }
+
+        newline {
+            ("\n")
+            ("\r\n")
+        }
+
+        keyval /* KeyValue */ {
+            (key (ws::ws) "=" (ws::ws) val) => |v, _| KeyValue {
+                key: *v(0).downcast().unwrap(),
+                value: *v(4).downcast().unwrap(),
+            }.into_value();
+        }
+
+        // The parts of a dotted key
+        key /* Vec<Key> */ {
+            (simple_key) => |v, _| vec![*v(0).downcast::<Key>().unwrap()].into_value();
+            (key (ws::ws) "." (ws::ws) simple_key) => |v, _| {
+                let mut key = v(0).downcast::<Vec<Key>>().unwrap();
+
+                key.push(*v(4).downcast().unwrap());
+
+                key
+            };
+        }
+
+        simple_key /* Key */ {
+            (bare_key) => |v, span| (*v(0).downcast::<String>().unwrap(), span).into_value();
+            (basic_string) => |v, span| (*v(0).downcast::<String>().unwrap(), span).into_value();
+            (literal_string) => |v, span| (*v(0).downcast::<String>().unwrap(), span).into_value();
+        }
+
+        // Date-times and floats start like integers, so they are tried first
+        #[policy(ordered)]
+        val /* Value */ {
+            (string) => |v, _| Value::Toml(Toml::String(*v(0).downcast().unwrap())).into_value();
+            ("true") => |_, _| Value::Toml(Toml::Boolean(true)).into_value();
+            ("false") => |_, _| Value::Toml(Toml::Boolean(false)).into_value();
+            (array) => |v, _| Value::Array(*v(0).downcast().unwrap()).into_value();
+            (inline_table) => |v, _| Value::InlineTable(*v(0).downcast().unwrap()).into_value();
+            (datetime) => |v, span| Value::Datetime { datetime: *v(0).downcast().unwrap(), span }.into_value();
+            (float) => |v, _| {
+                Value::Toml(Toml::Float(v(0).downcast::<String>().unwrap().parse().unwrap())).into_value()
+            };
+            (integer) => |v, span| {
+                let (digits, radix) = *v(0).downcast::<(String, u32)>().unwrap();
+
+                Value::Integer { digits, radix, span }.into_value()
+            };
+        }
+
+        #[policy(ordered)]
+        string /* String */ {
+            (ml_basic_string)
+            (basic_string)
+            (ml_literal_string)
+            (literal_string)
+        }
+
+        basic_string /* String */ {
+            ("\"" basic_chars "\"") => |v, _| v(1);
+        }
+
+        basic_chars /* String */ {
+            () => |_, _| String::new().into_value();
+            (basic_chars escape) => |v, _| append(v(0), v(1));
+            (basic_chars (until "\"" "\\" "\n") (? |v| is_text(&v[1]))) => |v, _| append(v(0), v(1));
+        }
+
+        // A newline right after the opening quotes is left out, and the closing quotes can be
+        // preceded by one or two quotes of the string
+        ml_basic_string /* String */ {
+            ("\"\"\"" ml_start ml_basic_chars ml_basic_end) => |v, _| append(v(2), v(3));
+        }
+
+        ml_basic_chars /* String */ {
+            () => |_, _| String::new().into_value();
+            (ml_basic_chars escape) => |v, _| append(v(0), v(1));
+            // A backslash at the end of a line leaves out the whitespace up to the next character
+            (ml_basic_chars "\\" (ws::ws) newline (ws::ws_ml)) => |v, _| v(0);
+            (ml_basic_chars newline) => |v, _| append(v(0), "\n".to_owned().into_value());
+            (ml_basic_chars (until "\"\"\"" "\\" "\n" "\r\n") (? |v| is_text(&v[1])))
+                => |v, _| append(v(0), v(1));
+        }
+
+        ml_basic_end /* String */ {
+            ("\"\"\"\"\"") => |_, _| "\"\"".to_owned().into_value();
+            ("\"\"\"\"") => |_, _| "\"".to_owned().into_value();
+            ("\"\"\"") => |_, _| String::new().into_value();
+        }
+
+        escape /* String */ {
+            ("\\b") => |_, _| "\u{8}".to_owned().into_value();
+            ("\\t") => |_, _| "\t".to_owned().into_value();
+            ("\\n") => |_, _| "\n".to_owned().into_value();
+            ("\\f") => |_, _| "\u{c}".to_owned().into_value();
+            ("\\r") => |_, _| "\r".to_owned().into_value();
+            ("\\\"") => |_, _| "\"".to_owned().into_value();
+            ("\\\\") => |_, _| "\\".to_owned().into_value();
+            // Only Unicode scalar values can be escaped
+            ("\\u" (hex::digit) (hex::digit) (hex::digit) (hex::digit) (? |v| scalar(&v[1..5]).is_some()))
+                => |v, _| scalar(&(1..5).map(v).collect::<Vec<_>>()).unwrap().to_string().into_value();
+            ("\\U" (hex::digit) (hex::digit) (hex::digit) (hex::digit) (hex::digit) (hex::digit) (hex::digit)
+                (hex::digit) (? |v| scalar(&v[1..9]).is_some()))
+                => |v, _| scalar(&(1..9).map(v).collect::<Vec<_>>()).unwrap().to_string().into_value();
+        }
+
+        literal_string /* String */ {
+            ("'" (until "'" "\n") "'" (? |v| is_allowed(&v[1]))) => |v, _| {
+                v(1).downcast::<Token>().unwrap().to_string().into_value()
+            };
+        }
+
+        ml_literal_string /* String */ {
+            ("'''" ml_start ml_literal_chars ml_literal_end) => |v, _| append(v(2), v(3));
+        }
+
+        ml_literal_chars /* String */ {
+            () => |_, _| String::new().into_value();
+            (ml_literal_chars newline) => |v, _| append(v(0), "\n".to_owned().into_value());
+            (ml_literal_chars (until "'''" "\n" "\r\n") (? |v| is_text(&v[1]))) => |v, _| append(v(0), v(1));
+        }
+
+        ml_literal_end /* String */ {
+            ("'''''") => |_, _| "''".to_owned().into_value();
+            ("''''") => |_, _| "'".to_owned().into_value();
+            ("'''") => |_, _| String::new().into_value();
+        }
+
+        #[policy(ordered)]
+        ml_start {
+            (newline)
+            ()
+        }
+
+        // Arrays can span lines, have comments and end with a comma
+        array /* Vec<Value> */ {
+            ("[" array_space "]") => |_, _| Vec::<Value>::new().into_value();
+            ("[" array_values array_space "]") => |v, _| v(1);
+            ("[" array_values array_space "," array_space "]") => |v, _| v(1);
+        }
+
+        array_values /* Vec<Value> */ {
+            (array_space val) => |v, _| vec![*v(1).downcast::<Value>().unwrap()].into_value();
+            (array_values array_space "," array_space val) => |v, _| {
+                let mut values = v(0).downcast::<Vec<Value>>().unwrap();
+
+                values.push(*v(4).downcast().unwrap());
+
+                values
+            };
+        }
+
+        array_space {
+            ((ws::ws_ml))
+            (array_space comment_text (ws::ws_ml))
+        }
+
+        // Unlike arrays, inline tables are on one line and can't end with a comma
+        inline_table /* Vec<KeyValue> */ {
+            ("{" (ws::ws) "}") => |_, _| Vec::<KeyValue>::new().into_value();
+            ("{" (ws::ws) inline_members (ws::ws) "}") => |v, _| v(2);
+        }
+
+        inline_members /* Vec<KeyValue> */ {
+            (keyval) => |v, _| vec![*v(0).downcast::<KeyValue>().unwrap()].into_value();
+            (inline_members (ws::ws) "," (ws::ws) keyval) => |v, _| {
+                let mut members = v(0).downcast::<Vec<KeyValue>>().unwrap();
+
+                members.push(*v(4).downcast().unwrap());
+
+                members
+            };
+        }
+    }
+}
+
+// Whether a run of characters has no control characters other than tabs
+fn is_allowed(value: &ParseValue) -> bool {
+    !value
+        .downcast_ref::<Token>()
+        .unwrap()
+        .chars()
+        .any(|c| c.is_ascii_control() && c != '\t')
+}
+
+// Whether a run of characters is allowed and not empty
+fn is_text(value: &ParseValue) -> bool {
+    !value.downcast_ref::<Token>().unwrap().is_empty() && is_allowed(value)
+}
+
+// The character of the hexadecimal digits of an escape
+fn scalar(digits: &[ParseValue]) -> Option<char> {
+    let hex = digits
+        .iter()
+        .map(|digit| digit.downcast_ref::<Token>().unwrap().as_str())
+        .collect::<String>();
+
+    char::from_u32(u32::from_str_radix(&hex, 16).unwrap())
+}
+
+// Appends a `String` or `Token` to a `String`
+fn append(string: ParseValue, other: ParseValue) -> ParseValue {
+    let mut string = string.downcast::<String>().unwrap();
+
+    match other.downcast::<Token>() {
+        Ok(token) => string.push_str(&token),
+        Err(other) => string.push_str(&other.downcast::<String>().unwrap()),
+    }
+
+    string
+}
+
+fn bare_key(input: &mut dyn MatcherInput) -> Option<ParseValue> {
+    let mut key = String::new();
+
+    while input
+        .peek()
+        .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        key.push(input.next().unwrap());
+    }
+
+    (!key.is_empty()).then(|| key.into_value())
+}
+
+// Matches an integer, giving its digits without underscores (and with its sign) and its radix
+fn integer(input: &mut dyn MatcherInput) -> Option<ParseValue> {
+    for (prefix, radix) in [("0x", 16u32), ("0o", 8), ("0b", 2)] {
+        if input.starts_with(prefix) {
+            input.next();
+            input.next();
+
+            return Some((digits(input, radix)?, radix).into_value());
+        }
+    }
+
+    let mut integer = sign(input);
+    integer.push_str(&decimal(input)?);
+
+    Some((integer, 10u32).into_value())
+}
+
+// Matches a float, giving its text without underscores. Unlike integers, floats have a fraction,
+// an exponent or both.
+fn float(input: &mut dyn MatcherInput) -> Option<ParseValue> {
+    let mut float = sign(input);
+
+    for special in ["inf", "nan"] {
+        if input.starts_with(special) {
+            for _ in 0..3 {
+                input.next();
+            }
+
+            return Some(format!("{float}{special}").into_value());
+        }
+    }
+
+    float.push_str(&decimal(input)?);
+
+    let fraction = input.peek() == Some('.');
+
+    if fraction {
+        input.next();
+
+        float.push('.');
+        float.push_str(&digits(input, 10)?);
+    }
+
+    let exponent = matches!(input.peek(), Some('e' | 'E'));
+
+    if exponent {
+        input.next();
+
+        float.push('e');
+        float.push_str(&sign(input));
+        float.push_str(&digits(input, 10)?);
+    }
+
+    (fraction || exponent).then(|| float.into_value())
+}
+
+fn sign(input: &mut dyn MatcherInput) -> String {
+    match input.peek() {
+        Some(sign @ ('+' | '-')) => {
+            input.next();
+
+            sign.to_string()
+        }
+        _ => String::new(),
+    }
+}
+
+// Decimal digits without leading zeros
+fn decimal(input: &mut dyn MatcherInput) -> Option<String> {
+    if input.peek() == Some('0') {
+        input.next();
+
+        return match input.peek() {
+            Some(c) if c.is_ascii_digit() || c == '_' => None,
+            _ => Some("0".to_owned()),
+        };
+    }
+
+    digits(input, 10)
+}
+
+// Digits with single underscores between them, which are left out
+fn digits(input: &mut dyn MatcherInput, radix: u32) -> Option<String> {
+    let mut digits = String::new();
+
+    loop {
+        if !input.peek()?.is_digit(radix) {
+            return None;
+        }
+
+        while input.peek().is_some_and(|c| c.is_digit(radix)) {
+            digits.push(input.next().unwrap());
+        }
+
+        if input.peek() != Some('_') {
+            return Some(digits);
+        }
+
+        input.next();
+    }
+}
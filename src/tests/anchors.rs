@@ -0,0 +1,133 @@
+use super::*;
+
+#[test]
+fn eof() {
+    init();
+
+    // The last statement may omit the semicolon
+    let rules = rules! {
+        start {
+            (statements)
+        }
+
+        statements {
+            () => |_, _| 0.into_value();
+            (statement ";" statements) => |v, _| (*v(2).downcast::<i32>().unwrap() + 1).into_value();
+            (statement EOF) => |_, _| 1.into_value();
+        }
+
+        statement {
+            ("x")
+        }
+    };
+
+    let inputs = [("", 0), ("x", 1), ("x;", 1), ("x;x", 2), ("x;x;", 2)];
+
+    for (input, expected_result) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(
+            Some(&expected_result),
+            rules
+                .parse("start", input)
+                .expect("Should be parsed")
+                .downcast_ref()
+        );
+    }
+
+    // A statement without a semicolon has to be the last one
+    assert!(rules.parse_entire("start", "xx").is_err());
+    assert!(rules.parse_entire("start", "x;xx").is_err());
+
+    // Anchors have no value
+    let rules = rules! {
+        start {
+            ("x" EOF)
+        }
+    };
+
+    assert_eq!(
+        Some(&Token::from("x")),
+        rules
+            .parse("start", "x")
+            .expect("Should be parsed")
+            .downcast_ref()
+    );
+}
+
+#[test]
+fn parse_entire() {
+    init();
+
+    let rules = rules! {
+        start {
+            ("x")
+        }
+    };
+
+    // The end of the input is required after the start rule, so that's where the error is
+    assert!(matches!(
+        rules.parse_entire("start", "xy"),
+        Err(ParseError::UnexpectedChar {
+            current_rule,
+            char: Some('y'),
+            pos: 1,
+            row: 1,
+            col: 2,
+        }) if current_rule == "start"
+    ));
+}
+
+#[test]
+fn line_anchors() {
+    init();
+
+    // Comments start with a "#" at the start of a line
+    let rules = rules! {
+        start {
+            () => |_, _| Vec::<String>::new().into_value();
+            (start line) => |v, _| {
+                let mut lines = v(0).downcast::<Vec<String>>().unwrap();
+                lines.push(*v(1).downcast::<String>().unwrap());
+
+                lines
+            };
+        }
+
+        line {
+            (BOL "#" text EOL newline) => |_, _| "comment".to_owned().into_value();
+            (BOL text EOL newline) => |v, _| v(1);
+        }
+
+        newline {
+            ("\n")
+            ("\r\n")
+            (EOF)
+        }
+
+        text {
+            ((! "\n" "\r\n")) => |v, _| v(0).downcast::<Token>().unwrap().to_string().into_value();
+            (text (! "\n" "\r\n")) => |v, _| {
+                format!("{}{}", v(0).downcast::<String>().unwrap(), v(1).downcast::<Token>().unwrap()).into_value()
+            };
+        }
+    };
+
+    let inputs = [
+        ("a", vec!["a"]),
+        ("a\n#b\nc#d", vec!["a", "comment", "c#d"]),
+        ("#a\r\nb\r\n", vec!["comment", "b"]),
+    ];
+
+    for (input, expected_result) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(
+            Some(&expected_result.into_iter().map(String::from).collect()),
+            rules
+                .parse_entire("start", input)
+                .expect("Should be parsed")
+                .downcast_ref::<Vec<String>>()
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use super::*;
+
+#[test]
+fn case_insensitive() {
+    init();
+
+    let rules = rules! {
+        start {
+            ((i "select") " " (i "*")) => |v, _| v(0);
+        }
+    };
+
+    let inputs = ["select *", "SELECT *", "SeLeCt *"];
+
+    for input in inputs {
+        log::debug!("input = \"{input}\"");
+
+        // The token holds the keyword as it appears in the input
+        assert_eq!(
+            Some(&Token::from(&input[..6])),
+            rules
+                .parse_entire("start", input)
+                .expect("Should be parsed")
+                .downcast_ref()
+        );
+    }
+
+    assert!(rules.parse_entire("start", "selec *").is_err());
+    assert!(rules.parse_entire("start", "ſelect *").is_err());
+}
+
+#[test]
+fn case_insensitive_unicode() {
+    init();
+
+    let rules = rules! {
+        start {
+            ((iu "straße"))
+        }
+
+        ascii {
+            ((i "straße"))
+        }
+    };
+
+    let inputs = ["straße", "STRASSE", "Strasse", "STRAẞE"];
+
+    for input in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(
+            Some(&Token::from(input)),
+            rules
+                .parse_entire("start", input)
+                .expect("Should be parsed")
+                .downcast_ref()
+        );
+    }
+
+    assert!(rules.parse_entire("start", "strase").is_err());
+
+    // ASCII case folding leaves other characters alone
+    assert!(rules.parse_entire("ascii", "STRAßE").is_ok());
+    assert!(rules.parse_entire("ascii", "STRASSE").is_err());
+}
+
+#[test]
+fn case_insensitive_not() {
+    init();
+
+    let rules = rules! {
+        start {
+            ("<" tag_name ">") => |v, _| v(1);
+        }
+
+        tag_name {
+            ((! (i "script") ">")) => |v, _| v(0).downcast::<Token>().unwrap().to_string().into_value();
+            (tag_name (! ">")) => |v, _| {
+                format!("{}{}", v(0).downcast::<String>().unwrap(), v(1).downcast::<Token>().unwrap()).into_value()
+            };
+        }
+    };
+
+    let inputs = [
+        ("<div>", Some("div")),
+        ("<script>", None),
+        ("<ScRiPt>", None),
+    ];
+
+    for (input, expected_result) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(
+            expected_result.map(String::from),
+            rules
+                .parse_entire("start", input)
+                .ok()
+                .map(|v| *v.downcast::<String>().unwrap())
+        );
+    }
+}
@@ -0,0 +1,328 @@
+use super::*;
+use crate::context::ContextError;
+use std::any::Any;
+
+// Represents "no value" in the tests, since `Nothing` is private to the engine
+struct Nothing;
+
+#[test]
+fn symbol_table() {
+    init();
+
+    let rules = rules! {
+        start {
+            (statements)
+        }
+
+        statements {
+            ()
+            (statements statement ";")
+        }
+
+        statement {
+            ("let " name) => mut |v, _, declared: &mut Vec<String>| {
+                declared.push(*v(1).downcast::<String>().unwrap());
+
+                Nothing.into_value()
+            };
+            ("use " name) => mut |v, _, declared: &mut Vec<String>| {
+                let name = *v(1).downcast::<String>().unwrap();
+
+                if declared.contains(&name) {
+                    name.into_value()
+                } else {
+                    std::io::Error::other(format!("'{name}' is not declared")).into_error()
+                }
+            };
+        }
+
+        name {
+            ("x") => |_, _| "x".to_owned().into_value();
+            ("y") => |_, _| "y".to_owned().into_value();
+        }
+    };
+
+    let mut declared: Vec<String> = Vec::new();
+
+    rules
+        .parse_entire_with_context("start", "let x;let y;use x;", &mut declared)
+        .expect("Should be parsed");
+
+    assert_eq!(vec!["x".to_owned(), "y".to_owned()], declared);
+
+    let mut declared: Vec<String> = Vec::new();
+
+    let error = rules
+        .parse_entire_with_context("start", "let x;use y;", &mut declared)
+        .expect_err("Should not be parsed");
+
+    log::debug!("error = {error}");
+
+    // Only the statements before the error are part of the parsed input
+    assert_eq!(vec!["x".to_owned()], declared);
+
+    let mut declared: Vec<String> = Vec::new();
+
+    rules
+        .parse_entire_with_context("start", "let x;let ", &mut declared)
+        .expect_err("Should not be parsed");
+
+    assert_eq!(vec!["x".to_owned()], declared);
+
+    let mut declared: Vec<String> = Vec::new();
+
+    rules
+        .parse_entire_with_context("statement", "use x", &mut declared)
+        .expect_err("Should not be parsed");
+
+    assert!(declared.is_empty());
+}
+
+#[test]
+fn rollback() {
+    init();
+
+    let rules = rules! {
+        start {
+            (a_then_b)
+            (a_then_c)
+        }
+
+        a_then_b {
+            (a "b")
+        }
+
+        a_then_c {
+            (a "c")
+        }
+
+        a {
+            ("a") => mut |_, _, count: &mut usize| {
+                *count += 1;
+
+                Nothing.into_value()
+            };
+        }
+    };
+
+    let inputs = [("ab", 1), ("ac", 1), ("ad", 0)];
+
+    for (input, expected_count) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        let mut count = 0usize;
+
+        let _ = rules.parse_entire_with_context("start", input, &mut count);
+
+        assert_eq!(expected_count, count);
+    }
+}
+
+#[test]
+fn rollback_left_recursion() {
+    init();
+
+    let rules = rules! {
+        start {
+            (list)
+        }
+
+        list {
+            (item)
+            (list "," item)
+        }
+
+        item {
+            ("x") => mut |_, _, items: &mut Vec<usize>| {
+                items.push(items.len());
+
+                Nothing.into_value()
+            };
+        }
+    };
+
+    let inputs = [("x", 1), ("x,x", 2), ("x,x,x", 3)];
+
+    for (input, expected_count) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        let mut items: Vec<usize> = Vec::new();
+
+        rules
+            .parse_with_context("start", input, &mut items)
+            .expect("Should be parsed");
+
+        assert_eq!((0..expected_count).collect::<Vec<_>>(), items);
+    }
+
+    // The last item fails to be parsed, so it never reaches the transformer
+    let mut items: Vec<usize> = Vec::new();
+
+    rules
+        .parse_with_context("start", "x,x,y", &mut items)
+        .expect("Should be parsed");
+
+    assert_eq!(vec![0, 1], items);
+}
+
+#[test]
+fn rollback_longest() {
+    init();
+
+    let rules = rules! {
+        #[policy(longest)]
+        start {
+            (word) => mut |_, _, log: &mut Vec<&'static str>| {
+                log.push("word");
+
+                Nothing.into_value()
+            };
+            (word "!") => mut |_, _, log: &mut Vec<&'static str>| {
+                log.push("word!");
+
+                Nothing.into_value()
+            };
+        }
+
+        word {
+            ("hello")
+        }
+    };
+
+    let inputs = [("hello", vec!["word"]), ("hello!", vec!["word!"])];
+
+    for (input, expected_log) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        let mut log: Vec<&'static str> = Vec::new();
+
+        rules
+            .parse_entire_with_context("start", input, &mut log)
+            .expect("Should be parsed");
+
+        assert_eq!(expected_log, log);
+    }
+}
+
+// A context which can't be cloned, so it undoes its changes using their order
+#[derive(Default)]
+struct Names {
+    names: Vec<&'static str>,
+    commits: usize,
+}
+
+impl ParseContext for Names {
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn checkpoint(&mut self) -> Box<dyn Any> {
+        Box::new(self.names.len())
+    }
+
+    fn rollback(&mut self, checkpoint: Box<dyn Any>) {
+        self.names
+            .truncate(*checkpoint.downcast::<usize>().unwrap());
+    }
+
+    fn commit(&mut self, _checkpoint: Box<dyn Any>) {
+        self.commits += 1;
+    }
+}
+
+#[test]
+fn undo_log() {
+    init();
+
+    let rules = rules! {
+        #[policy(longest)]
+        start {
+            (word "!") => mut |_, _, names: &mut Names| {
+                names.names.push("word!");
+
+                Nothing.into_value()
+            };
+            (word) => mut |_, _, names: &mut Names| {
+                names.names.push("word");
+
+                Nothing.into_value()
+            };
+        }
+
+        word {
+            ("hello") => mut |_, _, names: &mut Names| {
+                names.names.push("hello");
+
+                Nothing.into_value()
+            };
+        }
+    };
+
+    // The longest alternative is not the last one tried, so it is parsed again to redo its changes
+    let inputs = [
+        ("hello", vec!["hello", "word"]),
+        ("hello!", vec!["hello", "word!"]),
+    ];
+
+    for (input, expected_names) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        let mut names = Names::default();
+
+        rules
+            .parse_entire_with_context("start", input, &mut names)
+            .expect("Should be parsed");
+
+        assert_eq!(expected_names, names.names);
+        assert_eq!(1, names.commits);
+    }
+
+    let mut names = Names::default();
+
+    rules
+        .parse_entire_with_context("start", "bye", &mut names)
+        .expect_err("Should not be parsed");
+
+    assert!(names.names.is_empty());
+    assert_eq!(0, names.commits);
+}
+
+#[test]
+fn missing_context() {
+    init();
+
+    let rules = rules! {
+        start {
+            ("a") => mut |_, _, count: &mut usize| {
+                *count += 1;
+
+                (*count).into_value()
+            };
+        }
+    };
+
+    for result in [
+        rules.parse("start", "a"),
+        rules.parse_with_context("start", "a", &mut "wrong type".to_owned()),
+    ] {
+        match result {
+            Err(ParseError::TransformerError { error, .. }) => {
+                assert_eq!(
+                    Some(&ContextError::new::<usize>()),
+                    error.downcast_ref::<ContextError>()
+                );
+            }
+            result => panic!("Expected a ContextError, got {result:?}"),
+        }
+    }
+
+    let mut count = 0usize;
+
+    assert_eq!(
+        Some(&1),
+        rules
+            .parse_with_context("start", "a", &mut count)
+            .expect("Should be parsed")
+            .downcast_ref::<usize>()
+    );
+}
@@ -0,0 +1,316 @@
+use super::*;
+use crate::de::DeserializeError;
+use crate::rules::{self, grammar::parse_grammar};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Config {
+    name: String,
+    port: u16,
+    debug: Option<bool>,
+    servers: Vec<Server>,
+    mode: Mode,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Server {
+    host: String,
+    weight: f64,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Mode {
+    Fast,
+    Slow { delay: u32 },
+}
+
+#[test]
+fn json() {
+    init();
+
+    let input = r#"{
+        "name": "psi",
+        "port": 8080,
+        "servers": [{"host": "a", "weight": 0.5}, {"host": "b", "weight": 2}],
+        "mode": {"slow": {"delay": 10}}
+    }"#;
+
+    let config: Config = psi_parser::from_str(rules::JsonRules, input).unwrap();
+
+    assert_eq!(
+        Config {
+            name: "psi".to_owned(),
+            port: 8080,
+            debug: None,
+            servers: vec![
+                Server {
+                    host: "a".to_owned(),
+                    weight: 0.5
+                },
+                Server {
+                    host: "b".to_owned(),
+                    weight: 2.0
+                }
+            ],
+            mode: Mode::Slow { delay: 10 },
+        },
+        config
+    );
+
+    let map: HashMap<String, Vec<i32>> =
+        psi_parser::from_str(rules::JsonRules, r#"{"a": [1, -2], "b": []}"#).unwrap();
+    assert_eq!(Some(&vec![1, -2]), map.get("a"));
+    assert_eq!(Some(&vec![]), map.get("b"));
+
+    let mode: Mode = psi_parser::from_str(rules::JsonRules, "\"fast\"").unwrap();
+    assert_eq!(Mode::Fast, mode);
+
+    // Predicates (like the one rejecting lone surrogates) see the values of their rules
+    let escaped: String =
+        psi_parser::from_str(rules::JsonRules, "\"\\u0041\\ud83d\\ude00\"").unwrap();
+    assert_eq!("A\u{1F600}", escaped);
+
+    let escaped: String = psi_parser::from_str(rules::Json5Rules, "'\\u0041'").unwrap();
+    assert_eq!("A", escaped);
+
+    let integers = rules! {
+        #[import (rules::Integer) as integer]
+
+        start {
+            ((integer::integer))
+        }
+    };
+
+    let (integer, tree) = integers
+        .parse_entire_spanned("start", "-12")
+        .expect("Should be parsed");
+    assert_eq!(Some(&-12), integer.downcast_ref::<isize>());
+    assert_eq!(
+        1,
+        tree.expect("Should have a tree")
+            .find("integer::integer")
+            .len()
+    );
+
+    // JSON5 and JSONC give the same values
+    let json5 = "{name: 'psi', port: 0x1F90, servers: [], mode: 'fast', /* comment */}";
+    let jsonc =
+        "{\"name\": \"psi\", // comment\n \"port\": 8080, \"servers\": [], \"mode\": \"fast\"}";
+
+    assert_eq!(
+        psi_parser::from_str::<Config>(rules::Json5Rules, json5).unwrap(),
+        psi_parser::from_str::<Config>(rules::JsoncRules, jsonc).unwrap()
+    );
+}
+
+#[test]
+fn json_error_spans() {
+    init();
+
+    for (input, message, line, column) in [
+        (
+            "{\"name\": \"psi\",\n \"port\": \"8080\"}",
+            "invalid type: string \"8080\", expected u16",
+            2,
+            10,
+        ),
+        (
+            "{\"name\": \"psi\",\n \"port\": 80,\n \"servers\": [\n  {\"host\": \"a\", \"weight\": 1},\n  {\"host\": \"b\"}\n ],\n \"mode\": \"fast\"}",
+            "missing field `weight`",
+            5,
+            3,
+        ),
+        (
+            "{\"name\": \"psi\", \"port\": 80, \"servers\": [], \"mode\": \"quick\"}",
+            "unknown variant `quick`, expected `fast` or `slow`",
+            1,
+            52,
+        ),
+        (
+            "{\"name\": \"psi\", \"port\": 70000, \"servers\": [], \"mode\": \"fast\"}",
+            "invalid value: integer `70000`, expected u16",
+            1,
+            25,
+        ),
+    ] {
+        log::debug!("input = \"{input}\"");
+
+        match psi_parser::from_str::<Config>(rules::JsonRules, input) {
+            Err(DeserializeError::Invalid {
+                message: m,
+                span: Some(span),
+            }) => {
+                assert_eq!(message, m);
+                assert_eq!(
+                    (line, column),
+                    (span.start.line, span.start.column),
+                    "{m}"
+                );
+            }
+            result => panic!("Expected an error with a span, got {result:?}"),
+        }
+    }
+
+    // Errors in parsing aren't deserialization errors
+    assert!(matches!(
+        psi_parser::from_str::<Config>(rules::JsonRules, "{\"name\": }"),
+        Err(DeserializeError::Parse(_))
+    ));
+}
+
+#[test]
+fn xml() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Library {
+        #[serde(rename = "@name")]
+        name: String,
+        book: Vec<Book>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Book {
+        #[serde(rename = "@year")]
+        year: u32,
+        title: String,
+        author: Vec<String>,
+    }
+
+    init();
+
+    let input = r#"
+        <library name="city">
+            <book year="1990">
+                <title>Good Omens</title>
+                <author>Pratchett</author>
+                <author>Gaiman</author>
+            </book>
+            <book year='1949'><title>1984</title><author>Orwell</author></book>
+        </library>"#;
+
+    let library: Library = psi_parser::from_str(rules::XmlRules, input).unwrap();
+
+    assert_eq!(
+        Library {
+            name: "city".to_owned(),
+            book: vec![
+                Book {
+                    year: 1990,
+                    title: "Good Omens".to_owned(),
+                    author: vec!["Pratchett".to_owned(), "Gaiman".to_owned()],
+                },
+                Book {
+                    year: 1949,
+                    title: "1984".to_owned(),
+                    author: vec!["Orwell".to_owned()],
+                },
+            ],
+        },
+        library
+    );
+
+    let input = "<library name='city'>\n<book year='soon'><title>Dune</title><author>Herbert</author></book>\n</library>";
+
+    match psi_parser::from_str::<Library>(rules::XmlRules, input) {
+        Err(DeserializeError::Invalid {
+            message,
+            span: Some(span),
+        }) => {
+            assert_eq!("Expected a u32, found \"soon\"", message);
+            assert_eq!((2, 1), (span.start.line, span.start.column));
+        }
+        result => panic!("Expected an error with a span, got {result:?}"),
+    }
+}
+
+#[test]
+fn cst() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Assignment {
+        name: String,
+        values: Vec<Value>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Value {
+        Number(i64),
+        Word(String),
+    }
+
+    init();
+
+    let rules = parse_grammar(
+        r#"
+        start {
+            (name " = " values)
+        }
+
+        name {
+            ((until " "))
+        }
+
+        values {
+            (value)
+            (values ", " value)
+        }
+
+        value {
+            (number)
+            (word)
+        }
+
+        number {
+            (digits)
+            ("-" digits)
+        }
+
+        digits {
+            (digit)
+            (digits digit)
+        }
+
+        digit {
+            ("0") ("1") ("2") ("3") ("4") ("5") ("6") ("7") ("8") ("9")
+        }
+
+        word {
+            ("a") ("b") ("c")
+            (word word)
+        }
+        "#,
+    )
+    .expect("The grammar should be parsed");
+
+    let assignment: Assignment = psi_parser::from_str(rules.clone(), "x = 12, abc, -3").unwrap();
+
+    assert_eq!(
+        Assignment {
+            name: "x".to_owned(),
+            values: vec![
+                Value::Number(12),
+                Value::Word("abc".to_owned()),
+                Value::Number(-3)
+            ],
+        },
+        assignment
+    );
+
+    #[derive(Debug, Deserialize)]
+    struct Numbers {
+        #[allow(unused)]
+        values: Vec<u8>,
+    }
+
+    match psi_parser::from_str::<Numbers>(rules, "x = 1, 2, 300") {
+        Err(DeserializeError::Invalid {
+            message,
+            span: Some(span),
+        }) => {
+            assert_eq!("Expected a u8, found \"300\"", message);
+            assert_eq!(11, span.start.column);
+        }
+        result => panic!("Expected an error with a span, got {result:?}"),
+    }
+}
@@ -0,0 +1,98 @@
+use super::*;
+
+fn list_rules() -> Rules {
+    rules! {
+        #[import (rules! { sep { (",") (";") } }) as s]
+
+        list {
+            (item)
+            (list (s::sep) item)
+        }
+
+        item {
+            ("a")
+            ((i "b"))
+            ("\"'" (until "\n"))
+            ()
+        }
+    }
+}
+
+#[test]
+fn ebnf() {
+    init();
+
+    let ebnf = list_rules().to_ebnf();
+
+    log::debug!("{ebnf}");
+
+    assert_eq!(
+        [
+            "item = ? \"\\\"'\" ?, { ? any character ? - ( ? \"\\n\" ? ) } | \"a\" | ? case-insensitive \"b\" ? | ;",
+            "list = list, s_3a__3a_sep, item | item ;",
+            "s_3a__3a_sep = \",\" | \";\" ;",
+        ]
+        .join("\n")
+            + "\n",
+        ebnf
+    );
+
+    // Names which only differ in their punctuation stay different, and `?` can't end a special sequence
+    let rules = rules! {
+        #[import (rules! { b { ((i "?")) } }) as a]
+
+        a__b {
+            ((a::b))
+        }
+    };
+
+    assert_eq!(
+        "a_3a__3a_b = ? case-insensitive \"\\u{3f}\" ? ;\na____b = a_3a__3a_b ;\n",
+        rules.to_ebnf()
+    );
+}
+
+#[test]
+fn dot() {
+    init();
+
+    let dot = list_rules().to_dot();
+
+    log::debug!("{dot}");
+
+    assert!(dot.starts_with("digraph rules {"));
+    assert!(dot.contains("subgraph \"cluster_s\" {"));
+    assert!(dot.contains("\"s::sep\" [label=\"sep\"];"));
+    assert!(dot.contains("\"list\" -> \"item\";"));
+    assert!(dot.contains("\"list\" -> \"s::sep\";"));
+    // Left recursion isn't a reference
+    assert!(!dot.contains("\"list\" -> \"list\";"));
+}
+
+#[test]
+fn railroad() {
+    init();
+
+    let rules = list_rules();
+
+    let svg = rules.railroad_svg("list").expect("The rule should exist");
+
+    log::debug!("{svg}");
+
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.ends_with("</svg>"));
+    assert!(svg.contains("<a href=\"#s::sep\">"));
+    // item, s::sep and item again (the left recursion is a loop)
+    assert_eq!(3, svg.matches("class=\"nonterminal\"").count());
+
+    assert!(rules.railroad_svg("missing").is_none());
+
+    let html = rules.railroad_html();
+
+    for rule_name in ["list", "item", "s::sep"] {
+        assert!(html.contains(&format!("<h2 id=\"{rule_name}\">")));
+    }
+
+    // Text is escaped
+    assert!(html.contains(">&quot;\\&quot;'&quot;</text>"));
+}
@@ -0,0 +1,174 @@
+use super::*;
+use crate::generate::{GenerateError, Generator};
+
+fn list_rules() -> Rules {
+    rules! {
+        list {
+            (item)
+            (list "," item)
+        }
+
+        item {
+            ("x")
+            ("y")
+        }
+    }
+}
+
+#[test]
+fn seed() {
+    init();
+
+    let rules = list_rules();
+
+    let sentences = |seed| {
+        let mut generator = Generator::new(&rules).seed(seed);
+
+        (0..10)
+            .map(|_| generator.generate("list").expect("Should be generated"))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(sentences(1), sentences(1));
+    assert_ne!(sentences(1), sentences(2));
+}
+
+#[test]
+fn depth() {
+    init();
+
+    let rules = list_rules();
+
+    let mut generator = Generator::new(&rules).max_depth(0);
+
+    for _ in 0..10 {
+        let sentence = generator.generate("list").expect("Should be generated");
+
+        assert!(sentence == "x" || sentence == "y");
+    }
+
+    let mut generator = Generator::new(&rules).min_depth(5).max_depth(5);
+
+    for _ in 0..10 {
+        let sentence = generator.generate("list").expect("Should be generated");
+
+        log::debug!("sentence = \"{sentence}\"");
+
+        assert_eq!(5, sentence.matches(',').count());
+    }
+}
+
+#[test]
+fn weight() {
+    init();
+
+    let rules = list_rules();
+
+    let y = rules
+        .alternatives("item")
+        .unwrap()
+        .iter()
+        .position(|parts| parts[0] == &psi_parser::rule::RulePart::Term("y".to_owned()))
+        .unwrap();
+
+    let mut generator = Generator::new(&rules).weight("item", y, 0.0);
+
+    for _ in 0..10 {
+        let sentence = generator.generate("list").expect("Should be generated");
+
+        assert!(!sentence.contains('y'));
+    }
+}
+
+#[test]
+fn round_trip() {
+    init();
+
+    let rules = rules! {
+        start {
+            (statement)
+            (start "\n" statement)
+        }
+
+        statement {
+            ((i "select") " " column (ws) ";")
+            ("-- " (until "\n"))
+        }
+
+        column {
+            ((! "x" "0") (until " " ";" "\n"))
+        }
+
+        ws {
+            ()
+            (ws " ")
+        }
+    };
+
+    Generator::new(&rules)
+        .seed(7)
+        .alphabet('a'..='z')
+        .assert_round_trip("start", 50);
+}
+
+#[test]
+#[cfg(feature = "included_parsers")]
+fn round_trip_json() {
+    init();
+
+    let json = Rules::from(psi_parser::rules::JsonRules);
+
+    for seed in 0..5 {
+        Generator::new(&json)
+            .seed(seed)
+            .max_depth(10)
+            .assert_round_trip("start", 20);
+    }
+}
+
+#[test]
+fn generate_errors() {
+    init();
+
+    let rules = rules! {
+        #[matcher (|_: &mut dyn psi_parser::input::MatcherInput| None) as nothing]
+
+        endless {
+            (endless "x")
+        }
+
+        matched {
+            (nothing)
+        }
+
+        uses_matched {
+            (matched "x")
+            ("y" uses_matched)
+        }
+    };
+
+    let mut generator = Generator::new(&rules);
+
+    assert_eq!(
+        Err(GenerateError::Unbounded {
+            rule_name: "endless".to_owned()
+        }),
+        generator.generate("endless")
+    );
+
+    for rule_name in ["matched", "uses_matched"] {
+        assert_eq!(
+            Err(GenerateError::Matcher {
+                matcher: "nothing".to_owned()
+            }),
+            generator.generate(rule_name)
+        );
+    }
+
+    assert_eq!(
+        Err(GenerateError::RuleNotFound {
+            rule_name: "missing".to_owned()
+        }),
+        generator.generate("missing")
+    );
+}
@@ -0,0 +1,193 @@
+use super::*;
+
+#[test]
+fn comma_list() {
+    init();
+
+    let rules = rules! {
+        start {
+            ("[" (comma_list<number>) "]") => |v, _| v(1);
+            ("{" (comma_list<word>) "}") => |v, _| v(1);
+        }
+
+        comma_list<item> {
+            (item) => |v, _| vec![*v(0).downcast::<String>().unwrap()].into_value();
+            (comma_list "," item) => |v, _| {
+                let mut list = v(0).downcast::<Vec<String>>().unwrap();
+                list.push(*v(2).downcast::<String>().unwrap());
+
+                list
+            };
+        }
+
+        number {
+            ("1") => |_, _| "1".to_owned().into_value();
+            ("2") => |_, _| "2".to_owned().into_value();
+        }
+
+        word {
+            ("a") => |_, _| "a".to_owned().into_value();
+            ("b") => |_, _| "b".to_owned().into_value();
+        }
+    };
+
+    let inputs = [
+        ("[1]", Some(vec!["1"])),
+        ("[1,2,1]", Some(vec!["1", "2", "1"])),
+        ("{a,b}", Some(vec!["a", "b"])),
+        ("[a]", None),
+        ("{1}", None),
+        ("[1,a]", None),
+        ("[]", None),
+    ];
+
+    for (input, expected) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        let result = rules.parse_entire("start", input);
+
+        match expected {
+            Some(expected) => assert_eq!(
+                expected,
+                *result
+                    .expect("Should be parsed")
+                    .downcast::<Vec<String>>()
+                    .unwrap()
+            ),
+            None => {
+                result.expect_err("Should not be parsed");
+            }
+        }
+    }
+}
+
+#[test]
+fn quoted() {
+    init();
+
+    let rules = rules! {
+        start {
+            ((quoted<"'">))
+            ((quoted<"\"">))
+            ((quoted<(i "q")>))
+        }
+
+        quoted<q> {
+            (q (until q) q) => |v, _| v(1).downcast::<Token>().unwrap().to_string().into_value();
+        }
+    };
+
+    let inputs = [
+        ("'abc'", Some("abc")),
+        ("\"it's\"", Some("it's")),
+        ("qabcQ", Some("abc")),
+        ("''", Some("")),
+        ("'abc\"", None),
+        ("'a'b'", None),
+    ];
+
+    for (input, expected) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        let result = rules.parse_entire("start", input);
+
+        match expected {
+            Some(expected) => assert_eq!(
+                Some(&expected.to_owned()),
+                result.expect("Should be parsed").downcast_ref::<String>()
+            ),
+            None => {
+                result.expect_err("Should not be parsed");
+            }
+        }
+    }
+}
+
+#[test]
+fn nested_instances() {
+    init();
+
+    let rules = rules! {
+        start {
+            ((pair<(pair<"a", "b">), "c">))
+        }
+
+        pair<first, second> {
+            (first second) => |_, span| (span.end.pos - span.start.pos).into_value();
+        }
+    };
+
+    assert_eq!(
+        Some(&3),
+        rules
+            .parse_entire("start", "abc")
+            .expect("Should be parsed")
+            .downcast_ref::<usize>()
+    );
+
+    rules
+        .parse_entire("start", "acb")
+        .expect_err("Should not be parsed");
+
+    // Each instance is its own rule
+    let names = rules.rule_names();
+
+    assert!(names.contains(&"pair<\"a\", \"b\">".to_owned()));
+    assert!(names.contains(&"pair<pair<\"a\", \"b\">, \"c\">".to_owned()));
+    assert!(!names.contains(&"pair".to_owned()));
+}
+
+#[test]
+fn instance_errors() {
+    use psi_parser::rule::{InstanceError, Rule, RulePart};
+
+    init();
+
+    let pair = Rule {
+        name: "pair".to_owned(),
+        params: vec!["first".to_owned(), "second".to_owned()],
+        parts: vec![
+            RulePart::NonTerm("first".to_owned()),
+            RulePart::NonTerm("second".to_owned()),
+        ],
+        transformer: None,
+    };
+
+    let start = |rule: &str, args: Vec<RulePart>| Rule {
+        name: "start".to_owned(),
+        params: Vec::new(),
+        parts: vec![RulePart::Instance(rule.to_owned(), args)],
+        transformer: None,
+    };
+
+    let x = || RulePart::Term("x".to_owned());
+
+    assert!(Rules::try_new([pair.clone(), start("pair", vec![x(), x()])]).is_ok());
+
+    assert_eq!(
+        Some(InstanceError::RuleNotFound {
+            rule_name: "start".to_owned(),
+            instance: "missing<\"x\">".to_owned(),
+        }),
+        Rules::try_new([pair.clone(), start("missing", vec![x()])]).err()
+    );
+
+    assert_eq!(
+        Some(InstanceError::WrongArgumentCount {
+            rule_name: "start".to_owned(),
+            instance: "pair<\"x\">".to_owned(),
+            expected: 2,
+        }),
+        Rules::try_new([pair, start("pair", vec![x()])]).err()
+    );
+}
+
+#[test]
+#[should_panic(expected = "No parameterized rule for 'missing<\"x\">' (in rule 'start')")]
+fn unknown_instance() {
+    rules! {
+        start {
+            ((missing<"x">))
+        }
+    };
+}
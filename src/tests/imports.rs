@@ -0,0 +1,240 @@
+use super::*;
+
+#[test]
+fn override_rule() {
+    init();
+
+    let base = rules! {
+        list {
+            (item)
+            (list "," item)
+        }
+
+        item {
+            ("1")
+            ("2")
+        }
+    };
+
+    let rules = rules! {
+        #[import (base) as base { override item = letter }]
+
+        start {
+            ((base::list))
+        }
+
+        letter {
+            ("a")
+            ("b")
+        }
+    };
+
+    let inputs = [("a", true), ("a,b,a", true), ("1", false), ("a,2", false)];
+
+    for (input, expected) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(expected, rules.parse_entire("start", input).is_ok());
+    }
+
+    assert!(!rules.rule_names().contains(&"base::item".to_owned()));
+}
+
+#[test]
+#[cfg(feature = "included_parsers")]
+fn override_nested_rule() {
+    init();
+
+    let rules = rules! {
+        #[import (rules::JsonRules) as json { override ws::ws_ml = underscores }]
+
+        underscores {
+            ()
+            (underscores "_")
+        }
+    };
+
+    let inputs = [
+        ("_[1,__2]_", true),
+        ("{\"a\":_1}", true),
+        ("[1, 2]", false),
+    ];
+
+    for (input, expected) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(expected, rules.parse_entire("json::start", input).is_ok());
+    }
+}
+
+#[test]
+#[cfg(feature = "included_parsers")]
+fn override_json_string() {
+    init();
+
+    let rules = rules! {
+        #[import (rules::JsonRules) as json { override string = single_quoted }]
+
+        single_quoted {
+            ("'" (until "'") "'") => |v, _| v(1).downcast::<Token>().unwrap().to_string().into_value();
+        }
+    };
+
+    let inputs = [
+        ("['a', 'b']", true),
+        ("{'key': 'value'}", true),
+        ("[\"a\"]", false),
+    ];
+
+    for (input, expected) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(expected, rules.parse_entire("json::start", input).is_ok());
+    }
+}
+
+#[test]
+fn extend_rule() {
+    init();
+
+    let base = rules! {
+        list {
+            (value)
+            (list "," value)
+        }
+
+        value {
+            ("1")
+            ("2")
+        }
+    };
+
+    let rules = rules! {
+        #[import (base) as base { extend value }]
+
+        start {
+            ((base::list))
+        }
+
+        value {
+            ("x")
+        }
+    };
+
+    let inputs = [("1", true), ("x", true), ("1,x,2", true), ("y", false)];
+
+    for (input, expected) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(expected, rules.parse_entire("start", input).is_ok());
+    }
+
+    // The extended rule has the imported alternatives too
+    rules.parse_entire("value", "1").expect("Should be parsed");
+}
+
+#[test]
+#[cfg(feature = "included_parsers")]
+fn hide_private() {
+    init();
+
+    let rules = rules! {
+        #[import (rules::Float) as float { hide private }]
+        #[import (rules::Float) as visible]
+
+        start {
+            ((float::float))
+        }
+    };
+
+    assert_eq!(
+        Some(&12.0),
+        rules
+            .parse_entire("start", "12")
+            .expect("Should be parsed")
+            .downcast_ref::<f64>()
+    );
+
+    let names = rules.rule_names();
+
+    assert!(names.contains(&"visible::_int".to_owned()));
+    assert!(!names.contains(&"float::_int".to_owned()));
+
+    assert!(matches!(
+        rules.parse_entire("float::_int", "12"),
+        Err(ParseError::RuleNotFound { .. })
+    ));
+
+    // The hidden rules have names, but can't be used by them
+    let hidden = names
+        .iter()
+        .find(|name| name.starts_with("float::_int"))
+        .expect("Should be a hidden rule");
+
+    assert!(matches!(
+        rules.parse_entire(hidden, "12"),
+        Err(ParseError::RuleNotFound { .. })
+    ));
+
+    // Errors in hidden rules are reported in the rule using them
+    let letters = rules! {
+        word { (_letter) }
+        _letter { ("a") }
+    };
+
+    let words = rules! {
+        #[import (letters) as l { hide private }]
+
+        start {
+            ((l::word))
+        }
+    };
+
+    assert!(matches!(
+        words.parse_entire("start", "b"),
+        Err(ParseError::UnexpectedChar { current_rule, .. }) if current_rule == "l::word"
+    ));
+
+    // They stay hidden when imported again
+    let outer = rules! {
+        #[import (rules) as inner]
+    };
+
+    assert!(matches!(
+        outer.parse_entire(&format!("inner::{hidden}"), "12"),
+        Err(ParseError::RuleNotFound { .. })
+    ));
+    assert!(outer.parse_entire("inner::start", "12").is_ok());
+}
+
+#[test]
+#[cfg(feature = "included_parsers")]
+fn export_rule() {
+    init();
+
+    let boolean_rules = rules! {
+        #[import (rules::Boolean) as boolean { export boolean as bool }]
+    };
+
+    let rules = rules! {
+        #[import (boolean_rules) as b]
+
+        start {
+            ((b::bool))
+        }
+    };
+
+    let inputs = [("true", true), ("false", false)];
+
+    for (input, expected) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(
+            Some(&expected),
+            rules
+                .parse_entire("start", input)
+                .expect("Should be parsed")
+                .downcast_ref::<bool>()
+        );
+    }
+}
@@ -19,6 +19,45 @@ fn json_large_file() {
     assert!(parsed.is_ok() && parsed.unwrap().is_ok());
 }
 
+#[test]
+fn json_large_file_profile() {
+    use psi_parser::observer::Profiler;
+
+    init();
+
+    let mut profiler = Profiler::new();
+
+    let parsed = rules::JsonRules.parse_entire_with(
+        "start",
+        JSON_LARGE_FILE,
+        ParseOptions::new().observer(&mut profiler),
+    );
+
+    assert!(parsed.is_ok());
+
+    println!("{}", profiler.report());
+}
+
+#[test]
+#[ignore]
+fn json_1mb_profile() {
+    use psi_parser::observer::Profiler;
+
+    init();
+
+    let mut profiler = Profiler::new();
+
+    let parsed = rules::JsonRules.parse_entire_with(
+        "start",
+        JSON_1MB,
+        ParseOptions::new().observer(&mut profiler),
+    );
+
+    assert!(parsed.is_ok());
+
+    println!("{}", profiler.report());
+}
+
 #[test]
 #[ignore] // it's just too big...
 fn json_huge_file() {
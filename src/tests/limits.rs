@@ -0,0 +1,124 @@
+use std::{
+    sync::{atomic::AtomicBool, Arc},
+    time::Instant,
+};
+
+use psi_parser::result::AbortReason;
+
+use super::*;
+
+fn aborted(result: Result<ParseValue, ParseError>) -> Option<AbortReason> {
+    match result {
+        Err(ParseError::Aborted { reason, .. }) => Some(reason),
+        _ => None,
+    }
+}
+
+#[test]
+fn no_limits() {
+    init();
+
+    let rules = rules! {
+        start { (as) }
+        as {
+            ()
+            (as "a")
+        }
+    };
+
+    let input = "a".repeat(1000);
+
+    assert!(rules
+        .parse_entire_with("start", &input, ParseOptions::new())
+        .is_ok());
+}
+
+#[test]
+fn max_steps() {
+    init();
+
+    let rules = rules! {
+        start { (as) }
+        as {
+            ()
+            (as "a")
+        }
+    };
+
+    let input = "a".repeat(1000);
+
+    assert_eq!(
+        Some(AbortReason::Steps(100)),
+        aborted(rules.parse_entire_with("start", &input, ParseOptions::new().max_steps(100)))
+    );
+
+    assert!(rules
+        .parse_entire_with("start", "aaa", ParseOptions::new().max_steps(100))
+        .is_ok());
+}
+
+#[test]
+fn max_depth() {
+    init();
+
+    let rules = rules! {
+        start { (nested) }
+        nested {
+            ("x")
+            ("(" nested ")")
+        }
+    };
+
+    let options = || ParseOptions::new().max_depth(10);
+
+    assert!(rules
+        .parse_entire_with("start", "((((x))))", options())
+        .is_ok());
+
+    let input = format!("{}x{}", "(".repeat(20), ")".repeat(20));
+
+    assert_eq!(
+        Some(AbortReason::Depth(10)),
+        aborted(rules.parse_entire_with("start", &input, options()))
+    );
+}
+
+#[test]
+#[cfg(feature = "included_parsers")]
+fn max_input_length() {
+    init();
+
+    let options = || ParseOptions::new().max_input_length(10);
+
+    assert!(rules::Identifier
+        .parse_entire_with("identifier", "abcdef", options())
+        .is_ok());
+
+    let result = rules::Identifier.parse_entire_with("identifier", "abcdefghijklmnop", options());
+
+    assert_eq!(Some(AbortReason::InputLength(10)), aborted(result));
+}
+
+#[test]
+fn deadline_and_cancel() {
+    init();
+
+    let rules = rules! {
+        start { ("a") }
+    };
+
+    assert_eq!(
+        Some(AbortReason::Deadline),
+        aborted(rules.parse_entire_with(
+            "start",
+            "a",
+            ParseOptions::new().deadline(Instant::now())
+        ))
+    );
+
+    let cancel = Arc::new(AtomicBool::new(true));
+
+    let result = rules.parse_entire_with("start", "a", ParseOptions::new().cancel(cancel));
+
+    assert_eq!(Some(AbortReason::Cancelled), aborted(result));
+}
@@ -0,0 +1,138 @@
+use super::*;
+use crate::input::MatcherInput;
+
+// Matches a (unicode) identifier
+fn identifier(input: &mut dyn MatcherInput) -> Option<ParseValue> {
+    let mut identifier = String::new();
+
+    while input.peek().is_some_and(|c| {
+        c.is_alphabetic() || c == '_' || (!identifier.is_empty() && c.is_numeric())
+    }) {
+        identifier.push(input.next().unwrap());
+    }
+
+    (!identifier.is_empty()).then(|| identifier.into_value())
+}
+
+#[test]
+fn matcher() {
+    init();
+
+    let rules = rules! {
+        #[matcher (identifier) as ident]
+
+        start {
+            (ident "=" ident) => |v, _| {
+                (*v(0).downcast::<String>().unwrap(), *v(2).downcast::<String>().unwrap()).into_value()
+            };
+        }
+    };
+
+    let inputs = [
+        ("a=b", ("a", "b")),
+        ("größe=wert_1", ("größe", "wert_1")),
+        ("変数=値", ("変数", "値")),
+    ];
+
+    for (input, (left, right)) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(
+            Some(&(left.to_owned(), right.to_owned())),
+            rules
+                .parse_entire("start", input)
+                .expect("Should be parsed")
+                .downcast_ref()
+        );
+    }
+
+    assert!(rules.parse_entire("start", "1=b").is_err());
+    assert!(rules.parse_entire("start", "a=").is_err());
+}
+
+#[test]
+fn matcher_backtracking() {
+    init();
+
+    let mut rules = rules! {
+        start {
+            (digits "x") => |_, _| "digits".to_owned().into_value();
+            (number "y") => |_, _| "number".to_owned().into_value();
+        }
+
+        number {
+            ("1" "2" "3")
+        }
+    };
+
+    // Registered after the rules were created
+    rules.add_matcher("digits", |input| {
+        let mut digits = String::new();
+
+        while input.peek().is_some_and(|c| c.is_ascii_digit()) {
+            digits.push(input.next().unwrap());
+        }
+
+        (!digits.is_empty()).then(|| digits.into_value())
+    });
+
+    let inputs = [("123x", "digits"), ("123y", "number")];
+
+    for (input, expected_result) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(
+            Some(&expected_result.to_owned()),
+            rules
+                .parse_entire("start", input)
+                .expect("Should be parsed")
+                .downcast_ref()
+        );
+    }
+}
+
+#[test]
+fn matcher_import() {
+    init();
+
+    let identifiers = rules! {
+        #[matcher (identifier) as ident]
+
+        list {
+            (ident) => |v, _| vec![*v(0).downcast::<String>().unwrap()].into_value();
+            (list "," ident) => |v, _| {
+                let mut list = v(0).downcast::<Vec<String>>().unwrap();
+                list.push(*v(2).downcast::<String>().unwrap());
+
+                list
+            };
+        }
+    };
+
+    let rules = rules! {
+        #[import (identifiers) as ids]
+
+        start {
+            ("[" (ids::list) "]") => |v, _| v(1);
+            ("<" (ids::ident) ">") => |v, _| vec![*v(1).downcast::<String>().unwrap()].into_value();
+        }
+    };
+
+    let inputs = [
+        ("[a,b,c]", vec!["a", "b", "c"]),
+        ("[x]", vec!["x"]),
+        ("<y>", vec!["y"]),
+    ];
+
+    for (input, expected_result) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(
+            Some(&expected_result.into_iter().map(String::from).collect()),
+            rules
+                .parse_entire("start", input)
+                .expect("Should be parsed")
+                .downcast_ref::<Vec<String>>()
+        );
+    }
+}
@@ -12,9 +12,40 @@ mod basic;
 
 mod recurse;
 
+#[cfg(feature = "included_parsers")]
 mod errors;
 
+mod policies;
+
+mod limits;
+
+mod observer;
+
+mod context;
+
+mod predicates;
+
+mod matchers;
+
+mod case_insensitive;
+
+mod anchors;
+
+mod until;
+
+mod generics;
+
+mod imports;
+
+mod export;
+
+mod generate;
+
+#[cfg(feature = "serde")]
+mod deserialize;
+
 #[cfg(feature = "included_parsers")]
 mod rules_tests;
 
+#[cfg(feature = "included_parsers")]
 mod large_files;
@@ -0,0 +1,284 @@
+use psi_parser::{
+    observer::TraceObserver,
+    result::{LineInfo, Span},
+};
+
+use super::*;
+
+#[test]
+fn trace() {
+    init();
+
+    let rules = rules! {
+        start {
+            (greeting "!")
+        }
+
+        greeting {
+            ("hi")
+            ("hello")
+        }
+    };
+
+    let expected = r#"start 1:1
+  greeting 1:1
+    - "hello" 1:1
+    backtrack to [1] 1:1
+    + "hi" 1:1-1:3
+  greeting matched 1:1-1:3 [1, 0]
+  + "!" 1:3-1:4
+start matched 1:1-1:4 [0, 0, 0]
+"#;
+
+    // `default` includes the terminals too, like `new`
+    for mut trace in [TraceObserver::new(), TraceObserver::default()] {
+        rules
+            .parse_entire_with("start", "hi!", ParseOptions::new().observer(&mut trace))
+            .expect("Should be parsed");
+
+        assert_eq!(expected, trace.output());
+    }
+}
+
+#[test]
+fn balanced_events() {
+    init();
+
+    #[derive(Default)]
+    struct Counter {
+        depth: isize,
+        max_depth: isize,
+        exits: Vec<(String, bool)>,
+        backtracks: usize,
+        transformers: usize,
+    }
+
+    impl ParseObserver for Counter {
+        fn rule_enter(&mut self, _rule: &str, _at: LineInfo) {
+            self.depth += 1;
+            self.max_depth = self.max_depth.max(self.depth);
+        }
+
+        fn rule_exit(&mut self, rule: &str, _path: &[usize], _span: Span, success: bool) {
+            self.depth -= 1;
+            self.exits.push((rule.to_owned(), success));
+        }
+
+        fn backtrack(&mut self, _rule: &str, _path: &[usize], _at: LineInfo) {
+            self.backtracks += 1;
+        }
+
+        fn transformer(
+            &mut self,
+            _rule: &str,
+            _path: &[usize],
+            _span: Span,
+            _elapsed: std::time::Duration,
+        ) {
+            self.transformers += 1;
+        }
+    }
+
+    let rules = rules! {
+        expr {
+            ("x") => |_, _| 1.into_value();
+            (expr "+" "x") => |v, _| (*v(0).downcast::<i32>().unwrap() + 1).into_value();
+        }
+    };
+
+    let mut counter = Counter::default();
+
+    let result =
+        rules.parse_entire_with("expr", "x+x+x", ParseOptions::new().observer(&mut counter));
+
+    assert_eq!(
+        Some(&3),
+        result.expect("Should be parsed").downcast_ref::<i32>()
+    );
+    assert_eq!(0, counter.depth);
+    assert_eq!(3, counter.transformers);
+    assert!(counter.max_depth >= 2);
+    assert_eq!(Some(&("expr".to_owned(), true)), counter.exits.last());
+    // the last attempt to grow `expr` fails
+    assert!(counter.exits.contains(&("expr".to_owned(), false)));
+
+    let mut counter = Counter::default();
+
+    let result = rules.parse_entire_with("expr", "x+y", ParseOptions::new().observer(&mut counter));
+
+    assert!(result.is_err());
+    assert_eq!(0, counter.depth);
+}
+
+#[test]
+#[cfg(feature = "included_parsers")]
+fn chrome_trace() {
+    use psi_parser::observer::ChromeTraceObserver;
+    use rules::json::Json;
+
+    init();
+
+    let mut chrome = ChromeTraceObserver::new();
+
+    rules::JsonRules
+        .parse_entire_with(
+            "start",
+            r#"{"a": [1, 2, "\"quoted\""]}"#,
+            ParseOptions::new().observer(&mut chrome),
+        )
+        .expect("Should be parsed");
+
+    let json = chrome.to_json();
+
+    // The trace itself should be valid JSON
+    let trace = rules::JsonRules
+        .parse_entire("start", &json)
+        .expect("Trace should be valid JSON");
+
+    match trace.downcast_ref::<Json>() {
+        Some(Json::Object(object)) => match object.get("traceEvents") {
+            Some(Json::Array(events)) => assert!(!events.is_empty()),
+            _ => panic!("traceEvents should be an array"),
+        },
+        _ => panic!("Trace should be an object"),
+    }
+}
+
+#[test]
+fn profiler() {
+    use psi_parser::observer::Profiler;
+
+    init();
+
+    let rules = rules! {
+        start {
+            (x "c")
+            (y)
+        }
+
+        x { ("a" "b") }
+        y { ("a" "b" "d") }
+    };
+
+    let mut profiler = Profiler::new();
+
+    for _ in 0..2 {
+        rules
+            .parse_entire_with("start", "abd", ParseOptions::new().observer(&mut profiler))
+            .expect("Should be parsed");
+    }
+
+    let report = profiler.report();
+
+    let rule = |name: &str| {
+        report
+            .rules
+            .iter()
+            .find(|rule| rule.rule == name)
+            .cloned()
+            .unwrap()
+    };
+
+    let start = rule("start");
+    assert_eq!(2, start.invocations);
+    assert_eq!(2, start.successes);
+    assert_eq!(6, start.consumed);
+    // "ab" is read twice on every parse
+    assert_eq!(4, start.rescanned);
+
+    let x = rule("x");
+    assert_eq!(
+        (2, 2, 0, 4),
+        (x.invocations, x.successes, x.failures, x.consumed)
+    );
+
+    let y = rule("y");
+    assert_eq!(
+        (2, 2, 0, 6),
+        (y.invocations, y.successes, y.failures, y.consumed)
+    );
+
+    // rules that backtracked the most come first
+    assert_eq!("start", report.rules[0].rule);
+
+    let failed = report
+        .alternatives
+        .iter()
+        .find(|alternative| alternative.rule == "start" && alternative.path == [0])
+        .unwrap();
+    assert_eq!(2, failed.failures);
+
+    assert!(report.to_string().contains("rescanned"));
+}
+
+#[test]
+fn coverage() {
+    use psi_parser::observer::{Coverage, CoverageStatus};
+
+    init();
+
+    let rules = rules! {
+        start {
+            (x "c")
+            (y)
+            (z)
+        }
+
+        x { ("a" "b") }
+        y { ("a" "b" "d") }
+        z { ("e") }
+
+        expr {
+            ("1")
+            ("2")
+            (expr "+" "1")
+            (expr "-" "1")
+        }
+    };
+
+    let mut coverage = Coverage::new();
+
+    for (rule, input) in [("start", "abd"), ("start", "abc"), ("expr", "1+1+1")] {
+        log::debug!("input = \"{input}\"");
+
+        rules
+            .parse_entire_with(rule, input, ParseOptions::new().observer(&mut coverage))
+            .expect("Should be parsed");
+    }
+
+    let report = coverage.report(&rules);
+
+    log::debug!("{report}");
+
+    let status = |rule: &str, alternative: &str| {
+        report
+            .alternatives
+            .iter()
+            .find(|a| a.rule == rule && a.alternative == alternative)
+            .map(|a| (a.status, a.successes))
+            .unwrap()
+    };
+
+    assert_eq!((CoverageStatus::Matched, 1), status("start", "(x \"c\")"));
+    assert_eq!((CoverageStatus::Matched, 1), status("start", "(y)"));
+    assert_eq!((CoverageStatus::Unreached, 0), status("start", "(z)"));
+    assert_eq!((CoverageStatus::Unreached, 0), status("z", "(\"e\")"));
+    assert_eq!((CoverageStatus::Matched, 1), status("expr", "(\"1\")"));
+    assert_eq!((CoverageStatus::Unreached, 0), status("expr", "(\"2\")"));
+    assert_eq!(
+        (CoverageStatus::Matched, 2),
+        status("expr", "(expr \"+\" \"1\")")
+    );
+    assert_eq!(
+        (CoverageStatus::Failed, 0),
+        status("expr", "(expr \"-\" \"1\")")
+    );
+
+    assert_eq!(6, report.count(CoverageStatus::Matched));
+    assert_eq!(3, report.count(CoverageStatus::Unreached));
+
+    assert!(report.to_string().contains("never reached"));
+    assert!(report
+        .to_html()
+        .contains("<td>(expr &quot;+&quot; &quot;1&quot;)</td>"));
+}
@@ -0,0 +1,188 @@
+use super::*;
+
+#[test]
+fn ordered() {
+    init();
+
+    let heuristic = rules! {
+        start {
+            ("a") => |_, _| "a".to_owned().into_value();
+            ("ab") => |_, _| "ab".to_owned().into_value();
+        }
+    };
+
+    let ordered = rules! {
+        #[policy(ordered)]
+        start {
+            ("a") => |_, _| "a".to_owned().into_value();
+            ("ab") => |_, _| "ab".to_owned().into_value();
+        }
+    };
+
+    assert_eq!(ChoicePolicy::Heuristic, heuristic.policy("start"));
+    assert_eq!(ChoicePolicy::Ordered, ordered.policy("start"));
+
+    // The heuristic tries the longer literal first
+    assert_eq!(
+        Some(&"ab".to_owned()),
+        heuristic
+            .parse("start", "ab")
+            .expect("Should be parsed")
+            .downcast_ref()
+    );
+
+    // Ordered choice takes the first alternative that matches
+    assert_eq!(
+        Some(&"a".to_owned()),
+        ordered
+            .parse("start", "ab")
+            .expect("Should be parsed")
+            .downcast_ref()
+    );
+
+    assert!(ordered.parse_entire("start", "ab").is_err());
+}
+
+#[test]
+fn ordered_shared_prefix() {
+    init();
+
+    let rules = rules! {
+        #[policy(ordered)]
+        start {
+            ("x" "y") => |_, _| 0.into_value();
+            ("z") => |_, _| 1.into_value();
+            ("x" "z") => |_, _| 2.into_value();
+            ()  => |_, _| 3.into_value();
+            ("y") => |_, _| 4.into_value();
+        }
+    };
+
+    let inputs = [("xy", 0), ("z", 1), ("xz", 2), ("", 3), ("y", 3)];
+
+    for (input, expected_result) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(
+            Some(&expected_result),
+            rules
+                .parse("start", input)
+                .expect("Should be parsed")
+                .downcast_ref::<i32>()
+        );
+    }
+}
+
+#[test]
+fn longest() {
+    init();
+
+    let rules = rules! {
+        #[policy(longest)]
+        word {
+            (x) => |_, _| "x".to_owned().into_value();
+            (xy) => |_, _| "xy".to_owned().into_value();
+            (xyz) => |_, _| "xyz".to_owned().into_value();
+            (xy_) => |_, _| "xy_".to_owned().into_value();
+        }
+
+        x { ("x") }
+        xy { ("x" "y") }
+        xyz { ("x" "y" "z") }
+        xy_ { ("x" "y") }
+    };
+
+    // Without the policy, `x` is tried first and wins
+    let heuristic = rules! {
+        #[import (rules.clone())]
+
+        start {
+            (x) => |_, _| "x".to_owned().into_value();
+            (xy) => |_, _| "xy".to_owned().into_value();
+        }
+    };
+
+    assert!(heuristic.parse_entire("start", "xy").is_err());
+
+    let inputs = [("x", "x"), ("xy", "xy"), ("xyz", "xyz")];
+
+    for (input, expected_result) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(
+            Some(&expected_result.to_owned()),
+            rules
+                .parse_entire("word", input)
+                .expect("Should be parsed")
+                .downcast_ref::<String>()
+        );
+    }
+
+    assert!(rules.parse_entire("word", "xyy").is_err());
+}
+
+#[test]
+fn longest_nested() {
+    init();
+
+    let rules = rules! {
+        start {
+            ("(" number ")") => |v, _| v(1);
+            (number "!") => |v, _| (*v(0).downcast::<usize>().unwrap() * 10).into_value();
+        }
+
+        #[policy(longest)]
+        number {
+            ("1") => |_, _| 1_usize.into_value();
+            ("1" "2") => |_, _| 12_usize.into_value();
+            ("1" "2" "3") => |_, _| 123_usize.into_value();
+        }
+    };
+
+    let inputs = [("(1)", 1), ("(12)", 12), ("(123)", 123), ("12!", 120)];
+
+    for (input, expected_result) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(
+            Some(&expected_result),
+            rules
+                .parse_entire("start", input)
+                .expect("Should be parsed")
+                .downcast_ref::<usize>()
+        );
+    }
+}
+
+#[test]
+fn longest_left_recursion() {
+    init();
+
+    let rules = rules! {
+        #[policy(longest)]
+        expr {
+            (expr "+" term) => |v, _| (*v(0).downcast::<usize>().unwrap() + *v(2).downcast::<usize>().unwrap()).into_value();
+            (term)
+        }
+
+        #[policy(longest)]
+        term {
+            ("1") => |_, _| 1_usize.into_value();
+            ("1" "0") => |_, _| 10_usize.into_value();
+        }
+    };
+
+    let inputs = [("1", 1), ("10", 10), ("10+1", 11), ("1+10+10", 21)];
+
+    for (input, expected_result) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(
+            Some(&expected_result),
+            rules
+                .parse_entire("expr", input)
+                .expect("Should be parsed")
+                .downcast_ref::<usize>()
+        );
+    }
+}
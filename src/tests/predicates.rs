@@ -0,0 +1,148 @@
+use super::*;
+
+#[test]
+fn predicate() {
+    init();
+
+    let rules = rules! {
+        start {
+            (byte)
+            (word)
+        }
+
+        byte {
+            (number (? |v| *v[0].downcast_ref::<u32>().unwrap() < 256))
+                => |v, _| (*v(0).downcast::<u32>().unwrap() as u8).into_value();
+        }
+
+        word {
+            (number) => |v, _| (*v(0).downcast::<u32>().unwrap() as u16).into_value();
+        }
+
+        number {
+            (digit) => |v, _| v(0);
+            (number digit) => |v, _| {
+                (*v(0).downcast::<u32>().unwrap() * 10 + *v(1).downcast::<u32>().unwrap()).into_value()
+            };
+        }
+
+        digit {
+            ("0") => |_, _| 0_u32.into_value();
+            ("1") => |_, _| 1_u32.into_value();
+            ("2") => |_, _| 2_u32.into_value();
+            ("3") => |_, _| 3_u32.into_value();
+            ("4") => |_, _| 4_u32.into_value();
+            ("5") => |_, _| 5_u32.into_value();
+            ("6") => |_, _| 6_u32.into_value();
+            ("7") => |_, _| 7_u32.into_value();
+            ("8") => |_, _| 8_u32.into_value();
+            ("9") => |_, _| 9_u32.into_value();
+        }
+    };
+
+    let value = rules
+        .parse_entire("start", "255")
+        .expect("Should be parsed");
+    assert_eq!(Some(&255_u8), value.downcast_ref());
+
+    // The predicate fails, so the next alternative is tried
+    let value = rules
+        .parse_entire("start", "256")
+        .expect("Should be parsed");
+    assert_eq!(Some(&256_u16), value.downcast_ref());
+
+    match rules.parse_entire("byte", "256") {
+        Err(ParseError::PredicateFailed {
+            current_rule, pos, ..
+        }) => {
+            assert_eq!("byte", current_rule);
+            assert_eq!(3, pos);
+        }
+        result => panic!("Expected the predicate to fail, got {result:?}"),
+    }
+}
+
+#[test]
+fn predicate_value() {
+    init();
+
+    let rules = rules! {
+        start {
+            ("a" (? |_| true) "b")
+        }
+
+        with_transformer {
+            ("a" (? |_| true) "b") => |v, _| v(2);
+        }
+    };
+
+    // Predicates are left out of the value of rules without a transformer
+    let value = rules.parse_entire("start", "ab").expect("Should be parsed");
+    let values = value
+        .downcast_ref::<Vec<ParseValue>>()
+        .expect("Should be a Vec");
+
+    assert_eq!(2, values.len());
+
+    // but still keep their place in the values given to a transformer
+    let value = rules
+        .parse_entire("with_transformer", "ab")
+        .expect("Should be parsed");
+
+    assert_eq!(Some(&Token::from("b")), value.downcast_ref());
+}
+
+#[test]
+fn predicate_context() {
+    init();
+
+    let rules = rules! {
+        start {
+            () => |_, _| Vec::<String>::new().into_value();
+            (start item) => |v, _| {
+                let mut items = v(0).downcast::<Vec<String>>().unwrap();
+                items.push(*v(1).downcast::<String>().unwrap());
+
+                items
+            };
+        }
+
+        item {
+            (keyword)
+            (name)
+        }
+
+        keyword {
+            (word (? mut |v, keywords: &mut Vec<String>| keywords.contains(v[0].downcast_ref::<String>().unwrap())))
+                => |v, _| format!("keyword {}", v(0).downcast::<String>().unwrap()).into_value();
+        }
+
+        name {
+            (word) => |v, _| format!("name {}", v(0).downcast::<String>().unwrap()).into_value();
+        }
+
+        word {
+            (letter " ") => |v, _| v(0).downcast::<Token>().unwrap().to_string().into_value();
+        }
+
+        letter {
+            ("a")
+            ("b")
+            ("c")
+        }
+    };
+
+    let mut keywords = vec!["b".to_owned()];
+
+    let value = rules
+        .parse_entire_with_context("start", "a b c ", &mut keywords)
+        .expect("Should be parsed");
+
+    let values = value
+        .downcast_ref::<Vec<String>>()
+        .expect("Should be a Vec");
+
+    log::debug!("values = {values:?}");
+
+    assert_eq!(&vec!["name a", "keyword b", "name c"], values);
+}
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use psi_parser::observer::Coverage;
+
 use super::*;
 
 #[test]
@@ -52,6 +54,36 @@ fn integer() {
                 .downcast_ref::<isize>()
         )
     }
+
+    let min = isize::MIN.to_string();
+    let max = isize::MAX.to_string();
+
+    for (input, expected_result) in [(&min, isize::MIN), (&max, isize::MAX)] {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(
+            Some(&expected_result),
+            rules::Integer
+                .parse_entire("integer", input.as_str())
+                .expect("Should be parsed")
+                .downcast_ref::<isize>()
+        )
+    }
+
+    // Out of range values are rejected instead of overflowing
+    let too_large = [
+        (isize::MAX as usize + 1).to_string(),
+        format!("-{}", isize::MIN.unsigned_abs() as u128 + 1),
+        format!("{}0", usize::MAX),
+    ];
+
+    for input in too_large {
+        log::debug!("input = \"{input}\"");
+
+        assert!(rules::Integer
+            .parse_entire("integer", input.as_str())
+            .is_err());
+    }
 }
 
 #[test]
@@ -244,86 +276,1533 @@ fn json() {
         ),
     )];
 
+    let mut coverage = Coverage::new();
+
     for (input, expected_result) in inputs {
         log::debug!("input = \"{input}\"");
 
         assert_eq!(
             Some(&expected_result),
             rules::JsonRules
-                .parse_entire("start", input)
+                .parse_entire_with("start", input, ParseOptions::new().observer(&mut coverage))
                 .expect("Should be parsed")
                 .downcast_ref::<Json>()
         )
     }
+
+    // Shows which alternatives of JsonRules the inputs above don't exercise
+    log::info!("{}", coverage.report(&Rules::from(rules::JsonRules)));
+}
+
+#[test]
+fn json_serialize() {
+    use rules::json::{Json, JsonFormat};
+
+    init();
+
+    let rules = Rules::from(rules::JsonRules);
+
+    let parse = |input: &str| {
+        *rules
+            .parse_entire("start", input)
+            .expect("Should be parsed")
+            .downcast::<Json>()
+            .unwrap()
+    };
+
+    // Compact output of compact input is the same text, including the order of the keys
+    let inputs = [
+        r#"{"z":1,"a":[true,false,null],"m":{"y":"","b":{}}}"#,
+        r#"["\"\\\/\b\f\n\r\t\u0001","π€𝄞",-0.5,1e300,1e-7,12,[]]"#,
+    ];
+
+    for input in inputs {
+        log::debug!("input = \"{input}\"");
+
+        let json = parse(input);
+
+        assert_eq!(json, parse(&json.to_string()));
+        assert_eq!(json, parse(&json.to_string_pretty()));
+        assert_eq!(
+            json,
+            parse(&json.format(&JsonFormat::new().ascii().sort_keys()))
+        );
+    }
+
+    assert_eq!(
+        r#"{"z":1,"a":[true,false,null],"m":{"y":"","b":{}}}"#,
+        parse(inputs[0]).to_string()
+    );
+    assert_eq!(
+        r#"["\"\\/\b\f\n\r\t\u0001","π€𝄞",-0.5,1e300,1e-7,12,[]]"#,
+        parse(inputs[1]).to_string()
+    );
+    assert_eq!(
+        r#""\u03c0\u20ac\ud834\udd1e""#,
+        Json::from("π€𝄞").format(&JsonFormat::new().ascii())
+    );
+
+    let expected = r#"{
+    "a": [
+        true,
+        false,
+        null
+    ],
+    "m": {
+        "b": {},
+        "y": ""
+    },
+    "z": 1
+}"#;
+
+    assert_eq!(
+        expected,
+        parse(inputs[0]).format(&JsonFormat::new().indent("    ").sort_keys())
+    );
+    assert_eq!(
+        "{\n  \"a\": 1\n}",
+        format!("{:#}", parse(r#"{"a":1}"#))
+    );
+    assert_eq!("[null]", Json::from(vec![f64::NAN]).to_string());
+}
+
+#[test]
+fn json_access() {
+    use rules::json::Json;
+
+    init();
+
+    let json = *rules::JsonRules
+        .parse_entire(
+            "start",
+            r#"{"servers": [{"name": "a", "port": 80}, {"name": "b"}], "a/b": {"m~n": true}, "": 0}"#,
+        )
+        .expect("Should be parsed")
+        .downcast::<Json>()
+        .unwrap();
+
+    assert_eq!(Some("a"), json["servers"][0]["name"].as_str());
+    assert_eq!(Some(80), json["servers"][0]["port"].as_i64());
+    assert!(json["servers"][1]["port"].is_null());
+    assert!(json["missing"][3]["anything"].is_null());
+    assert_eq!(None, json.get("missing"));
+    assert_eq!(Some(2), json["servers"].as_array().map(Vec::len));
+
+    let pointers = [
+        ("", Some(&json)),
+        ("/servers/1/name", Some(&json["servers"][1]["name"])),
+        ("/a~1b/m~0n", Some(&Json::Boolean(true))),
+        ("/", Some(&Json::from(0))),
+        ("/servers/01", None),
+        ("/servers/2", None),
+        ("/servers/-", None),
+        ("servers", None),
+    ];
+
+    for (pointer, expected) in pointers {
+        log::debug!("input = \"{pointer}\"");
+
+        assert_eq!(expected, json.pointer(pointer));
+    }
+
+    let mut json = json;
+
+    json["servers"][1] = Json::from("replaced");
+    *json.pointer_mut("/a~1b/m~0n").unwrap() = Json::Null;
+
+    assert_eq!(
+        r#"{"servers":[{"name":"a","port":80},"replaced"],"a/b":{"m~n":null},"":0}"#,
+        json.to_string()
+    );
+}
+
+#[test]
+fn json_convert() {
+    use rules::json::{Json, JsonConversionError, Map};
+    use std::collections::BTreeMap;
+
+    init();
+
+    let json = Json::from(
+        [
+            ("name".to_owned(), Json::from("psi")),
+            ("version".to_owned(), Json::from(vec![0, 2, 7])),
+            ("license".to_owned(), Json::from(None::<String>)),
+        ]
+        .into_iter()
+        .collect::<Map>(),
+    );
+
+    assert_eq!(
+        r#"{"name":"psi","version":[0,2,7],"license":null}"#,
+        json.to_string()
+    );
+
+    assert_eq!(Ok("psi".to_owned()), String::try_from(json["name"].clone()));
+    assert_eq!(
+        Ok(vec![0u8, 2, 7]),
+        Vec::<u8>::try_from(json["version"].clone())
+    );
+    assert_eq!(Ok(()), <()>::try_from(json["license"].clone()));
+    assert_eq!(
+        Err(JsonConversionError::WrongType {
+            expected: "a string",
+            found: "an array"
+        }),
+        String::try_from(json["version"].clone())
+    );
+    assert_eq!(
+        Err(JsonConversionError::NotAnInteger {
+            number: 1.5,
+            integer_type: "i32"
+        }),
+        i32::try_from(Json::from(1.5))
+    );
+    assert_eq!(
+        Err(JsonConversionError::NotAnInteger {
+            number: 300.0,
+            integer_type: "u8"
+        }),
+        u8::try_from(Json::from(300))
+    );
+
+    let map = BTreeMap::<String, Json>::try_from(json.clone()).unwrap();
+    assert_eq!(vec!["license", "name", "version"], map.keys().collect::<Vec<_>>());
+
+    // Objects are equal regardless of the order of their members
+    assert_eq!(json, Json::from(map));
+}
+
+// The files in `json_test_suite` are named like in JSONTestSuite: `y_` files must be accepted,
+// `n_` files rejected and `i_` files may be either, as long as the parser doesn't panic
+#[test]
+fn json_test_suite() {
+    init();
+
+    let rules = Rules::from(rules::JsonRules);
+
+    let mut paths = std::fs::read_dir(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/src/tests/json_test_suite"
+    ))
+    .expect("Should be read")
+    .map(|entry| entry.unwrap().path())
+    .collect::<Vec<_>>();
+
+    paths.sort();
+
+    let mut wrong = Vec::new();
+
+    for path in paths {
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let bytes = std::fs::read(&path).unwrap();
+
+        log::debug!("input = \"{name}\"");
+
+        // Text that isn't UTF-8 can't be given to the parser
+        let accepted = std::str::from_utf8(&bytes)
+            .is_ok_and(|input| rules.parse_entire("start", input).is_ok());
+
+        match (&name[..2], accepted) {
+            ("y_", false) | ("n_", true) => wrong.push(name),
+            _ => {}
+        }
+    }
+
+    assert!(wrong.is_empty(), "Wrongly accepted or rejected: {wrong:?}");
+}
+
+#[test]
+fn json5() {
+    use rules::json::Json;
+
+    init();
+
+    let rules = Rules::from(rules::Json5Rules);
+
+    let parse = |input: &str| {
+        rules
+            .parse_entire("start", input)
+            .map(|json| *json.downcast::<Json>().unwrap())
+    };
+
+    let config = parse(
+        r#"
+        // Server configuration
+        {
+            name: 'psi',
+            $schema: "config.json",
+            ports: [0x1F90, +443, .5, 5., -Infinity,],
+            /* multi
+               line */
+            "quoted": 'It\'s "fine"\x41\u00e9\
+ continued',
+        }
+        "#,
+    )
+    .expect("Should be parsed");
+
+    assert_eq!(Some("psi"), config["name"].as_str());
+    assert_eq!(Some("config.json"), config["$schema"].as_str());
+    assert_eq!(
+        Json::from(vec![8080.0, 443.0, 0.5, 5.0, f64::NEG_INFINITY]),
+        config["ports"]
+    );
+    assert_eq!(
+        Some("It's \"fine\"A\u{e9} continued"),
+        config["quoted"].as_str()
+    );
+    assert!(parse("NaN").unwrap().as_f64().unwrap().is_nan());
+
+    let inputs = [
+        ("[1, 2,]", true),
+        ("{a: 1, b_2: [],}", true),
+        ("'\\v\\0\\q'", true),
+        ("0XfF", true),
+        ("[1,,]", false),
+        ("[,]", false),
+        ("{1a: 1}", false),
+        ("01", false),
+        (".", false),
+        ("'unterminated", false),
+        ("/* unterminated", false),
+        ("\"line\nbreak\"", false),
+        ("'\\1'", false),
+    ];
+
+    for (input, expected) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(expected, parse(input).is_ok());
+    }
+
+    assert_eq!(Json::from("\u{b}\0q"), parse("'\\v\\0\\q'").unwrap());
+    assert_eq!(
+        Json::from("\u{8}\u{c}/A\u{1F600}A"),
+        parse("\"\\b\\f\\/\\u0041\\ud83d\\ude00\\x41\"").unwrap()
+    );
+    assert_eq!(Json::from(255), parse("0XfF").unwrap());
+
+    // JSON5 is a superset of JSON, and gives the same values
+    let json_rules = Rules::from(rules::JsonRules);
+
+    let paths = std::fs::read_dir(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/src/tests/json_test_suite"
+    ))
+    .expect("Should be read")
+    .map(|entry| entry.unwrap().path())
+    .filter(|path| path.file_name().unwrap().to_string_lossy().starts_with("y_"));
+
+    for path in paths {
+        let Ok(input) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+
+        log::debug!("input = \"{}\"", path.display());
+
+        assert_eq!(
+            json_rules.parse_entire("start", &input).ok().map(|json| *json.downcast::<Json>().unwrap()),
+            parse(&input).ok()
+        );
+    }
+}
+
+#[test]
+fn jsonc() {
+    use rules::json::Json;
+
+    init();
+
+    let rules = Rules::from(rules::JsoncRules);
+
+    let json = rules
+        .parse_entire(
+            "start",
+            "// settings\n{\n  \"a\": 1, // one\n  /* two */ \"b\": [2]\n}\n",
+        )
+        .expect("Should be parsed");
+
+    assert_eq!(
+        Json::Object([("a", Json::from(1)), ("b", Json::from(vec![2]))].into_iter().collect()),
+        *json.downcast::<Json>().unwrap()
+    );
+
+    let inputs = [
+        ("[1 /* , 2 */]", true),
+        ("[1, 2] // end", true),
+        ("[1, 2,]", false),
+        ("{a: 1}", false),
+        ("'single'", false),
+        ("[1 /* unterminated ]", false),
+    ];
+
+    for (input, expected) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(expected, rules.parse_entire("start", input).is_ok());
+    }
+
+    // None of the rest of JSON5 comes along with its comments
+    assert!(!rules
+        .rule_names()
+        .iter()
+        .any(|rule| rule.starts_with("json5::")));
 }
 
 #[test]
 fn xml() {
-    use rules::simple_xml::Xml;
+    use rules::simple_xml::{Attributes, Element, Xml};
     init();
 
     let inputs = [
         (
-            "<node attribute=id attribute2='string&amp;'>text <self_closing/> </node>",
-            Ok(Xml::Node(
-                "node".to_owned(),
-                {
-                    let mut map = HashMap::new();
-                    map.insert("attribute".to_owned(), "id".to_string());
-                    map.insert("attribute2".to_owned(), "string&".to_string());
-                    map
-                },
-                vec![
+            "<node attribute=\"id\" attribute2='string&amp;'>text <self_closing/> </node>",
+            Ok(Xml::Element(Element {
+                name: "node".to_owned(),
+                namespace: None,
+                attributes: [("attribute", "id"), ("attribute2", "string&")]
+                    .into_iter()
+                    .collect(),
+                children: vec![
                     Xml::Text("text ".to_owned()),
-                    Xml::Node("self_closing".to_owned(), HashMap::new(), Vec::new()),
+                    Xml::Element(Element::new("self_closing")),
                     Xml::Text(" ".to_owned()),
                 ],
-            )),
+            })),
         ),
         (
-            "<xml/>",
-            Err(
-                /*ParseError::TransformerError {
-                    current_rule: "node".to_owned(),
-                    pos: 6,
-                    row: 1,
-                    col: 7,
-                    error: Box::new(XmlParseError::IllegalTagName {
-                        tag: "xml".to_owned(),
-                    }),
-                }
-                .to_string()*/
-                (),
-            ),
+            "<?xml version='1.0'?>\n<xml/>\n",
+            Ok(Xml::Element(Element::new("xml"))),
         ),
         (
-            "<a><b/></c>",
-            Err(
-                /*ParseError::TransformerError {
-                    current_rule: "node".to_owned(),
-                    pos: 11,
-                    row: 1,
-                    col: 12,
-                    error: Box::new(XmlParseError::NonMatchingTagNames {
-                        start_tag: "a".to_owned(),
-                        end_tag: "c".to_owned(),
-                    }),
-                }
-                .to_string()*/
-                (),
-            ),
+            "<a>&#x41;&#66;<![CDATA[<c>]]><!-- comment --><?target data?></a>",
+            Ok(Xml::Element(Element {
+                name: "a".to_owned(),
+                namespace: None,
+                attributes: Attributes::new(),
+                children: vec![
+                    Xml::Text("AB".to_owned()),
+                    Xml::CData("<c>".to_owned()),
+                    Xml::Comment(" comment ".to_owned()),
+                    Xml::ProcessingInstruction {
+                        target: "target".to_owned(),
+                        data: "data".to_owned(),
+                    },
+                ],
+            })),
+        ),
+        (
+            "<a b='\tc\r\nd&#10;'/>",
+            Ok(Xml::Element(Element {
+                name: "a".to_owned(),
+                namespace: None,
+                attributes: [("b", " c d\n")].into_iter().collect(),
+                children: Vec::new(),
+            })),
         ),
+        ("<a><b/></c>", Err(())),
+        ("<node attribute=id/>", Err(())),
+        ("<a b='1' b='2'/>", Err(())),
+        ("<a>]]></a>", Err(())),
+        ("<a><!-- a -- b --></a>", Err(())),
+        ("<a>&#0;</a>", Err(())),
+        ("<a>&undeclared;</a>", Err(())),
+        ("<a/>\n<?xml version='1.0'?>", Err(())),
+        ("<a/><b/>", Err(())),
+        ("text", Err(())),
     ];
 
+    let mut coverage = Coverage::new();
+
     for (input, expected_result) in inputs {
         log::debug!("input = \"{input}\"");
 
         assert_eq!(
             expected_result,
             rules::XmlRules
-                .parse_entire("start", input)
+                .parse_entire_with("start", input, ParseOptions::new().observer(&mut coverage))
                 .map(|res| *res.downcast::<Xml>().unwrap())
                 .map_err(|_| ())
         )
     }
+
+    // Shows which alternatives of XmlRules the inputs above don't exercise
+    log::info!("{}", coverage.report(&Rules::from(rules::XmlRules)));
+}
+
+#[test]
+fn xml_document() {
+    use rules::simple_xml::{Doctype, Xml, XmlDeclaration, XmlDocument, XmlParseError};
+    init();
+
+    let input = r#"<?xml version="1.0" encoding="UTF-8" standalone='yes'?>
+<!-- before -->
+<!DOCTYPE greeting SYSTEM "greeting.dtd" [
+    <!ELEMENT greeting (#PCDATA)>
+    <!ATTLIST greeting lang CDATA "en>">
+    <!ENTITY name "World">
+    <!ENTITY message "Hello, &name;!">
+    <!ENTITY name "ignored">
+]>
+<greeting lang="&name;">&message;</greeting>
+<?after?>
+"#;
+
+    let document = *rules::XmlRules
+        .parse_entire("document", input)
+        .unwrap()
+        .downcast::<XmlDocument>()
+        .unwrap();
+
+    assert_eq!(
+        Some(XmlDeclaration {
+            version: "1.0".to_owned(),
+            encoding: Some("UTF-8".to_owned()),
+            standalone: Some(true),
+        }),
+        document.declaration
+    );
+    assert_eq!(
+        Some(Doctype {
+            name: "greeting".to_owned(),
+            public_id: None,
+            system_id: Some("greeting.dtd".to_owned()),
+            entities: [
+                ("name".to_owned(), "World".to_owned()),
+                (
+                    "message".to_owned(),
+                    "Hello, \u{FFFF}name\u{FFFF}!".to_owned()
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        }),
+        document.doctype
+    );
+    assert_eq!(vec![Xml::Comment(" before ".to_owned())], document.prolog);
+    assert_eq!(Some("World"), document.root.attribute("lang"));
+    assert_eq!("Hello, World!", document.root.text());
+    assert_eq!(
+        vec![Xml::ProcessingInstruction {
+            target: "after".to_owned(),
+            data: String::new(),
+        }],
+        document.epilog
+    );
+
+    let recursive = "<!DOCTYPE a [<!ENTITY a '&b;'><!ENTITY b '&a;'>]><a>&a;</a>";
+
+    assert!(rules::XmlRules.parse_entire("document", recursive).is_err());
+
+    // "Billion laughs": each entity refers to the previous one ten times
+    let mut laughs = "<!DOCTYPE a [<!ENTITY l0 'lol'>".to_owned();
+
+    for i in 1..10 {
+        let previous = format!("&l{};", i - 1).repeat(10);
+
+        laughs.push_str(&format!("<!ENTITY l{i} '{previous}'>"));
+    }
+
+    laughs.push_str("]><a>&l9;</a>");
+
+    // Few references to a long entity
+    let long = format!(
+        "<!DOCTYPE a [<!ENTITY l0 '{}'><!ENTITY l1 '{}'><!ENTITY l2 '{}'>]><a>&l2;</a>",
+        "lol".repeat(100_000),
+        "&l0;".repeat(10),
+        "&l1;".repeat(10)
+    );
+
+    for input in [laughs, long] {
+        let error = rules::XmlRules
+            .parse_entire("document", input.as_str())
+            .expect_err("Should not be parsed");
+
+        log::debug!("error = {error}");
+
+        match error {
+            ParseError::TransformerError { error, .. } => assert!(matches!(
+                error.downcast_ref::<XmlParseError>(),
+                Some(XmlParseError::EntityExpansionLimit { .. })
+            )),
+            error => panic!("Expected an XmlParseError, got {error:?}"),
+        }
+    }
+}
+
+#[test]
+fn xml_format() {
+    use rules::simple_xml::{Element, Xml, XmlDocument, XmlFormat};
+    init();
+
+    let parse = |input: &str| {
+        *rules::XmlRules
+            .parse_entire("start", input)
+            .unwrap()
+            .downcast::<Xml>()
+            .unwrap()
+    };
+
+    let inputs = [
+        "<a>text <b c='1' d=\"&quot;'\"/> &lt;&amp;&gt; </a>",
+        "<a xmlns='urn:a' xmlns:b='urn:b'><b:c b:d='&#9;&#10;&#13;'>&#13;</b:c></a>",
+        "<a><![CDATA[<b>&amp;</b>]]><!-- comment --><?target data?></a>",
+        "<a>\n  <b>\n    <c/>\n  </b>\n</a>",
+    ];
+
+    for input in inputs {
+        log::debug!("input = \"{input}\"");
+
+        let xml = parse(input);
+
+        assert_eq!(xml, parse(&xml.to_string()));
+    }
+
+    // Text that can't be written as it is
+    let mut element = Element::new("a");
+    element.attributes.insert("b", "\"<&>'\t\n\r");
+    element.children = vec![
+        Xml::Text("]]> < & \r\n".to_owned()),
+        Xml::Element(Element::new("c")),
+    ];
+
+    let xml = Xml::Element(element);
+
+    assert_eq!(
+        "<a b=\"&quot;&lt;&amp;>'&#9;&#10;&#13;\">]]&gt; &lt; &amp; &#13;\n<c/></a>",
+        xml.to_string()
+    );
+    assert_eq!(xml, parse(&xml.to_string()));
+
+    let xml = parse("<a b='c'><d><e/><!--f--></d><g>text <h/></g></a>");
+
+    assert_eq!(
+        "<a b=\"c\">\n  <d>\n    <e/>\n    <!--f-->\n  </d>\n  <g>text <h/></g>\n</a>",
+        format!("{xml:#}")
+    );
+    assert_eq!(
+        "<a b=\"c\">\n\t<d>\n\t\t<e></e>\n\t\t<!--f-->\n\t</d>\n\t<g>text <h></h></g>\n</a>",
+        xml.format(&XmlFormat::new().indent("\t").expand_empty())
+    );
+
+    let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE a PUBLIC "-//A//EN" "a.dtd" [<!ENTITY b "&#38;&#37;c"><!ENTITY d "&b;!">]>
+<!-- before -->
+<a>&d;</a>
+<?after?>"#;
+
+    let parse_document = |input: &str| {
+        *rules::XmlRules
+            .parse_entire("document", input)
+            .unwrap()
+            .downcast::<XmlDocument>()
+            .unwrap()
+    };
+
+    let document = parse_document(input);
+
+    assert_eq!(document, parse_document(&document.to_string()));
+    assert_eq!(document, parse_document(&format!("{document:#}")));
+    assert_eq!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE a PUBLIC "-//A//EN" "a.dtd" [
+  <!ENTITY b "&#38;&#37;c">
+  <!ENTITY d "&b;!">
+]>
+<!-- before -->
+<a>&amp;%c!</a>
+<?after?>"#,
+        format!("{document:#}")
+    );
+}
+
+#[test]
+fn xml_namespaces() {
+    use rules::simple_xml::{Xml, XMLNS_NAMESPACE, XML_NAMESPACE};
+    init();
+
+    let input = r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+    <use xlink:href="#shape" xml:lang="en"/>
+    <text xmlns="">plain</text>
+</svg>"##;
+
+    let Xml::Element(svg) = *rules::XmlRules
+        .parse_entire("start", input)
+        .unwrap()
+        .downcast::<Xml>()
+        .unwrap()
+    else {
+        panic!("The root is an element");
+    };
+
+    assert_eq!(Some("http://www.w3.org/2000/svg"), svg.namespace.as_deref());
+    assert_eq!(
+        Some(XMLNS_NAMESPACE),
+        svg.attributes.attribute("xmlns:xlink").unwrap().namespace.as_deref()
+    );
+
+    let children = svg.elements().collect::<Vec<_>>();
+    let (r#use, text) = (children[0], children[1]);
+
+    assert_eq!(Some("http://www.w3.org/2000/svg"), r#use.namespace.as_deref());
+    assert_eq!(
+        Some("http://www.w3.org/1999/xlink"),
+        r#use.attributes.attribute("xlink:href").unwrap().namespace.as_deref()
+    );
+    assert_eq!(
+        Some(XML_NAMESPACE),
+        r#use.attributes.attribute("xml:lang").unwrap().namespace.as_deref()
+    );
+    assert_eq!(None, text.namespace);
+    assert_eq!("text", text.local_name());
+
+    let errors = [
+        "<a:b/>",
+        "<a xmlns:a='urn:a'><b a:c='1' a:c='2'/></a>",
+        "<a xmlns:x='urn:a' xmlns:y='urn:a' x:c='1' y:c='2'/>",
+        "<a:b:c xmlns:a='urn:a'/>",
+    ];
+
+    for input in errors {
+        log::debug!("input = \"{input}\"");
+
+        assert!(rules::XmlRules.parse_entire("start", input).is_err());
+    }
+}
+
+#[test]
+fn html() {
+    use rules::simple_xml::{Attributes, Element, Xml};
+    init();
+
+    let inputs = [
+        (
+            "<!DOCTYPE html><p>One<p>Two<br>three</p>",
+            vec![
+                Xml::Element(Element {
+                    name: "p".to_owned(),
+                    namespace: None,
+                    attributes: Attributes::new(),
+                    children: vec![Xml::Text("One".to_owned())],
+                }),
+                Xml::Element(Element {
+                    name: "p".to_owned(),
+                    namespace: None,
+                    attributes: Attributes::new(),
+                    children: vec![
+                        Xml::Text("Two".to_owned()),
+                        Xml::Element(Element::new("br")),
+                        Xml::Text("three".to_owned()),
+                    ],
+                }),
+            ],
+        ),
+        (
+            "<ul><li>a<li>b <ol><li>c</ol></ul>",
+            vec![
+                Xml::Element(Element {
+                    name: "ul".to_owned(),
+                    namespace: None,
+                    attributes: Attributes::new(),
+                    children: vec![
+                        Xml::Element(Element {
+                            name: "li".to_owned(),
+                            namespace: None,
+                            attributes: Attributes::new(),
+                            children: vec![Xml::Text("a".to_owned())],
+                        }),
+                        Xml::Element(Element {
+                            name: "li".to_owned(),
+                            namespace: None,
+                            attributes: Attributes::new(),
+                            children: vec![
+                                Xml::Text("b ".to_owned()),
+                                Xml::Element(Element {
+                                    name: "ol".to_owned(),
+                                    namespace: None,
+                                    attributes: Attributes::new(),
+                                    children: vec![
+                                        Xml::Element(Element {
+                                            name: "li".to_owned(),
+                                            namespace: None,
+                                            attributes: Attributes::new(),
+                                            children: vec![Xml::Text("c".to_owned())],
+                                        }),
+                                    ],
+                                }),
+                            ],
+                        }),
+                    ],
+                }),
+            ],
+        ),
+        (
+            "<INPUT Type=text data-x=foo-bar disabled data-x='ignored'>",
+            vec![
+                Xml::Element(Element {
+                    name: "input".to_owned(),
+                    namespace: None,
+                    attributes: [("type", "text"), ("data-x", "foo-bar"), ("disabled", "")]
+                        .into_iter()
+                        .collect(),
+                    children: Vec::new(),
+                }),
+            ],
+        ),
+        (
+            "<script>if (a < b && c) document.write(\"</p>\")</script><style>p > a {}</style>",
+            vec![
+                Xml::Element(Element {
+                    name: "script".to_owned(),
+                    namespace: None,
+                    attributes: Attributes::new(),
+                    children: vec![
+                        Xml::Text("if (a < b && c) document.write(\"</p>\")".to_owned()),
+                    ],
+                }),
+                Xml::Element(Element {
+                    name: "style".to_owned(),
+                    namespace: None,
+                    attributes: Attributes::new(),
+                    children: vec![Xml::Text("p > a {}".to_owned())],
+                }),
+            ],
+        ),
+        (
+            "<p title=\"&quot;A&quot;\">&copy; &amp; &#x41;&#66; &unknown; &amp</p><title>&lt;b&gt;</title>",
+            vec![
+                Xml::Element(Element {
+                    name: "p".to_owned(),
+                    namespace: None,
+                    attributes: [("title", "\"A\"")].into_iter().collect(),
+                    children: vec![Xml::Text("© & AB &unknown; &amp".to_owned())],
+                }),
+                Xml::Element(Element {
+                    name: "title".to_owned(),
+                    namespace: None,
+                    attributes: Attributes::new(),
+                    children: vec![Xml::Text("<b>".to_owned())],
+                }),
+            ],
+        ),
+        (
+            "<table><tr><td>1<td>2<tr><td>3</table>",
+            vec![
+                Xml::Element(Element {
+                    name: "table".to_owned(),
+                    namespace: None,
+                    attributes: Attributes::new(),
+                    children: vec![
+                        Xml::Element(Element {
+                            name: "tr".to_owned(),
+                            namespace: None,
+                            attributes: Attributes::new(),
+                            children: vec![
+                                Xml::Element(Element {
+                                    name: "td".to_owned(),
+                                    namespace: None,
+                                    attributes: Attributes::new(),
+                                    children: vec![Xml::Text("1".to_owned())],
+                                }),
+                                Xml::Element(Element {
+                                    name: "td".to_owned(),
+                                    namespace: None,
+                                    attributes: Attributes::new(),
+                                    children: vec![Xml::Text("2".to_owned())],
+                                }),
+                            ],
+                        }),
+                        Xml::Element(Element {
+                            name: "tr".to_owned(),
+                            namespace: None,
+                            attributes: Attributes::new(),
+                            children: vec![
+                                Xml::Element(Element {
+                                    name: "td".to_owned(),
+                                    namespace: None,
+                                    attributes: Attributes::new(),
+                                    children: vec![Xml::Text("3".to_owned())],
+                                }),
+                            ],
+                        }),
+                    ],
+                }),
+            ],
+        ),
+        (
+            "</b>a < b<b><i>c</b><!-- d --><svg><path d='M 0'/></svg>",
+            vec![
+                Xml::Text("a < b".to_owned()),
+                Xml::Element(Element {
+                    name: "b".to_owned(),
+                    namespace: None,
+                    attributes: Attributes::new(),
+                    children: vec![
+                        Xml::Element(Element {
+                            name: "i".to_owned(),
+                            namespace: None,
+                            attributes: Attributes::new(),
+                            children: vec![Xml::Text("c".to_owned())],
+                        }),
+                    ],
+                }),
+                Xml::Comment(" d ".to_owned()),
+                Xml::Element(Element {
+                    name: "svg".to_owned(),
+                    namespace: None,
+                    attributes: Attributes::new(),
+                    children: vec![
+                        Xml::Element(Element {
+                            name: "path".to_owned(),
+                            namespace: None,
+                            attributes: [("d", "M 0")].into_iter().collect(),
+                            children: Vec::new(),
+                        }),
+                    ],
+                }),
+            ],
+        ),
+        ("", Vec::new()),
+    ];
+
+    for (input, expected) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(
+            expected,
+            *rules::HtmlRules
+                .parse_entire("start", input)
+                .unwrap()
+                .downcast::<Vec<Xml>>()
+                .unwrap()
+        );
+    }
+}
+
+#[test]
+fn toml() {
+    use rules::toml::{Date, Datetime, Offset, Table, Time, Toml};
+    init();
+
+    let parse = |input: &str| {
+        *rules::TomlRules
+            .parse_entire("start", input)
+            .unwrap()
+            .downcast::<Toml>()
+            .unwrap()
+    };
+
+    let manifest = parse(
+        r#"# A manifest
+[package]
+name = "psi-parser"
+version = "0.1.0"   # comment
+edition = '2021'
+authors = [
+    "A <a@example.com>", # first
+    "B",
+]
+
+[dependencies]
+derive_more = { version = "1", features = ["deref"] }
+log.version = "0.4"
+log.optional = true
+
+[dev-dependencies.env_logger]
+version = "0.11"
+
+[[bin]]
+name = "one"
+
+[[bin]]
+name = "two"
+path = "src/two.rs"
+"#,
+    );
+
+    assert_eq!(
+        Toml::Table(Table::from_iter([
+            (
+                "package",
+                Toml::Table(Table::from_iter([
+                    ("name", Toml::String("psi-parser".to_owned())),
+                    ("version", Toml::String("0.1.0".to_owned())),
+                    ("edition", Toml::String("2021".to_owned())),
+                    (
+                        "authors",
+                        Toml::Array(vec![
+                            Toml::String("A <a@example.com>".to_owned()),
+                            Toml::String("B".to_owned()),
+                        ])
+                    ),
+                ]))
+            ),
+            (
+                "dependencies",
+                Toml::Table(Table::from_iter([
+                    (
+                        "derive_more",
+                        Toml::Table(Table::from_iter([
+                            ("version", Toml::String("1".to_owned())),
+                            ("features", Toml::Array(vec![Toml::String("deref".to_owned())])),
+                        ]))
+                    ),
+                    (
+                        "log",
+                        Toml::Table(Table::from_iter([
+                            ("version", Toml::String("0.4".to_owned())),
+                            ("optional", Toml::Boolean(true)),
+                        ]))
+                    ),
+                ]))
+            ),
+            (
+                "dev-dependencies",
+                Toml::Table(Table::from_iter([(
+                    "env_logger",
+                    Toml::Table(Table::from_iter([("version", Toml::String("0.11".to_owned()))]))
+                )]))
+            ),
+            (
+                "bin",
+                Toml::Array(vec![
+                    Toml::Table(Table::from_iter([("name", Toml::String("one".to_owned()))])),
+                    Toml::Table(Table::from_iter([
+                        ("name", Toml::String("two".to_owned())),
+                        ("path", Toml::String("src/two.rs".to_owned())),
+                    ])),
+                ])
+            ),
+        ])),
+        manifest
+    );
+    assert_eq!(
+        Some("0.11"),
+        manifest
+            .get("dev-dependencies")
+            .and_then(|dependencies| dependencies.get("env_logger"))
+            .and_then(|env_logger| env_logger.get("version"))
+            .and_then(Toml::as_str)
+    );
+
+    let strings = parse(
+        "a = \"tab\\there \\u00e9\\U0001F600 \\\"q\\\"\"\n\
+         b = 'C:\\path'\n\
+         c = \"\"\"\nline 1\r\nline 2 \\\n    continued\"\"\"\"\n\
+         d = '''\n'raw' \\n'''''\n\
+         \"quoted key\" = \"\"\n\
+         'literal key' = ''\n",
+    );
+
+    assert_eq!(
+        Toml::Table(Table::from_iter([
+            ("a", Toml::String("tab\there é😀 \"q\"".to_owned())),
+            ("b", Toml::String("C:\\path".to_owned())),
+            ("c", Toml::String("line 1\nline 2 continued\"".to_owned())),
+            ("d", Toml::String("'raw' \\n''".to_owned())),
+            ("quoted key", Toml::String("".to_owned())),
+            ("literal key", Toml::String("".to_owned())),
+        ])),
+        strings
+    );
+
+    let numbers = parse(
+        "a = +99\nb = -17\nc = 1_000\nd = 0xDEAD_beef\ne = 0o755\nf = 0b1101\ng = 0\n\
+         h = 2.5\ni = -0.01\nj = 5e+22\nk = 6.626e-34\nl = 224_617.445_991\nm = -inf\nn = nan\n\
+         o = -9223372036854775808\n",
+    );
+
+    for (key, expected) in [
+        ("a", 99),
+        ("b", -17),
+        ("c", 1000),
+        ("d", 0xDEADBEEF),
+        ("e", 0o755),
+        ("f", 0b1101),
+        ("g", 0),
+        ("o", i64::MIN),
+    ] {
+        assert_eq!(Some(expected), numbers.get(key).and_then(Toml::as_integer));
+    }
+
+    for (key, expected) in [
+        ("h", 2.5),
+        ("i", -0.01),
+        ("j", 5e22),
+        ("k", 6.626e-34),
+        ("l", 224617.445991),
+        ("m", f64::NEG_INFINITY),
+    ] {
+        assert_eq!(Some(expected), numbers.get(key).and_then(Toml::as_float));
+    }
+
+    assert!(numbers.get("n").and_then(Toml::as_float).unwrap().is_nan());
+
+    let date = Some(Date { year: 1979, month: 5, day: 27 });
+    let time = |nanosecond| Some(Time { hour: 7, minute: 32, second: 0, nanosecond });
+
+    for (input, expected, text) in [
+        (
+            "1979-05-27T07:32:00Z",
+            Datetime { date, time: time(0), offset: Some(Offset::Z) },
+            "1979-05-27T07:32:00Z",
+        ),
+        (
+            "1979-05-27 07:32:00.999999-07:00",
+            Datetime { date, time: time(999_999_000), offset: Some(Offset::Custom { minutes: -420 }) },
+            "1979-05-27T07:32:00.999999-07:00",
+        ),
+        ("1979-05-27t07:32:00", Datetime { date, time: time(0), offset: None }, "1979-05-27T07:32:00"),
+        ("1979-05-27", Datetime { date, time: None, offset: None }, "1979-05-27"),
+        ("07:32:00.5", Datetime { date: None, time: time(500_000_000), offset: None }, "07:32:00.5"),
+    ] {
+        log::debug!("input = \"{input}\"");
+
+        let datetime = parse(&format!("a = {input} # comment"));
+        let datetime = datetime.get("a").and_then(Toml::as_datetime).unwrap();
+
+        assert_eq!(&expected, datetime);
+        assert_eq!(text, datetime.to_string());
+    }
+
+    // Tables can be defined in any order, and those of headers can have tables of dotted keys
+    let tables = parse(
+        "x.y = 1\n[a.b.c]\nd = 1\n[a]\nb.e = 2\n[fruit]\napple.color = 'red'\n\n[fruit.apple.texture]\nsmooth = true\n\
+         [[products]]\n[[products]]\nname = \"Nail\"\n[products.size]\nlength = 2\n  [ points ]  \n\
+         arrays = [ [ 1, 2 ], [\"a\", 1.5], [], { a = {} } ]\n",
+    );
+
+    assert_eq!(
+        Toml::Table(Table::from_iter([
+            ("x", Toml::Table(Table::from_iter([("y", Toml::Integer(1))]))),
+            (
+                "a",
+                Toml::Table(Table::from_iter([(
+                    "b",
+                    Toml::Table(Table::from_iter([
+                        ("c", Toml::Table(Table::from_iter([("d", Toml::Integer(1))]))),
+                        ("e", Toml::Integer(2)),
+                    ]))
+                )]))
+            ),
+            (
+                "fruit",
+                Toml::Table(Table::from_iter([(
+                    "apple",
+                    Toml::Table(Table::from_iter([
+                        ("color", Toml::String("red".to_owned())),
+                        (
+                            "texture",
+                            Toml::Table(Table::from_iter([("smooth", Toml::Boolean(true))]))
+                        ),
+                    ]))
+                )]))
+            ),
+            (
+                "products",
+                Toml::Array(vec![
+                    Toml::Table(Table::new()),
+                    Toml::Table(Table::from_iter([
+                        ("name", Toml::String("Nail".to_owned())),
+                        ("size", Toml::Table(Table::from_iter([("length", Toml::Integer(2))]))),
+                    ])),
+                ])
+            ),
+            (
+                "points",
+                Toml::Table(Table::from_iter([(
+                    "arrays",
+                    Toml::Array(vec![
+                        Toml::Array(vec![Toml::Integer(1), Toml::Integer(2)]),
+                        Toml::Array(vec![Toml::String("a".to_owned()), Toml::Float(1.5)]),
+                        Toml::Array(vec![]),
+                        Toml::Table(Table::from_iter([("a", Toml::Table(Table::new()))])),
+                    ])
+                )]))
+            ),
+        ])),
+        tables
+    );
+
+    assert_eq!(Toml::Table(Table::new()), parse(""));
+    assert_eq!(
+        Toml::Table(Table::from_iter([("a", Toml::Integer(1))])),
+        parse("\r\n  # only a comment\r\na = 1\r\n")
+    );
+}
+
+#[test]
+fn toml_errors() {
+    use rules::toml::TomlParseError;
+    init();
+
+    let errors = [
+        "a = ",
+        "a = 1 b = 2",
+        "= 1",
+        "a = 01",
+        "a = 1__0",
+        "a = +0x10",
+        "a = 1.",
+        "a = .5",
+        "a = \"unterminated",
+        "a = \"new\nline\"",
+        "a = \"\\q\"",
+        "a = \"\\uD800\"",
+        "a = { b = 1, }",
+        "a = { b = 1,\n c = 2 }",
+        "a = [1 2]",
+        "[a",
+        "[[a]",
+        "a = 1979-05-27T07:32",
+        "a = 1979-05-27T07:32:00+07",
+    ];
+
+    for input in errors {
+        log::debug!("input = \"{input}\"");
+
+        assert!(rules::TomlRules.parse_entire("start", input).is_err());
+    }
+
+    // Documents which are parsed but aren't valid give the key or value at fault
+    let invalid = [
+        ("a = 1\na = 2", "Duplicate key: a at line 2, column 1"),
+        ("[a]\nb = 1\n[a]", "Duplicate key: a at line 3, column 2"),
+        ("[a.b]\n[a]\nb = 1", "Duplicate key: b at line 3, column 1"),
+        ("a.b = 1\n[a]", "Duplicate key: a at line 2, column 2"),
+        ("[a]\nb.c = 1\n[a.b]", "Duplicate key: b at line 3, column 4"),
+        ("a = {}\n[a.b]", "Duplicate key: a at line 2, column 2"),
+        ("a = [1]\n[[a]]", "Duplicate key: a at line 2, column 3"),
+        ("[[a]]\n[a]", "Duplicate key: a at line 2, column 2"),
+        ("a = { b = 1, b = 2 }", "Duplicate key: b at line 1, column 14"),
+        ("a = 1\na.b = 2", "Key is not a table: a at line 2, column 1"),
+        ("a = true\n[a.b]", "Key is not a table: a at line 2, column 2"),
+        ("a = 9223372036854775808", "Integer out of range: 9223372036854775808 at line 1, column 5"),
+        ("a = 0xffff_ffff_ffff_ffff", "Integer out of range: 0xffffffffffffffff at line 1, column 5"),
+        ("\na = 2023-02-29", "Invalid date-time: 2023-02-29 at line 2, column 5"),
+        ("a = 24:00:00", "Invalid date-time: 24:00:00 at line 1, column 5"),
+    ];
+
+    for (input, message) in invalid {
+        log::debug!("input = \"{input}\"");
+
+        match rules::TomlRules.parse_entire("start", input) {
+            Err(ParseError::TransformerError { error, .. }) => {
+                assert!(error.downcast_ref::<TomlParseError>().is_some());
+                assert_eq!(message, error.to_string());
+            }
+            result => panic!("Expected a TOML error, got {:?}", result.map(|_| ())),
+        }
+    }
+}
+
+#[test]
+fn csv() {
+    use rules::csv::{Csv, CsvDialect};
+    init();
+
+    let inputs = [
+        ("a,b,c\n1,2,3\n", vec![vec!["a", "b", "c"], vec!["1", "2", "3"]]),
+        ("a,b\r\n1,2", vec![vec!["a", "b"], vec!["1", "2"]]),
+        (
+            "\"quoted, with comma\",\"line\r\nbreak\",\"say \"\"hi\"\"\"\n",
+            vec![vec!["quoted, with comma", "line\r\nbreak", "say \"hi\""]],
+        ),
+        (",,\n\n\"\"", vec![vec!["", "", ""], vec![""], vec![""]]),
+        ("a\"b,c", vec![vec!["a\"b", "c"]]),
+        ("", vec![]),
+    ];
+
+    for (input, expected) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(
+            expected,
+            *rules::CsvRules
+                .parse_entire("start", input)
+                .unwrap()
+                .downcast::<Vec<Vec<String>>>()
+                .unwrap()
+        );
+    }
+
+    let errors = ["\"unterminated", "\"a\"b,c", "a,\"b\"\"\n"];
+
+    for input in errors {
+        log::debug!("input = \"{input}\"");
+
+        assert!(rules::CsvRules.parse_entire("start", input).is_err());
+    }
+
+    let tsv = CsvDialect::tsv().headers().parse("name\tage\nAda\t36\n\"Grace\tH.\"\t85\n").unwrap();
+
+    assert_eq!(Some(vec!["name".to_owned(), "age".to_owned()]), tsv.headers);
+    assert_eq!(vec![vec!["Ada", "36"], vec!["Grace\tH.", "85"]], tsv.rows);
+    assert_eq!(Some(1), tsv.column("age"));
+    assert_eq!(Some("85"), tsv.get(1, "age"));
+    assert_eq!(None, tsv.get(2, "age"));
+
+    let dialect = CsvDialect::new().delimiter(';').quote(Some('\'')).escape(Some('\\'));
+
+    assert_eq!(
+        vec![vec!["it's", "a;b", "c\\d"], vec!["e'f", "'"]],
+        dialect.parse("'it\\'s';a\\;b;c\\\\d\ne'f;''''").unwrap().rows
+    );
+
+    let unquoted = CsvDialect::new().quote(None).parse("\"a\",\"b\n").unwrap();
+
+    assert_eq!(vec![vec!["\"a\"", "\"b"]], unquoted.rows);
+    assert_eq!(Csv::default(), CsvDialect::new().headers().parse("").unwrap());
+}
+
+#[test]
+fn csv_rows() {
+    use rules::csv::CsvDialect;
+    init();
+
+    let mut rows = CsvDialect::new().headers().rows("id,name\n1,\"a\nb\"\n2,c\n3,\"d");
+
+    assert_eq!(None, rows.headers());
+    assert_eq!(vec!["1".to_owned(), "a\nb".to_owned()], rows.next().unwrap().unwrap());
+    assert_eq!(Some(&["id".to_owned(), "name".to_owned()][..]), rows.headers());
+    assert_eq!(vec!["2".to_owned(), "c".to_owned()], rows.next().unwrap().unwrap());
+
+    // The error has the position in the whole input
+    match rows.next() {
+        Some(Err(ParseError::UnexpectedChar { row, .. })) => assert_eq!(5, row),
+        result => panic!("Expected an unterminated field, got {result:?}"),
+    }
+
+    assert!(rows.next().is_none());
+
+    let rows = CsvDialect::new().rows("a\r\nb\r\n").collect::<Result<Vec<_>, _>>().unwrap();
+
+    assert_eq!(vec![vec!["a".to_owned()], vec!["b".to_owned()]], rows);
+}
+
+#[cfg(feature = "file_input")]
+#[test]
+fn csv_rows_file() {
+    use rules::csv::CsvDialect;
+    init();
+
+    let path = std::env::temp_dir().join(format!("psi_csv_rows_{}.csv", std::process::id()));
+    std::fs::write(&path, "x,y\n1,2\n3,4\n").unwrap();
+
+    let rows = CsvDialect::new()
+        .headers()
+        .rows(&path)
+        .map(|row| row.unwrap().join(" "))
+        .collect::<Vec<_>>();
+
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(vec!["1 2", "3 4"], rows);
+}
+
+// Parsing goes on from the returned input after the characters before it were discarded
+#[cfg(feature = "file_input")]
+#[test]
+fn parse_prefix_file() {
+    use crate::input::{Input, IntoInput};
+    init();
+
+    let rules = rules! {
+        letter {
+            ("a")
+            ("b")
+        }
+    };
+
+    let path = std::env::temp_dir().join(format!("psi_prefix_{}.txt", std::process::id()));
+    std::fs::write(&path, "ab").unwrap();
+
+    let input = path.as_path().into_input();
+    let first = input.clone();
+
+    let (_, input) = rules.parse_prefix("letter", input).unwrap();
+
+    input.discard_before();
+
+    assert!(rules.parse_prefix("letter", input.clone()).is_ok());
+    assert!(rules.parse_prefix("letter", first).is_err());
+
+    assert!(matches!(
+        rules.parse_prefix_with("letter", input, ParseOptions::new().max_steps(0)),
+        Err(ParseError::Aborted { .. })
+    ));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+// Each row is read as soon as it has been sent, before the stream is closed
+#[cfg(feature = "tcp_input")]
+#[test]
+fn csv_rows_tcp() {
+    use rules::csv::CsvDialect;
+    use std::io::Write;
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::mpsc;
+
+    init();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let (read, was_read) = mpsc::channel::<()>();
+
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+
+        for row in ["a,1\n", "b,2\n", "c,3\n"] {
+            stream.write_all(row.as_bytes()).unwrap();
+            stream.flush().unwrap();
+
+            was_read.recv().unwrap();
+        }
+    });
+
+    let mut rows = CsvDialect::new().rows(TcpStream::connect(address).unwrap());
+
+    for expected in ["a 1", "b 2", "c 3"] {
+        assert_eq!(expected, rows.next().unwrap().unwrap().join(" "));
+
+        read.send(()).unwrap();
+    }
+
+    server.join().unwrap();
+
+    assert!(rows.next().is_none());
+}
+
+#[test]
+fn grammar() {
+    use rules::grammar::{parse_grammar, Cst};
+    init();
+
+    let rules = parse_grammar(
+        r#"
+        // A list of greetings
+        start {
+            (greetings EOF)
+        }
+
+        #[policy(ordered)]
+        greetings {
+            (greeting)
+            (greetings ", " greeting)
+        }
+
+        greeting {
+            ((i "hello") " " name)
+            ("hi " (until ","))
+        }
+
+        /* Names can't start with a digit */
+        name {
+            ((! "0" "1" "2") (until ",") )
+        }
+
+        pair<a, b> {
+            (a b)
+        }
+
+        twice {
+            ((pair<"x", "y">) (pair<"x", "y">))
+        }
+        "#,
+    )
+    .expect("The grammar should be parsed");
+
+    let token = |token: &str| Cst::Token(token.to_owned());
+    let node = |rule: &str, children: Vec<Cst>| Cst::Node {
+        rule: rule.to_owned(),
+        children,
+    };
+
+    assert_eq!(
+        Some(&node(
+            "start",
+            vec![node(
+                "greetings",
+                vec![
+                    node(
+                        "greetings",
+                        vec![node(
+                            "greeting",
+                            vec![
+                                token("HELLO"),
+                                token(" "),
+                                node("name", vec![token("J"), token("ohn")]),
+                            ],
+                        )],
+                    ),
+                    token(", "),
+                    node("greeting", vec![token("hi "), token("there")]),
+                ],
+            )],
+        )),
+        rules
+            .parse_entire("start", "HELLO John, hi there")
+            .expect("Should be parsed")
+            .downcast_ref::<Cst>()
+    );
+
+    rules
+        .parse_entire("start", "hello 2pac")
+        .expect_err("Should not be parsed");
+
+    assert_eq!(
+        Some(&node(
+            "twice",
+            vec![
+                node("pair", vec![token("x"), token("y")]),
+                node("pair", vec![token("x"), token("y")]),
+            ],
+        )),
+        rules
+            .parse_entire("twice", "xyxy")
+            .expect("Should be parsed")
+            .downcast_ref::<Cst>()
+    );
+
+    let errors = [
+        // Missing closing parenthesis
+        "start { (\"a\" }",
+        // Missing alternatives
+        "start { }",
+        "#[policy(shortest)] start { (\"a\") }",
+        "start { (\"a\") } }",
+    ];
+
+    for grammar in errors {
+        log::debug!("grammar = \"{grammar}\"");
+
+        match parse_grammar(grammar) {
+            Ok(_) => panic!("The grammar should not be parsed"),
+            Err(error) => log::debug!("error = {error}"),
+        }
+    }
+
+    match parse_grammar("start {\n  (\"a\")\n}\n#[policy(shortest)] a { (\"a\") }") {
+        Err(ParseError::TransformerError { row, error, .. }) => {
+            assert_eq!(4, row);
+            assert_eq!(
+                "Unknown choice policy: shortest",
+                error.to_string()
+            );
+        }
+        result => panic!("Expected an unknown policy error, got {:?}", result.map(|_| ())),
+    }
+
+    match parse_grammar("start {\n  (\"a\")\n}\na { ((list<\"a\">)) }") {
+        Err(ParseError::TransformerError { row, error, .. }) => {
+            assert_eq!(4, row);
+            assert_eq!(
+                "No parameterized rule for 'list<\"a\">' (in rule 'a')",
+                error.to_string()
+            );
+        }
+        result => panic!("Expected an instance error, got {:?}", result.map(|_| ())),
+    }
 }
@@ -0,0 +1,200 @@
+use psi_parser::context::ContextError;
+use psi_parser::result::{AbortReason, LineInfo};
+
+use super::*;
+
+#[test]
+fn until() {
+    init();
+
+    let rules = rules! {
+        start {
+            ("<!--" (until "-->") "-->") => |v, _| v(1);
+        }
+    };
+
+    let inputs = [
+        ("<!---->", ""),
+        ("<!-- comment -->", " comment "),
+        ("<!-- a -- b -> c -->", " a -- b -> c "),
+    ];
+
+    for (input, expected_result) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(
+            Some(&Token::from(expected_result)),
+            rules
+                .parse_entire("start", input)
+                .expect("Should be parsed")
+                .downcast_ref()
+        );
+    }
+
+    assert!(rules.parse_entire("start", "<!-- unterminated").is_err());
+
+    // The limits are checked while reading, not only once the stop is found
+    let input = "<!--".to_owned() + &"x".repeat(100) + "-->";
+
+    assert!(matches!(
+        rules.parse_entire_with("start", &input, ParseOptions::new().max_input_length(50)),
+        Err(ParseError::Aborted {
+            reason: AbortReason::InputLength(50),
+            pos: 51,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn until_multiple() {
+    init();
+
+    let rules = rules! {
+        start {
+            ((until "," (i "end")))
+        }
+    };
+
+    let inputs = [
+        ("", ""),
+        ("abc", "abc"),
+        ("ab,c", "ab"),
+        ("abENDc", "ab"),
+        ("a,bendc", "a"),
+    ];
+
+    for (input, expected_result) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(
+            Some(&Token::from(expected_result)),
+            rules
+                .parse("start", input)
+                .expect("Should be parsed")
+                .downcast_ref()
+        );
+    }
+}
+
+#[test]
+fn until_rule() {
+    init();
+
+    let terminators = rules! {
+        terminator {
+            (";")
+            ("\n" "\n")
+        }
+    };
+
+    let rules = rules! {
+        #[import (terminators) as t]
+
+        start {
+            ((until (t::terminator)) (t::terminator)) => |v, _| v(0);
+        }
+    };
+
+    let inputs = [
+        ("let x = 1;", "let x = 1"),
+        ("let x\n= 1\n\n", "let x\n= 1"),
+    ];
+
+    for (input, expected_result) in inputs {
+        log::debug!("input = \"{input}\"");
+
+        assert_eq!(
+            Some(&Token::from(expected_result)),
+            rules
+                .parse_entire("start", input)
+                .expect("Should be parsed")
+                .downcast_ref()
+        );
+    }
+
+    // Checking the rule counts towards the limits of the parse, and is seen by its observer
+    let input = "x".repeat(100) + ";";
+
+    assert!(matches!(
+        rules.parse_entire_with("start", &input, ParseOptions::new().max_steps(50)),
+        Err(ParseError::Aborted {
+            reason: AbortReason::Steps(50),
+            ..
+        })
+    ));
+
+    #[derive(Default)]
+    struct Entered(Vec<String>);
+
+    impl ParseObserver for Entered {
+        fn rule_enter(&mut self, rule: &str, _at: LineInfo) {
+            self.0.push(rule.to_owned());
+        }
+    }
+
+    let mut entered = Entered::default();
+
+    rules
+        .parse_entire_with("start", "ab;", ParseOptions::new().observer(&mut entered))
+        .expect("Should be parsed");
+
+    // Once at each of the 3 positions, and once after the run
+    assert_eq!(
+        4,
+        entered
+            .0
+            .iter()
+            .filter(|rule| *rule == "t::terminator")
+            .count()
+    );
+
+    let rules = rules! {
+        start {
+            ((until missing))
+        }
+    };
+
+    assert!(matches!(
+        rules.parse("start", "abc"),
+        Err(ParseError::RuleNotFound { .. })
+    ));
+}
+
+#[test]
+fn until_rule_context() {
+    init();
+
+    let rules = rules! {
+        start {
+            ((until stop))
+        }
+
+        stop {
+            (";") => mut |_, _, count: &mut usize| {
+                *count += 1;
+
+                (*count).into_value()
+            };
+        }
+    };
+
+    // The stop gets the context, but as it isn't part of the match, its changes are undone
+    let mut count = 0usize;
+
+    assert_eq!(
+        Some(&Token::from("ab")),
+        rules
+            .parse_with_context("start", "ab;cd", &mut count)
+            .expect("Should be parsed")
+            .downcast_ref()
+    );
+    assert_eq!(0, count);
+
+    match rules.parse("start", "ab;cd") {
+        Err(ParseError::TransformerError { error, .. }) => {
+            assert!(error.is::<ContextError>());
+        }
+        result => panic!("Expected a ContextError, got {result:?}"),
+    }
+}